@@ -0,0 +1,52 @@
+// minimal bot: fires at every other tile, then at the rest
+//
+//     cargo run -p bslib --example checkerboard_bot -- 127.0.0.1:8000
+
+use bslib::bot::error::BotError;
+use bslib::bot::{Bot, BotRunner};
+use bslib::game::ruleset::Ruleset;
+use bslib::game::ShotResult;
+use bslib::game_record::PlacedShip;
+use bslib::placement::{random_fleet, PlacementMode};
+use tokio::net::TcpStream;
+
+struct CheckerboardBot {
+    targets: Vec<[usize; 2]>,
+    hits: usize,
+}
+impl Bot for CheckerboardBot {
+    fn place_fleet(&mut self, ruleset: &Ruleset) -> Result<Vec<PlacedShip>, BotError> {
+        let [height, width] = ruleset.get_size();
+        let tiles = (0..height).flat_map(|i| (0..width).map(move |j| [i, j]));
+        let (black, white): (Vec<_>, Vec<_>) = tiles.partition(|[i, j]| (i + j) % 2 == 0);
+        // popped from the end, so black tiles go last
+        self.targets = white.into_iter().chain(black).rev().collect();
+        self.hits = 0;
+        Ok(random_fleet(ruleset, PlacementMode::Uniform, &mut rand::thread_rng())?.get_ships().to_vec())
+    }
+
+    fn choose_shot(&mut self) -> [usize; 2] {
+        self.targets.pop().unwrap_or_default()
+    }
+
+    fn on_result(&mut self, _target: [usize; 2], result: ShotResult) {
+        if result != ShotResult::Miss {
+            self.hits += 1;
+        }
+    }
+
+    fn on_game_over(&mut self, won: bool) {
+        println!("game over - won: {}, hits: {}", won, self.hits);
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| String::from("127.0.0.1:8000"));
+    let bot = CheckerboardBot { targets: Vec::new(), hits: 0 };
+    let mut runner = BotRunner::new(bot, "checkerboard").games(Some(3));
+    match runner.run(|| TcpStream::connect(addr.clone())).await {
+        Ok(summary) => println!("{summary}"),
+        Err(e) => println!("{e}"),
+    }
+}
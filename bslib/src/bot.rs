@@ -0,0 +1,397 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+use error::BotError;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::io;
+
+use crate::game::ruleset::Ruleset;
+use crate::game::ShotResult;
+use crate::game_record::PlacedShip;
+use crate::placement::{random_fleet, PlacementMode};
+use crate::strategy::{fill_salvo, Difficulty, Strategy};
+use crate::tcp_protocol::{
+    ConnectBody, Connection, FireBody, Packet, PacketBody, PlaceFleetBody, Requester, SalvoBody,
+};
+
+pub mod error;
+
+// player that makes its own decisions - `BotRunner` takes care of talking to the server
+pub trait Bot: Send {
+    // ships of the ruleset's fleet for a new game, it's also the place to reset whatever was
+    // learned in the last one - an error when the fleet can't be placed at all
+    fn place_fleet(&mut self, ruleset: &Ruleset) -> Result<Vec<PlacedShip>, BotError>;
+    fn choose_shot(&mut self) -> [usize; 2];
+    // targets of a salvo, all chosen before any of them is fired
+    fn choose_salvo(&mut self, count: usize) -> Vec<[usize; 2]> {
+        (0..count).map(|_| self.choose_shot()).collect()
+    }
+    // result of the bot's own shot
+    fn on_result(&mut self, _target: [usize; 2], _result: ShotResult) {}
+    fn on_opponent_shot(&mut self, _target: [usize; 2], _result: ShotResult) {}
+    fn on_game_over(&mut self, _won: bool) {}
+}
+
+// bot made of a targeting strategy and a placement mode
+pub struct StrategyBot {
+    difficulty: Difficulty,
+    placement: PlacementMode,
+    strategy: Box<dyn Strategy>,
+    rng: StdRng,
+    // board size and the tiles fired at in this game, for when the strategy runs out of tiles
+    size: [usize; 2],
+    fired: Vec<[usize; 2]>,
+}
+impl StrategyBot {
+    pub fn new(difficulty: Difficulty, placement: PlacementMode) -> Self {
+        Self {
+            difficulty,
+            placement,
+            strategy: difficulty.strategy(&Ruleset::default()),
+            rng: StdRng::from_entropy(),
+            size: Ruleset::default().get_size(),
+            fired: Vec::new(),
+        }
+    }
+
+    // same seed, same placements and shots against the same opponent
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+}
+impl Bot for StrategyBot {
+    fn place_fleet(&mut self, ruleset: &Ruleset) -> Result<Vec<PlacedShip>, BotError> {
+        self.strategy = self.difficulty.strategy(ruleset);
+        self.size = ruleset.get_size();
+        self.fired.clear();
+        Ok(random_fleet(ruleset, self.placement, &mut self.rng)?.get_ships().to_vec())
+    }
+
+    // a strategy can rule out every tile it hasn't fired at, e.g. when it got the adjacency
+    // wrong - the first of those is still a legal shot, and with every tile fired at the game
+    // is already over
+    fn choose_shot(&mut self) -> [usize; 2] {
+        if let Some(target) = self.strategy.choose_shot(&mut self.rng) {
+            return target;
+        }
+        let [height, width] = self.size;
+        let mut tiles = (0..height).flat_map(|i| (0..width).map(move |j| [i, j]));
+        tiles.find(|tile| !self.fired.contains(tile)).unwrap_or_default()
+    }
+
+    fn choose_salvo(&mut self, count: usize) -> Vec<[usize; 2]> {
+        let mut targets = self.strategy.choose_salvo(count, &mut self.rng);
+        fill_salvo(&mut targets, count, self.size, &self.fired);
+        targets
+    }
+
+    fn on_result(&mut self, target: [usize; 2], result: ShotResult) {
+        self.fired.push(target);
+        self.strategy.on_result(target, result);
+    }
+}
+
+// games played by a runner, and how many times it had to connect again
+#[derive(Debug, Default, Clone)]
+pub struct Summary {
+    games: usize,
+    wins: usize,
+    reconnects: usize,
+    // what went wrong the last time a game was cut short
+    last_error: Option<String>,
+}
+impl Summary {
+    pub fn get_games(&self) -> usize {
+        self.games
+    }
+
+    pub fn get_wins(&self) -> usize {
+        self.wins
+    }
+
+    pub fn get_reconnects(&self) -> usize {
+        self.reconnects
+    }
+
+    pub fn get_last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "won {} of {} games", self.wins, self.games)?;
+        match &self.last_error {
+            Some(e) => write!(f, " (reconnected {} times, last after: {})", self.reconnects, e),
+            None => Ok(()),
+        }
+    }
+}
+
+// plays games on a server one after another, connecting again when a game is cut short
+pub struct BotRunner<B: Bot> {
+    bot: B,
+    nick: String,
+    ruleset: Option<String>,
+    games: Option<usize>,
+    reconnect_attempts: usize,
+    reconnect_delay: Duration,
+}
+impl<B: Bot> BotRunner<B> {
+    pub fn new(bot: B, nick: &str) -> Self {
+        Self {
+            bot,
+            nick: String::from(nick),
+            ruleset: None,
+            games: Some(1),
+            reconnect_attempts: 3,
+            reconnect_delay: Duration::from_secs(2),
+        }
+    }
+
+    // preset to ask the server for, None plays with the server's default
+    pub fn ruleset(mut self, ruleset: Option<String>) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    // number of games to play, None keeps playing until the server can't be reached
+    pub fn games(mut self, games: Option<usize>) -> Self {
+        self.games = games;
+        self
+    }
+
+    // how many times in a row connecting may fail before the runner gives up
+    pub fn reconnect(mut self, attempts: usize, delay: Duration) -> Self {
+        self.reconnect_attempts = attempts;
+        self.reconnect_delay = delay;
+        self
+    }
+
+    pub fn get_bot(&self) -> &B {
+        &self.bot
+    }
+
+    // `connect` opens a new connection for every game, so it can be tcp or a unix socket
+    pub async fn run<S, F, Fut>(&mut self, mut connect: F) -> Result<Summary, BotError>
+    where
+        S: Connection,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<S>>,
+    {
+        let mut summary = Summary::default();
+        let mut failures = 0;
+        while self.games.is_none_or(|games| summary.games < games) {
+            let result = match connect().await {
+                Ok(stream) => self.play_game(Requester::new(stream)).await,
+                Err(e) => Err(BotError::from(e)),
+            };
+            match result {
+                Ok(won) => {
+                    failures = 0;
+                    summary.games += 1;
+                    summary.wins += won as usize;
+                }
+                Err(e) => {
+                    failures += 1;
+                    if failures > self.reconnect_attempts {
+                        return Err(e);
+                    }
+                    summary.reconnects += 1;
+                    summary.last_error = Some(e.to_string());
+                    tokio::time::sleep(self.reconnect_delay).await;
+                }
+            }
+        }
+        Ok(summary)
+    }
+
+    // one game from connecting to game over, returns whether the bot won
+    async fn play_game<S: Connection>(&mut self, mut requester: Requester<S>) -> Result<bool, BotError> {
+        let body = ConnectBody::new(self.nick.clone()).with_ruleset(self.ruleset.clone());
+        let body = PacketBody::Connect(Box::new(body));
+        requester.send(Packet::from_body(body)).await?;
+        // shots in the bot's next salvo, stays 0 in games without salvos
+        let mut salvo = 0;
+        loop {
+            let Some(packet) = requester.receive().await? else {
+                return Err(BotError::new("Server has closed the connection"));
+            };
+            let your_turn = match packet.get_body()? {
+                PacketBody::ConnectResp(body) if body.get_opponent() => {
+                    let ships = self.bot.place_fleet(body.get_ruleset())?;
+                    let body = PacketBody::PlaceFleet(Box::new(PlaceFleetBody::new(ships)));
+                    requester.send(Packet::from_body(body)).await?;
+                    false
+                }
+                PacketBody::GameStart(body) => {
+                    salvo = body.get_salvo();
+                    body.is_your_turn()
+                }
+                PacketBody::FireResult(body) => {
+                    match body.is_yours() {
+                        true => self.bot.on_result(body.get_target(), body.get_result()),
+                        false => self.bot.on_opponent_shot(body.get_target(), body.get_result()),
+                    }
+                    body.is_your_turn()
+                }
+                PacketBody::SalvoResult(body) => {
+                    for &(target, result) in body.get_shots() {
+                        match body.is_yours() {
+                            true => self.bot.on_result(target, result),
+                            false => self.bot.on_opponent_shot(target, result),
+                        }
+                    }
+                    salvo = body.get_salvo();
+                    body.is_your_turn()
+                }
+                // bots don't use special weapons, but their opponents might
+                PacketBody::WeaponResult(body) => {
+                    for &(target, result) in body.get_strike().get_shots() {
+                        match body.is_yours() {
+                            true => self.bot.on_result(target, result),
+                            false => self.bot.on_opponent_shot(target, result),
+                        }
+                    }
+                    body.is_your_turn()
+                }
+                // bots never scan with a radar, and a moved ship was never hit, so they learn nothing
+                PacketBody::MoveResult(body) => body.is_your_turn(),
+                PacketBody::PlayerLeft(body) => {
+                    salvo = body.get_salvo();
+                    body.is_your_turn()
+                }
+                PacketBody::GameOver(body) => {
+                    self.bot.on_game_over(body.is_won());
+                    return Ok(body.is_won());
+                }
+                PacketBody::Error(body) => {
+                    return Err(BotError::new(&format!("Move rejected: {}", body.get_msg())))
+                }
+                PacketBody::Shutdown(body) => {
+                    return Err(BotError::new(&format!("Server is shutting down: {}", body.get_reason())))
+                }
+                _ => false,
+            };
+            if !your_turn {
+                continue;
+            }
+            let body = match salvo {
+                0 => PacketBody::Fire(Box::new(FireBody::new(self.bot.choose_shot()))),
+                count => PacketBody::Salvo(Box::new(SalvoBody::new(self.bot.choose_salvo(count)))),
+            };
+            requester.send(Packet::from_body(body)).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ShipType;
+    use crate::tcp_protocol::{
+        ConnectRespBody, FireResultBody, GameOverBody, GameStartBody, PacketReader, Ready,
+    };
+    use tokio::io::{AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+    // fires along the first row
+    struct RowBot {
+        next: usize,
+        results: Vec<ShotResult>,
+    }
+    impl Bot for RowBot {
+        fn place_fleet(&mut self, ruleset: &Ruleset) -> Result<Vec<PlacedShip>, BotError> {
+            self.next = 0;
+            let mut rng = StdRng::seed_from_u64(1);
+            Ok(random_fleet(ruleset, PlacementMode::Uniform, &mut rng)?.get_ships().to_vec())
+        }
+
+        fn choose_shot(&mut self) -> [usize; 2] {
+            self.next += 1;
+            [0, self.next - 1]
+        }
+
+        fn on_result(&mut self, _target: [usize; 2], result: ShotResult) {
+            self.results.push(result);
+        }
+    }
+
+    async fn send(stream: &mut WriteHalf<DuplexStream>, body: PacketBody) {
+        let bytes = Packet::from_body(body).as_bytes().expect("failed to serialize a packet");
+        stream.write_all(&bytes).await.expect("failed to send a packet");
+    }
+
+    async fn receive(reader: &mut PacketReader<ReadHalf<DuplexStream>>) -> Packet<Ready> {
+        reader
+            .read_packet()
+            .await
+            .expect("failed to read a packet")
+            .expect("bot closed the connection")
+    }
+
+    // plays the server's part of a short game where the bot sinks a destroyer in two shots
+    async fn fake_server(stream: DuplexStream) {
+        let (read_half, mut write_half) = tokio::io::split(stream);
+        let mut reader = PacketReader::new(BufReader::new(read_half));
+        assert!(matches!(receive(&mut reader).await.get_body(), Ok(PacketBody::Connect(_))));
+        let joined = ConnectRespBody::with_opponent(String::from("tester"), false);
+        send(&mut write_half, PacketBody::ConnectResp(Box::new(joined))).await;
+        assert!(matches!(receive(&mut reader).await.get_body(), Ok(PacketBody::PlaceFleet(_))));
+        send(&mut write_half, PacketBody::GameStart(Box::new(GameStartBody::new(true)))).await;
+        let results = [ShotResult::Hit, ShotResult::Sunk(ShipType::Destroyer)];
+        for (shot, result) in results.into_iter().enumerate() {
+            let packet = receive(&mut reader).await;
+            let Ok(PacketBody::Fire(body)) = packet.get_body() else {
+                panic!("expected a shot, got {packet:?}");
+            };
+            assert_eq!(body.get_target(), [0, shot]);
+            let body = FireResultBody::new(true, body.get_target(), result, shot == 0);
+            send(&mut write_half, PacketBody::FireResult(Box::new(body))).await;
+        }
+        let body = GameOverBody::new(true, String::from("tester's fleet is gone"));
+        send(&mut write_half, PacketBody::GameOver(Box::new(body))).await;
+    }
+
+    #[test]
+    fn test_strategy_bot_falls_back_to_unshot_tiles() {
+        let mut bot = StrategyBot::new(Difficulty::HuntTarget, PlacementMode::Uniform).with_seed(0);
+        bot.place_fleet(&Ruleset::classic()).expect("classic fleet should fit");
+        // the sunk destroyer rules out the tiles around it, everything else was a miss
+        bot.on_result([0, 0], ShotResult::Hit);
+        bot.on_result([0, 1], ShotResult::Sunk(ShipType::Destroyer));
+        let around = [[0, 2], [1, 0], [1, 1], [1, 2]];
+        for i in 0..10 {
+            for j in 0..10 {
+                if ![[0, 0], [0, 1]].contains(&[i, j]) && !around.contains(&[i, j]) {
+                    bot.on_result([i, j], ShotResult::Miss);
+                }
+            }
+        }
+        assert_eq!(bot.choose_shot(), [0, 2]);
+        let mut salvo = bot.choose_salvo(4);
+        salvo.sort();
+        assert_eq!(salvo, around);
+    }
+
+    #[tokio::test]
+    async fn test_runner_plays_a_game() {
+        let (client, server) = tokio::io::duplex(4096);
+        let server = tokio::spawn(fake_server(server));
+
+        let bot = RowBot { next: 0, results: Vec::new() };
+        let mut runner = BotRunner::new(bot, "row").reconnect(0, Duration::ZERO);
+        let mut stream = Some(client);
+        let summary = runner
+            .run(|| {
+                let stream = stream.take().ok_or(io::Error::other("only one connection"));
+                async move { stream }
+            })
+            .await
+            .expect("runner failed");
+        server.await.expect("fake server failed");
+        assert_eq!(summary.get_wins(), 1);
+        assert_eq!(runner.get_bot().results.len(), 2);
+    }
+}
@@ -0,0 +1,51 @@
+use std::fmt::Display;
+use tokio::io;
+
+use crate::game::error::GameError;
+use crate::tcp_protocol::error::{PacketError, RequestError};
+
+#[derive(Debug)]
+pub struct BotError {
+    msg: String,
+}
+impl BotError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for BotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BotError: {}", self.msg)
+    }
+}
+impl std::convert::From<io::Error> for BotError {
+    fn from(value: io::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::convert::From<RequestError> for BotError {
+    fn from(value: RequestError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::convert::From<PacketError> for BotError {
+    fn from(value: PacketError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::convert::From<GameError> for BotError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::error::Error for BotError {}
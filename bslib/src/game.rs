@@ -0,0 +1,664 @@
+use board::Board;
+use error::GameError;
+use movement::Direction;
+use serde::{Deserialize, Serialize};
+use shape::Shape;
+use weapon::{Arsenal, Strike, Weapon};
+use std::fmt::Display;
+
+use crate::game_record::GameRecord;
+
+pub mod board;
+pub mod error;
+pub mod movement;
+pub mod ruleset;
+pub mod shape;
+pub mod weapon;
+
+// ships every player gets at the start of a game
+pub const FLEET: [ShipType; 5] = [
+    ShipType::Carrier,
+    ShipType::Battleship,
+    ShipType::Cruiser,
+    ShipType::Submarine,
+    ShipType::Destroyer,
+];
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ShipType {
+    Carrier,
+    Battleship,
+    Cruiser,
+    Submarine,
+    Destroyer,
+    PatrolBoat,
+    // ships that aren't straight, played with the house rules
+    LShip,
+    TShip,
+    PlusShip,
+}
+impl ShipType {
+    pub fn get_size(&self) -> u8 {
+        const CARRIER_SIZE: u8 = 5;
+        const BATTLESHIP_SIZE: u8 = 4;
+        const CRUISER_SIZE: u8 = 3;
+        const SUBMARINE_SIZE: u8 = 3;
+        const DESTROYER_SIZE: u8 = 2;
+        const PATROL_BOAT_SIZE: u8 = 1;
+        const L_SHIP_SIZE: u8 = 4;
+        const T_SHIP_SIZE: u8 = 4;
+        const PLUS_SHIP_SIZE: u8 = 5;
+
+        match self {
+            ShipType::Carrier => CARRIER_SIZE,
+            ShipType::Battleship => BATTLESHIP_SIZE,
+            ShipType::Cruiser => CRUISER_SIZE,
+            ShipType::Submarine => SUBMARINE_SIZE,
+            ShipType::Destroyer => DESTROYER_SIZE,
+            ShipType::PatrolBoat => PATROL_BOAT_SIZE,
+            ShipType::LShip => L_SHIP_SIZE,
+            ShipType::TShip => T_SHIP_SIZE,
+            ShipType::PlusShip => PLUS_SHIP_SIZE,
+        }
+    }
+
+    // tiles the ship takes before it's rotated or mirrored
+    pub fn get_shape(&self) -> Shape {
+        match self {
+            ShipType::LShip => Shape::new(&[[0, 0], [1, 0], [2, 0], [2, 1]]),
+            ShipType::TShip => Shape::new(&[[0, 0], [0, 1], [0, 2], [1, 1]]),
+            ShipType::PlusShip => Shape::new(&[[0, 1], [1, 0], [1, 1], [1, 2], [2, 1]]),
+            _ => Shape::line(self.get_size() as usize),
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        const CARRIER_NAME: &str = "carrier";
+        const BATTLESHIP_NAME: &str = "battleship";
+        const CRUISER_NAME: &str = "cruiser";
+        const SUBMARINE_NAME: &str = "submarine";
+        const DESTROYER_NAME: &str = "destroyer";
+        const PATROL_BOAT_NAME: &str = "patrol boat";
+        const L_SHIP_NAME: &str = "L-ship";
+        const T_SHIP_NAME: &str = "T-ship";
+        const PLUS_SHIP_NAME: &str = "plus-ship";
+
+        match self {
+            ShipType::Carrier => CARRIER_NAME,
+            ShipType::Battleship => BATTLESHIP_NAME,
+            ShipType::Cruiser => CRUISER_NAME,
+            ShipType::Submarine => SUBMARINE_NAME,
+            ShipType::Destroyer => DESTROYER_NAME,
+            ShipType::PatrolBoat => PATROL_BOAT_NAME,
+            ShipType::LShip => L_SHIP_NAME,
+            ShipType::TShip => T_SHIP_NAME,
+            ShipType::PlusShip => PLUS_SHIP_NAME,
+        }
+    }
+}
+impl Display for ShipType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub struct Ship {
+    ship_type: ShipType,
+    size: u8,
+    name: &'static str,
+    destroyed: bool,
+}
+impl Ship {
+    pub fn new(ship_type: ShipType) -> Self {
+        Ship {
+            ship_type,
+            size: ship_type.get_size(),
+            name: ship_type.get_name(),
+            destroyed: false,
+        }
+    }
+
+    pub fn get_type(&self) -> ShipType {
+        self.ship_type
+    }
+
+    pub fn get_size(&self) -> u8 {
+        self.size
+    }
+}
+impl Display for Ship {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ShotResult {
+    Miss,
+    Hit,
+    Sunk(ShipType),
+}
+impl Display for ShotResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShotResult::Miss => write!(f, "miss"),
+            ShotResult::Hit => write!(f, "hit"),
+            ShotResult::Sunk(ship_type) => write!(f, "{} sunk", ship_type),
+        }
+    }
+}
+
+// rules of a single game between two players or teams - used both by the server and local games
+#[derive(Debug)]
+pub struct Game {
+    boards: Vec<Board>,
+    turn: usize,
+    // team that won - in a duel every player is a team of their own
+    winner: Option<usize>,
+    record: GameRecord,
+    arsenals: Vec<Arsenal>,
+    // players that left before the game was over, they sit it out like eliminated ones
+    forfeited: Vec<bool>,
+}
+impl Game {
+    // `first` is the index of the player that shoots first
+    pub fn new(players: Vec<String>, boards: Vec<Board>, first: usize) -> Self {
+        Self::from_record(GameRecord::new(players), boards, first)
+    }
+
+    // start a game whose record already has the players filled in, with a board for every seat
+    // of its ruleset
+    pub fn from_record(mut record: GameRecord, boards: Vec<Board>, first: usize) -> Self {
+        for (player, board) in boards.iter().enumerate() {
+            for ship in board.get_ships() {
+                record.add_ship(player, ship.clone());
+            }
+        }
+        let arsenal = match record.get_ruleset().has_weapons() {
+            true => Arsenal::full(),
+            false => Arsenal::default(),
+        };
+        Self {
+            arsenals: vec![arsenal; boards.len()],
+            forfeited: vec![false; boards.len()],
+            boards,
+            turn: first,
+            winner: None,
+            record,
+        }
+    }
+
+    pub fn get_turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn get_winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    pub fn get_team(&self, player: usize) -> usize {
+        self.record.get_ruleset().get_team(player)
+    }
+
+    // whether the player is on the team that won
+    pub fn is_winner(&self, player: usize) -> bool {
+        self.winner == Some(self.get_team(player))
+    }
+
+    // a player without ships left, or one that left, sits out the rest of the game
+    pub fn is_eliminated(&self, player: usize) -> bool {
+        self.forfeited[player] || self.boards[player].is_fleet_destroyed()
+    }
+
+    // players of the other teams that still have ships to fire at
+    pub fn get_opponents(&self, player: usize) -> Vec<usize> {
+        (0..self.boards.len())
+            .filter(|&other| self.get_team(other) != self.get_team(player) && !self.is_eliminated(other))
+            .collect()
+    }
+
+    pub fn get_board(&self, player: usize) -> &Board {
+        &self.boards[player]
+    }
+
+    pub fn get_record(&self) -> &GameRecord {
+        &self.record
+    }
+
+    // special weapons the player can still use
+    pub fn get_arsenal(&self, player: usize) -> Arsenal {
+        self.arsenals[player]
+    }
+
+    // shots the player fires in their turn - one without salvos, otherwise one for every ship
+    // still afloat, but never more than any opponent has tiles left to fire at, so the salvo
+    // doesn't depend on whom it's fired at
+    pub fn get_salvo_size(&self, player: usize) -> usize {
+        if !self.record.get_ruleset().is_salvo() {
+            return 1;
+        }
+        let opponents = self.get_opponents(player).into_iter();
+        let unshot = opponents.map(|opponent| self.boards[opponent].count_unshot_tiles());
+        self.boards[player].count_ships_afloat().min(unshot.min().unwrap_or_default())
+    }
+
+    pub fn fire(&mut self, player: usize, opponent: usize, target: [usize; 2]) -> Result<ShotResult, GameError> {
+        self.check_turn(player)?;
+        self.check_opponent(player, opponent)?;
+        if self.record.get_ruleset().is_salvo() {
+            return Err(GameError::new("Shots have to be fired in a salvo in this game"));
+        }
+        let result = self.boards[opponent].fire(target)?;
+        self.record.add_shot(player, opponent, target);
+        self.end_turn(player, result != ShotResult::Miss);
+        Ok(result)
+    }
+
+    // all shots of a salvo are checked before any of them is fired, so a salvo that breaks
+    // the rules changes nothing
+    pub fn fire_salvo(
+        &mut self,
+        player: usize,
+        opponent: usize,
+        targets: &[[usize; 2]],
+    ) -> Result<Vec<ShotResult>, GameError> {
+        self.check_turn(player)?;
+        self.check_opponent(player, opponent)?;
+        if !self.record.get_ruleset().is_salvo() {
+            return Err(GameError::new("This game isn't played with salvos"));
+        }
+        let size = self.get_salvo_size(player);
+        if targets.len() != size {
+            return Err(GameError::new(&format!("Salvo has to have exactly {size} shots")));
+        }
+        let board = &mut self.boards[opponent];
+        for (index, &target) in targets.iter().enumerate() {
+            if !board.contains(target) {
+                return Err(GameError::new("Target is outside of the board"));
+            }
+            if board.get_tile(target).is_shot() || targets[..index].contains(&target) {
+                return Err(GameError::new("This tile was already shot"));
+            }
+        }
+        let mut results = Vec::with_capacity(targets.len());
+        for &target in targets {
+            results.push(board.fire(target)?);
+            self.record.add_shot(player, opponent, target);
+        }
+        self.end_turn(player, results.iter().any(|&result| result != ShotResult::Miss));
+        Ok(results)
+    }
+
+    // a special weapon used instead of the turn's shot - one that wouldn't fire at anything new
+    // is rejected before it's used up
+    pub fn use_weapon(
+        &mut self,
+        player: usize,
+        opponent: usize,
+        weapon: Weapon,
+        target: [usize; 2],
+    ) -> Result<Strike, GameError> {
+        self.check_turn(player)?;
+        self.check_opponent(player, opponent)?;
+        let board = &self.boards[opponent];
+        if !board.contains(target) {
+            return Err(GameError::new("Target is outside of the board"));
+        }
+        let area = weapon.area(target, board.get_size());
+        let targets = match weapon {
+            Weapon::Radar => Vec::new(),
+            Weapon::Bombardment => area.iter().copied().filter(|&tile| !board.get_tile(tile).is_shot()).collect(),
+            Weapon::Torpedo => {
+                let mut targets = Vec::new();
+                for &tile in &area {
+                    let state = board.get_tile(tile);
+                    // wrecks stop it as well, shot water doesn't
+                    if state.is_shot() && state.has_ship() {
+                        break;
+                    }
+                    if !state.is_shot() {
+                        targets.push(tile);
+                    }
+                    if state.has_ship() {
+                        break;
+                    }
+                }
+                targets
+            }
+        };
+        if weapon != Weapon::Radar && targets.is_empty() {
+            return Err(GameError::new(&format!("The {weapon} wouldn't hit any tile that wasn't shot")));
+        }
+        self.arsenals[player].take(weapon)?;
+
+        let board = &mut self.boards[opponent];
+        let strike = match weapon {
+            Weapon::Radar => Strike::scan(area.into_iter().any(|tile| {
+                let state = board.get_tile(tile);
+                state.has_ship() && !state.is_shot()
+            })),
+            _ => {
+                let mut shots = Vec::with_capacity(targets.len());
+                for target in targets {
+                    shots.push((target, board.fire(target)?));
+                    self.record.add_shot(player, opponent, target);
+                }
+                Strike::new(shots)
+            }
+        };
+        self.end_turn(player, strike.is_hit());
+        Ok(strike)
+    }
+
+    // a move of one of the player's own undamaged ships instead of the turn's shot
+    pub fn move_ship(&mut self, player: usize, tile: [usize; 2], direction: Direction) -> Result<(), GameError> {
+        self.check_turn(player)?;
+        if !self.record.get_ruleset().is_moving() {
+            return Err(GameError::new("Ships can't be moved in this game"));
+        }
+        self.boards[player].move_ship(tile, direction)?;
+        self.record.add_move(player, tile, direction);
+        self.end_turn(player, false);
+        Ok(())
+    }
+
+    // a player that leaves is out of the game, which goes on while more than one team has
+    // players left - the last team standing wins
+    pub fn forfeit(&mut self, player: usize) {
+        if self.winner.is_some() {
+            return;
+        }
+        self.forfeited[player] = true;
+        let mut teams = (0..self.boards.len())
+            .filter(|&other| !self.is_eliminated(other))
+            .map(|other| self.get_team(other));
+        let Some(team) = teams.next() else {
+            return;
+        };
+        if teams.all(|other| other == team) {
+            self.winner = Some(team);
+        } else if self.turn == player {
+            self.turn = self.next_turn(player);
+        }
+    }
+
+    fn check_turn(&self, player: usize) -> Result<(), GameError> {
+        if self.winner.is_some() {
+            return Err(GameError::new("The game is already over"));
+        }
+        if player != self.turn {
+            return Err(GameError::new("It's not your turn"));
+        }
+        Ok(())
+    }
+
+    fn check_opponent(&self, player: usize, opponent: usize) -> Result<(), GameError> {
+        match self.get_opponents(player).contains(&opponent) {
+            true => Ok(()),
+            false => Err(GameError::new("There's no opponent to fire at on that seat")),
+        }
+    }
+
+    // the game is won once the player's team is the only one with ships left, otherwise the
+    // turn goes to the next seat that isn't eliminated - unless the extra shot rule gives the
+    // player another turn for a hit
+    fn end_turn(&mut self, player: usize, hit: bool) {
+        if self.get_opponents(player).is_empty() {
+            self.winner = Some(self.get_team(player));
+        } else if !(hit && self.record.get_ruleset().is_extra_shot()) {
+            self.turn = self.next_turn(player);
+        }
+    }
+
+    // the next seat after the player's that isn't eliminated
+    fn next_turn(&self, player: usize) -> usize {
+        let players = self.boards.len();
+        let mut next = (1..players).map(|offset| (player + offset) % players);
+        next.find(|&other| !self.is_eliminated(other)).unwrap_or(player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Ruleset;
+
+    fn board_with_destroyer(tiles: Vec<[usize; 2]>) -> Board {
+        let mut board = Board::new();
+        board
+            .place_ship(ShipType::Destroyer, tiles)
+            .expect("failed to place a ship");
+        board
+    }
+
+    #[test]
+    fn test_turns_and_winner() {
+        let boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[9, 8], [9, 9]]),
+        ];
+        let mut game = Game::new(vec![String::from("a"), String::from("b")], boards, 1);
+        assert!(game.fire(0, 1, [0, 0]).is_err());
+        assert_eq!(game.fire(1, 0, [0, 0]).expect("shot failed"), ShotResult::Hit);
+        assert_eq!(game.fire(0, 1, [5, 5]).expect("shot failed"), ShotResult::Miss);
+        assert_eq!(
+            game.fire(1, 0, [0, 1]).expect("shot failed"),
+            ShotResult::Sunk(ShipType::Destroyer)
+        );
+        assert_eq!(game.get_winner(), Some(1));
+        assert!(game.fire(0, 1, [9, 9]).is_err());
+        assert_eq!(game.get_record().get_shots().len(), 3);
+    }
+
+    #[test]
+    fn test_salvo() {
+        let ruleset = Ruleset::classic().with_salvo(true);
+        let mut boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[9, 8], [9, 9]]),
+        ];
+        boards[1]
+            .place_ship(ShipType::Cruiser, vec![[5, 0], [5, 1], [5, 2]])
+            .expect("failed to place a ship");
+        let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+        record.set_ruleset(ruleset);
+        let mut game = Game::from_record(record, boards, 1);
+        assert!(game.fire(1, 0, [0, 0]).is_err());
+        assert_eq!(game.get_salvo_size(1), 2);
+        // wrong number of shots, and the same tile twice - nothing gets fired
+        assert!(game.fire_salvo(1, 0, &[[0, 0]]).is_err());
+        assert!(game.fire_salvo(1, 0, &[[0, 0], [0, 0]]).is_err());
+        assert!(game.get_record().get_shots().is_empty());
+
+        let results = game.fire_salvo(1, 0, &[[0, 0], [3, 3]]).expect("salvo failed");
+        assert_eq!(results, [ShotResult::Hit, ShotResult::Miss]);
+        assert_eq!(game.get_turn(), 0);
+        assert_eq!(game.get_salvo_size(0), 1);
+        game.fire_salvo(0, 1, &[[5, 0]]).expect("salvo failed");
+        let results = game.fire_salvo(1, 0, &[[0, 1], [4, 4]]).expect("salvo failed");
+        assert_eq!(results[0], ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(game.get_winner(), Some(1));
+    }
+
+    #[test]
+    fn test_salvo_against_several_opponents() {
+        let ruleset = Ruleset::new("small", [2, 4], &[ShipType::Destroyer, ShipType::PatrolBoat])
+            .expect("ruleset should be valid")
+            .with_salvo(true)
+            .with_teams(3, 1);
+        let mut boards = vec![Board::from_ruleset(&ruleset); 3];
+        let ships = [
+            (0, ShipType::Destroyer, vec![[0, 0], [0, 1]]),
+            (0, ShipType::PatrolBoat, vec![[1, 3]]),
+            (1, ShipType::Destroyer, vec![[0, 0], [0, 1]]),
+            (2, ShipType::Destroyer, vec![[1, 0], [1, 1]]),
+        ];
+        for (player, ship_type, tiles) in ships {
+            boards[player].place_ship(ship_type, tiles).expect("failed to place a ship");
+        }
+        // a single tile of the second board is left to fire at
+        for tile in [[0, 0], [0, 2], [0, 3], [1, 0], [1, 1], [1, 2], [1, 3]] {
+            boards[1].fire(tile).expect("shot failed");
+        }
+        let mut record = GameRecord::new(["a", "b", "c"].map(String::from).to_vec());
+        record.set_ruleset(ruleset);
+        let mut game = Game::from_record(record, boards, 0);
+        // the salvo is as big whoever it's fired at
+        assert_eq!(game.get_salvo_size(0), 1);
+        assert!(game.fire_salvo(0, 2, &[[0, 0], [0, 1]]).is_err());
+        game.fire_salvo(0, 2, &[[0, 0]]).expect("salvo failed");
+    }
+
+    #[test]
+    fn test_hit_means_another_shot() {
+        let boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[9, 8], [9, 9]]),
+        ];
+        let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+        record.set_ruleset(Ruleset::classic().with_extra_shot(true));
+        let mut game = Game::from_record(record, boards, 0);
+        assert_eq!(game.fire(0, 1, [9, 8]).expect("shot failed"), ShotResult::Hit);
+        assert_eq!(game.get_turn(), 0);
+        assert!(game.fire(1, 0, [0, 0]).is_err());
+        assert_eq!(game.fire(0, 1, [5, 5]).expect("shot failed"), ShotResult::Miss);
+        assert_eq!(game.get_turn(), 1);
+        game.fire(1, 0, [0, 0]).expect("shot failed");
+        assert_eq!(game.fire(1, 0, [0, 1]).expect("shot failed"), ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(game.get_winner(), Some(1));
+
+        // a salvo with a single hit is followed by another salvo
+        let boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[9, 8], [9, 9]]),
+        ];
+        let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+        record.set_ruleset(Ruleset::classic().with_salvo(true).with_extra_shot(true));
+        let mut game = Game::from_record(record, boards, 0);
+        game.fire_salvo(0, 1, &[[9, 9]]).expect("salvo failed");
+        assert_eq!(game.get_turn(), 0);
+        game.fire_salvo(0, 1, &[[3, 3]]).expect("salvo failed");
+        assert_eq!(game.get_turn(), 1);
+    }
+
+    #[test]
+    fn test_special_weapons() {
+        let mut boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[4, 6], [4, 7]]),
+        ];
+        boards[1]
+            .place_ship(ShipType::Cruiser, vec![[0, 2], [1, 2], [2, 2]])
+            .expect("failed to place a ship");
+        let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+        record.set_ruleset(Ruleset::classic().with_weapons(true));
+        let mut game = Game::from_record(record, boards, 0);
+
+        let strike = game.use_weapon(0, 1, Weapon::Radar, [8, 8]).expect("scan failed");
+        assert_eq!(strike.get_detected(), Some(false));
+        game.fire(1, 0, [9, 9]).expect("shot failed");
+        // stops at the destroyer, the tiles behind it aren't touched
+        let strike = game.use_weapon(0, 1, Weapon::Torpedo, [4, 3]).expect("torpedo failed");
+        let results = strike.get_shots().iter().map(|&(_, result)| result).collect::<Vec<_>>();
+        assert_eq!(results, [ShotResult::Miss, ShotResult::Miss, ShotResult::Miss, ShotResult::Hit]);
+        game.fire(1, 0, [9, 8]).expect("shot failed");
+        let strike = game.use_weapon(0, 1, Weapon::Bombardment, [1, 1]).expect("bombardment failed");
+        assert_eq!(strike.get_shots().len(), 9);
+        assert!(strike.get_shots().contains(&([2, 2], ShotResult::Sunk(ShipType::Cruiser))));
+        assert_eq!(game.get_arsenal(0).get_uses(Weapon::Bombardment), 0);
+        assert_eq!(game.get_arsenal(1), Arsenal::full());
+
+        game.fire(1, 0, [9, 7]).expect("shot failed");
+        assert!(game.use_weapon(0, 1, Weapon::Bombardment, [5, 5]).is_err());
+        // the wreck of the destroyer stops the torpedo right away, so it isn't used up
+        assert!(game.use_weapon(0, 1, Weapon::Torpedo, [4, 5]).is_err());
+        assert_eq!(game.get_arsenal(0).get_uses(Weapon::Torpedo), 1);
+        assert_eq!(game.get_record().get_shots().len(), 16);
+
+        // nothing to use in games without special weapons
+        let boards = vec![Board::new(), Board::new()];
+        let mut game = Game::new(vec![String::from("a"), String::from("b")], boards, 0);
+        assert!(game.use_weapon(0, 1, Weapon::Radar, [0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_moving_ships() {
+        let boards = vec![board_with_destroyer(vec![[0, 0], [0, 1]]), board_with_destroyer(vec![[5, 5], [5, 6]])];
+        let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+        record.set_ruleset(Ruleset::classic().with_moving(true));
+        let mut game = Game::from_record(record, boards, 0);
+
+        // only own ships, and only on your turn
+        assert!(game.move_ship(0, [5, 5], Direction::Down).is_err());
+        assert!(game.move_ship(1, [5, 5], Direction::Down).is_err());
+        game.move_ship(0, [0, 0], Direction::Down).expect("move failed");
+        assert_eq!(game.get_turn(), 1);
+        assert_eq!(game.get_board(0).get_ships()[0].get_tiles(), [[1, 0], [1, 1]]);
+        assert_eq!(game.fire(1, 0, [1, 1]).expect("shot failed"), ShotResult::Hit);
+        assert!(game.move_ship(0, [1, 0], Direction::Down).is_err());
+        assert_eq!(game.get_record().get_shots()[0].get_move(), Some(Direction::Down));
+
+        let boards = vec![Board::new(), board_with_destroyer(vec![[5, 5], [5, 6]])];
+        let mut game = Game::new(vec![String::from("a"), String::from("b")], boards, 1);
+        assert!(game.move_ship(1, [5, 5], Direction::Down).is_err());
+    }
+
+    #[test]
+    fn test_team_game() {
+        let boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[1, 0], [1, 1]]),
+            board_with_destroyer(vec![[2, 0], [2, 1]]),
+            board_with_destroyer(vec![[3, 0], [3, 1]]),
+        ];
+        let players = ["a", "b", "c", "d"].map(String::from).to_vec();
+        let mut record = GameRecord::new(players);
+        record.set_ruleset(Ruleset::classic().with_teams(4, 2));
+        let mut game = Game::from_record(record, boards, 0);
+        assert_eq!(game.get_opponents(0), [1, 3]);
+        // teammates aren't targets
+        assert!(game.fire(0, 2, [2, 0]).is_err());
+        game.fire(0, 1, [1, 0]).expect("shot failed");
+        assert_eq!(game.get_turn(), 1);
+        game.fire(1, 0, [9, 9]).expect("shot failed");
+        game.fire(2, 1, [1, 1]).expect("shot failed");
+        assert!(game.is_eliminated(1));
+        // eliminated players are skipped
+        assert_eq!(game.get_turn(), 3);
+        game.fire(3, 2, [9, 9]).expect("shot failed");
+        assert_eq!(game.get_turn(), 0);
+        assert!(game.fire(0, 1, [5, 5]).is_err());
+        game.fire(0, 3, [3, 0]).expect("shot failed");
+        assert_eq!((game.get_turn(), game.get_winner()), (2, None));
+        game.fire(2, 3, [3, 1]).expect("shot failed");
+        assert_eq!(game.get_winner(), Some(0));
+        assert!(game.is_winner(0) && game.is_winner(2) && !game.is_winner(1));
+        assert_eq!(game.get_record().get_shots()[2].get_opponent(), 1);
+    }
+
+    #[test]
+    fn test_forfeit() {
+        let boards = vec![
+            board_with_destroyer(vec![[0, 0], [0, 1]]),
+            board_with_destroyer(vec![[1, 0], [1, 1]]),
+            board_with_destroyer(vec![[2, 0], [2, 1]]),
+        ];
+        let players = ["a", "b", "c"].map(String::from).to_vec();
+        let mut record = GameRecord::new(players);
+        record.set_ruleset(Ruleset::classic().with_teams(3, 1));
+        let mut game = Game::from_record(record, boards, 1);
+        // leaving on your turn passes it on, the others play on without you
+        game.forfeit(1);
+        assert!(game.is_eliminated(1));
+        assert_eq!((game.get_turn(), game.get_winner()), (2, None));
+        assert_eq!(game.get_opponents(2), [0]);
+        assert!(game.fire(2, 1, [1, 0]).is_err());
+        game.fire(2, 0, [9, 9]).expect("shot failed");
+        assert_eq!(game.get_turn(), 0);
+        // once a single player is left, they win
+        game.forfeit(2);
+        assert_eq!(game.get_winner(), Some(0));
+        assert!(game.is_winner(0) && !game.is_winner(2));
+    }
+}
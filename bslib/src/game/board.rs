@@ -0,0 +1,329 @@
+use super::error::GameError;
+use super::movement::Direction;
+use super::ruleset::{Adjacency, Ruleset};
+use super::{ShipType, ShotResult};
+use crate::game_record::PlacedShip;
+
+// size of the classic board
+pub const BOARD_SIZE: usize = 10;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tile {
+    shot: bool,
+    ship: Option<usize>,
+}
+impl Tile {
+    pub fn is_shot(&self) -> bool {
+        self.shot
+    }
+
+    pub fn has_ship(&self) -> bool {
+        self.ship.is_some()
+    }
+}
+
+// authoritative board of one player - knows where the ships are and which tiles were shot
+#[derive(Debug, Clone)]
+pub struct Board {
+    // rows of tiles, `tiles[i][j]` is the tile at [i, j]
+    tiles: Vec<Vec<Tile>>,
+    ships: Vec<PlacedShip>,
+    adjacency: Adjacency,
+}
+impl Board {
+    // empty classic board
+    pub fn new() -> Self {
+        Self::with_size([BOARD_SIZE, BOARD_SIZE])
+    }
+
+    pub fn with_size([height, width]: [usize; 2]) -> Self {
+        Self {
+            tiles: vec![vec![Tile::default(); width]; height],
+            ships: Vec::new(),
+            adjacency: Adjacency::default(),
+        }
+    }
+
+    // empty board of the ruleset's size, following its placement rules
+    pub fn from_ruleset(ruleset: &Ruleset) -> Self {
+        Self::with_size(ruleset.get_size()).with_adjacency(ruleset.get_adjacency())
+    }
+
+    pub fn with_adjacency(mut self, adjacency: Adjacency) -> Self {
+        self.adjacency = adjacency;
+        self
+    }
+
+    // board with the given ships, which have to be exactly the ships of the ruleset's fleet
+    pub fn from_fleet(ships: &[PlacedShip], ruleset: &Ruleset) -> Result<Self, GameError> {
+        let mut missing = ruleset.get_fleet().to_vec();
+        let mut board = Self::from_ruleset(ruleset);
+        for ship in ships {
+            let Some(index) = missing.iter().position(|&ship_type| ship_type == ship.get_type()) else {
+                return Err(GameError::new("Ship is not a part of the fleet"));
+            };
+            missing.remove(index);
+            board.place_ship(ship.get_type(), ship.get_tiles().to_vec())?;
+        }
+        if !missing.is_empty() {
+            return Err(GameError::new("Not all ships of the fleet were placed"));
+        }
+        Ok(board)
+    }
+
+    // [height, width]
+    pub fn get_size(&self) -> [usize; 2] {
+        [self.tiles.len(), self.tiles.first().map_or(0, Vec::len)]
+    }
+
+    pub fn contains(&self, [i, j]: [usize; 2]) -> bool {
+        let [height, width] = self.get_size();
+        i < height && j < width
+    }
+
+    pub fn get_tile(&self, [i, j]: [usize; 2]) -> &Tile {
+        &self.tiles[i][j]
+    }
+
+    pub fn get_ships(&self) -> &[PlacedShip] {
+        &self.ships
+    }
+
+    // ship has to have its shape, rotated or mirrored, and can't overlap other ships or touch
+    // them in a way the adjacency rule forbids
+    pub fn check_placement(&self, ship_type: ShipType, tiles: &[[usize; 2]]) -> Result<(), GameError> {
+        if !tiles.iter().all(|&tile| self.contains(tile)) {
+            return Err(GameError::new("Ship doesn't fit on the board"));
+        }
+        let shape = ship_type.get_shape();
+        if !shape.matches(tiles) {
+            return Err(GameError::new(match shape.is_line() {
+                true => "Ship has to be a straight line of tiles matching its size",
+                false => "Ship's tiles don't form its shape",
+            }));
+        }
+        let [height, width] = self.get_size();
+        for &[i, j] in tiles {
+            if self.tiles[i][j].ship.is_some() {
+                return Err(GameError::new("Tile is not empty"));
+            }
+
+            let top = i.saturating_sub(1);
+            let down = (i + 1).min(height - 1);
+            let left = j.saturating_sub(1);
+            let right = (j + 1).min(width - 1);
+
+            for k in top..down + 1 {
+                for l in left..right + 1 {
+                    if self.tiles[k][l].ship.is_some() && self.adjacency.forbids([i, j], [k, l]) {
+                        return Err(GameError::new("The tile is next to another ship"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn place_ship(&mut self, ship_type: ShipType, tiles: Vec<[usize; 2]>) -> Result<(), GameError> {
+        self.check_placement(ship_type, &tiles)?;
+        for &[i, j] in &tiles {
+            self.tiles[i][j].ship = Some(self.ships.len());
+        }
+        self.ships.push(PlacedShip::new(ship_type, tiles));
+        Ok(())
+    }
+
+    // move the undamaged ship on the tile by one tile, it can't go onto a tile that was shot
+    // and has to follow the same placement rules as when it was placed
+    pub fn move_ship(&mut self, tile: [usize; 2], direction: Direction) -> Result<(), GameError> {
+        if !self.contains(tile) {
+            return Err(GameError::new("Tile is outside of the board"));
+        }
+        let Some(index) = self.get_tile(tile).ship else {
+            return Err(GameError::new("There is no ship on this tile"));
+        };
+        let ship = self.ships[index].clone();
+        if ship.get_tiles().iter().any(|&tile| self.get_tile(tile).shot) {
+            return Err(GameError::new("Only undamaged ships can be moved"));
+        }
+        let size = self.get_size();
+        let tiles = ship
+            .get_tiles()
+            .iter()
+            .map(|&tile| direction.step(tile, size))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(GameError::new("Ship can't leave the board"))?;
+        if tiles.iter().any(|&tile| self.get_tile(tile).shot) {
+            return Err(GameError::new("Ship can't move onto a tile that was shot"));
+        }
+
+        // the ship is taken off the board so it doesn't get in its own way
+        for &[i, j] in ship.get_tiles() {
+            self.tiles[i][j].ship = None;
+        }
+        let checked = self.check_placement(ship.get_type(), &tiles);
+        let tiles = match checked {
+            Ok(()) => tiles,
+            Err(_) => ship.get_tiles().to_vec(),
+        };
+        for &[i, j] in &tiles {
+            self.tiles[i][j].ship = Some(index);
+        }
+        self.ships[index] = PlacedShip::new(ship.get_type(), tiles);
+        checked
+    }
+
+    pub fn fire(&mut self, [i, j]: [usize; 2]) -> Result<ShotResult, GameError> {
+        if !self.contains([i, j]) {
+            return Err(GameError::new("Target is outside of the board"));
+        }
+        let tile = &mut self.tiles[i][j];
+        if tile.shot {
+            return Err(GameError::new("This tile was already shot"));
+        }
+        tile.shot = true;
+        match tile.ship {
+            None => Ok(ShotResult::Miss),
+            Some(index) => {
+                let ship = &self.ships[index];
+                match self.is_sunk(ship) {
+                    true => Ok(ShotResult::Sunk(ship.get_type())),
+                    false => Ok(ShotResult::Hit),
+                }
+            }
+        }
+    }
+
+    pub fn is_fleet_destroyed(&self) -> bool {
+        self.ships.iter().all(|ship| self.is_sunk(ship))
+    }
+
+    pub fn count_ships_afloat(&self) -> usize {
+        self.ships.iter().filter(|ship| !self.is_sunk(ship)).count()
+    }
+
+    pub fn count_unshot_tiles(&self) -> usize {
+        self.tiles.iter().flatten().filter(|tile| !tile.shot).count()
+    }
+
+    fn is_sunk(&self, ship: &PlacedShip) -> bool {
+        ship.get_tiles()
+            .iter()
+            .all(|&[i, j]| self.tiles[i][j].shot)
+    }
+}
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placement_rules() {
+        let mut board = Board::new();
+        board
+            .place_ship(ShipType::Destroyer, vec![[0, 0], [0, 1]])
+            .expect("failed to place a ship");
+        // touching diagonally
+        assert!(board.check_placement(ShipType::Destroyer, &[[1, 2], [2, 2]]).is_err());
+        // overlapping
+        assert!(board.check_placement(ShipType::Destroyer, &[[0, 1], [1, 1]]).is_err());
+        // not in line
+        assert!(board.check_placement(ShipType::Destroyer, &[[5, 5], [6, 6]]).is_err());
+        // wrong length
+        assert!(board.check_placement(ShipType::Cruiser, &[[5, 5], [5, 6]]).is_err());
+        // off the board
+        assert!(board.check_placement(ShipType::Destroyer, &[[9, 9], [9, 10]]).is_err());
+        assert!(board.check_placement(ShipType::Destroyer, &[[5, 6], [5, 5]]).is_ok());
+    }
+
+    #[test]
+    fn test_shaped_ships() {
+        let mut board = Board::new();
+        // an L lying on its back, mirrored
+        board
+            .place_ship(ShipType::LShip, vec![[1, 1], [1, 2], [1, 3], [0, 1]])
+            .expect("failed to place a ship");
+        assert!(board.check_placement(ShipType::TShip, &[[5, 5], [6, 5], [7, 5], [6, 4]]).is_ok());
+        assert!(board.check_placement(ShipType::TShip, &[[5, 5], [6, 5], [7, 5], [7, 4]]).is_err());
+        // the corner of the L is out of the way, but not far enough
+        assert!(board.check_placement(ShipType::Destroyer, &[[0, 3], [0, 4]]).is_err());
+        assert!(board.check_placement(ShipType::Destroyer, &[[0, 5], [0, 6]]).is_ok());
+    }
+
+    #[test]
+    fn test_adjacency_rules() {
+        let mut diagonal = Board::new().with_adjacency(Adjacency::Diagonal);
+        let mut any = Board::new().with_adjacency(Adjacency::Any);
+        for board in [&mut diagonal, &mut any] {
+            board
+                .place_ship(ShipType::Destroyer, vec![[0, 0], [0, 1]])
+                .expect("failed to place a ship");
+            assert!(board.check_placement(ShipType::Destroyer, &[[1, 2], [2, 2]]).is_ok());
+            assert!(board.check_placement(ShipType::Destroyer, &[[0, 1], [1, 1]]).is_err());
+        }
+        assert!(diagonal.check_placement(ShipType::Destroyer, &[[1, 1], [2, 1]]).is_err());
+        assert!(any.check_placement(ShipType::Destroyer, &[[1, 1], [2, 1]]).is_ok());
+    }
+
+    #[test]
+    fn test_firing() {
+        let mut board = Board::new();
+        board
+            .place_ship(ShipType::Destroyer, vec![[3, 3], [4, 3]])
+            .expect("failed to place a ship");
+        assert_eq!(board.fire([0, 0]).expect("shot failed"), ShotResult::Miss);
+        assert!(board.fire([0, 0]).is_err());
+        assert_eq!(board.fire([3, 3]).expect("shot failed"), ShotResult::Hit);
+        assert!(!board.is_fleet_destroyed());
+        assert_eq!(
+            board.fire([4, 3]).expect("shot failed"),
+            ShotResult::Sunk(ShipType::Destroyer)
+        );
+        assert!(board.is_fleet_destroyed());
+    }
+
+    #[test]
+    fn test_moving_ships() {
+        let mut board = Board::new();
+        board
+            .place_ship(ShipType::Destroyer, vec![[0, 0], [0, 1]])
+            .expect("failed to place a ship");
+        board
+            .place_ship(ShipType::Cruiser, vec![[3, 0], [3, 1], [3, 2]])
+            .expect("failed to place a ship");
+        assert!(board.move_ship([0, 1], Direction::Up).is_err());
+        // would touch the cruiser
+        board.move_ship([0, 0], Direction::Down).expect("destroyer should move down");
+        assert!(board.move_ship([1, 1], Direction::Down).is_err());
+        assert_eq!(board.get_ships()[0].get_tiles(), [[1, 0], [1, 1]]);
+        assert!(board.get_tile([1, 0]).has_ship() && !board.get_tile([0, 0]).has_ship());
+
+        board.fire([0, 0]).expect("shot failed");
+        assert!(board.move_ship([1, 0], Direction::Up).is_err());
+        board.fire([3, 2]).expect("shot failed");
+        assert!(board.move_ship([3, 0], Direction::Right).is_err());
+        assert!(board.move_ship([5, 5], Direction::Right).is_err());
+    }
+
+    #[test]
+    fn test_fleet_has_to_be_complete() {
+        let fleet = [ShipType::Cruiser, ShipType::Destroyer];
+        let ruleset = Ruleset::new("test", [6, 8], &fleet).expect("ruleset should be valid");
+        let cruiser = PlacedShip::new(ShipType::Cruiser, vec![[0, 0], [0, 1], [0, 2]]);
+        let destroyer = PlacedShip::new(ShipType::Destroyer, vec![[4, 5], [5, 5]]);
+        assert!(Board::from_fleet(std::slice::from_ref(&cruiser), &ruleset).is_err());
+        assert!(Board::from_fleet(&[cruiser.clone(), cruiser.clone()], &ruleset).is_err());
+        let board = Board::from_fleet(&[destroyer, cruiser.clone()], &ruleset)
+            .expect("fleet should be valid");
+        assert_eq!(board.get_ships().len(), 2);
+        assert_eq!(board.get_size(), [6, 8]);
+        // fits on a classic board, but not on this one
+        let destroyer = PlacedShip::new(ShipType::Destroyer, vec![[6, 5], [7, 5]]);
+        assert!(Board::from_fleet(&[destroyer, cruiser], &ruleset).is_err());
+    }
+}
@@ -0,0 +1,17 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct GameError {
+    msg: String,
+}
+impl GameError {
+    pub fn new(msg: &str) -> Self {
+        Self { msg: String::from(msg) }
+    }
+}
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GameError: {}", self.msg)
+    }
+}
+impl std::error::Error for GameError {}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+// way an undamaged ship can be moved by one tile in the moving fleet variant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+impl Direction {
+    pub const ALL: [Direction; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+
+    // neighbour of the tile in this direction, None past the edge of a board of the given size
+    pub fn step(&self, [i, j]: [usize; 2], [height, width]: [usize; 2]) -> Option<[usize; 2]> {
+        let tile = match self {
+            Self::Up => [i.checked_sub(1)?, j],
+            Self::Down => [i + 1, j],
+            Self::Left => [i, j.checked_sub(1)?],
+            Self::Right => [i, j + 1],
+        };
+        (tile[0] < height && tile[1] < width).then_some(tile)
+    }
+}
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_str())
+    }
+}
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|direction| direction.get_str() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|direction| direction.get_str());
+                format!("unknown direction `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
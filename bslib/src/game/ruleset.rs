@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+
+use super::board::BOARD_SIZE;
+use super::error::GameError;
+use super::{ShipType, FLEET};
+use crate::placement;
+
+// rows are labelled with letters, so a board can't be taller than the alphabet
+pub const MAX_BOARD_SIZE: usize = 26;
+// seats at a single game
+pub const MAX_PLAYERS: usize = 6;
+
+// which ships may be placed next to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Adjacency {
+    // ships can't touch, not even diagonally
+    #[default]
+    NoTouching,
+    // ships may touch at the corners, but not along their sides
+    Diagonal,
+    // ships may be right next to each other, they just can't overlap
+    Any,
+}
+impl Adjacency {
+    // whether ships on these two different tiles would touch in a way the rule doesn't allow
+    pub fn forbids(&self, [i, j]: [usize; 2], [k, l]: [usize; 2]) -> bool {
+        let distance = [i.abs_diff(k), j.abs_diff(l)];
+        match self {
+            Self::NoTouching => distance[0] <= 1 && distance[1] <= 1,
+            Self::Diagonal => distance[0] + distance[1] <= 1,
+            Self::Any => false,
+        }
+    }
+}
+
+// board size, fleet and placement rules of a game - both players and the server use the same one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruleset {
+    name: String,
+    width: usize,
+    height: usize,
+    fleet: Vec<ShipType>,
+    #[serde(default)]
+    adjacency: Adjacency,
+    // every turn a player fires one shot for each of their ships still afloat
+    #[serde(default)]
+    salvo: bool,
+    // a turn with a hit is followed by another turn of the same player
+    #[serde(default)]
+    extra_shot: bool,
+    // every player gets a few bombardments, torpedoes and radar scans
+    #[serde(default)]
+    weapons: bool,
+    // instead of firing a player may move one of their undamaged ships by a tile
+    #[serde(default)]
+    moving: bool,
+    // seats at the game and how many of them play on the same side - a duel is two teams of one
+    #[serde(default = "default_players")]
+    players: usize,
+    #[serde(default = "default_team_size")]
+    team_size: usize,
+}
+impl Ruleset {
+    pub const PRESETS: [&'static str; 4] = ["classic", "russian", "large", "house"];
+    // variants added to a preset with a `+`, e.g. `russian+salvo`
+    pub const OPTIONS: [&'static str; 11] = [
+        "salvo",
+        "extra-shot",
+        "diagonal",
+        "touching",
+        "weapons",
+        "moving",
+        "2v2",
+        "ffa3",
+        "ffa4",
+        "ffa5",
+        "ffa6",
+    ];
+
+    pub fn new(name: &str, [height, width]: [usize; 2], fleet: &[ShipType]) -> Result<Self, GameError> {
+        let ruleset = Self {
+            name: String::from(name),
+            width,
+            height,
+            fleet: fleet.to_vec(),
+            adjacency: Adjacency::default(),
+            salvo: false,
+            extra_shot: false,
+            weapons: false,
+            moving: false,
+            players: default_players(),
+            team_size: default_team_size(),
+        };
+        ruleset.validate()?;
+        Ok(ruleset)
+    }
+
+    // Milton Bradley rules - five ships from a carrier down to a destroyer on a 10x10 board
+    pub fn classic() -> Self {
+        Self {
+            name: String::from("classic"),
+            width: BOARD_SIZE,
+            height: BOARD_SIZE,
+            fleet: FLEET.to_vec(),
+            adjacency: Adjacency::NoTouching,
+            salvo: false,
+            extra_shot: false,
+            weapons: false,
+            moving: false,
+            players: default_players(),
+            team_size: default_team_size(),
+        }
+    }
+
+    // one battleship, two cruisers, three destroyers and four patrol boats on a 10x10 board
+    pub fn russian() -> Self {
+        let counts = [
+            (ShipType::Battleship, 1),
+            (ShipType::Cruiser, 2),
+            (ShipType::Destroyer, 3),
+            (ShipType::PatrolBoat, 4),
+        ];
+        let fleet = counts
+            .into_iter()
+            .flat_map(|(ship_type, count)| std::iter::repeat_n(ship_type, count));
+        Self {
+            name: String::from("russian"),
+            fleet: fleet.collect(),
+            ..Self::classic()
+        }
+    }
+
+    // two of every classic ship on a 15x15 board
+    pub fn large() -> Self {
+        Self {
+            name: String::from("large"),
+            width: 15,
+            height: 15,
+            fleet: FLEET.iter().flat_map(|&ship_type| [ship_type; 2]).collect(),
+            ..Self::classic()
+        }
+    }
+
+    // classic board with an L, a T and a plus in place of the middle ships
+    pub fn house() -> Self {
+        Self {
+            name: String::from("house"),
+            fleet: vec![
+                ShipType::Carrier,
+                ShipType::PlusShip,
+                ShipType::LShip,
+                ShipType::TShip,
+                ShipType::Destroyer,
+            ],
+            ..Self::classic()
+        }
+    }
+
+    // preset followed by any number of options, the whole spec becomes the name
+    pub fn preset(spec: &str) -> Option<Self> {
+        let mut parts = spec.split('+');
+        let mut ruleset = match parts.next()? {
+            "classic" => Self::classic(),
+            "russian" => Self::russian(),
+            "large" => Self::large(),
+            "house" => Self::house(),
+            _ => return None,
+        };
+        for option in parts {
+            match option {
+                "salvo" => ruleset.salvo = true,
+                "extra-shot" => ruleset.extra_shot = true,
+                "diagonal" => ruleset.adjacency = Adjacency::Diagonal,
+                "touching" => ruleset.adjacency = Adjacency::Any,
+                "weapons" => ruleset.weapons = true,
+                "moving" => ruleset.moving = true,
+                "2v2" => ruleset = ruleset.with_teams(4, 2),
+                // free for all, every player on their own
+                "ffa3" => ruleset = ruleset.with_teams(3, 1),
+                "ffa4" => ruleset = ruleset.with_teams(4, 1),
+                "ffa5" => ruleset = ruleset.with_teams(5, 1),
+                "ffa6" => ruleset = ruleset.with_teams(6, 1),
+                _ => return None,
+            }
+        }
+        ruleset.name = String::from(spec);
+        ruleset.validate().ok()?;
+        Some(ruleset)
+    }
+
+    // what `preset` accepts, for error messages and help
+    pub fn describe_presets() -> String {
+        format!(
+            "{}, optionally followed by +{}",
+            Self::PRESETS.join(", "),
+            Self::OPTIONS.join(", +")
+        )
+    }
+
+    pub fn with_salvo(mut self, salvo: bool) -> Self {
+        self.salvo = salvo;
+        self
+    }
+
+    pub fn with_adjacency(mut self, adjacency: Adjacency) -> Self {
+        self.adjacency = adjacency;
+        self
+    }
+
+    pub fn with_extra_shot(mut self, extra_shot: bool) -> Self {
+        self.extra_shot = extra_shot;
+        self
+    }
+
+    pub fn with_weapons(mut self, weapons: bool) -> Self {
+        self.weapons = weapons;
+        self
+    }
+
+    pub fn with_moving(mut self, moving: bool) -> Self {
+        self.moving = moving;
+        self
+    }
+
+    // `players` seats split into teams of `team_size`, seated so that the teams take turns
+    pub fn with_teams(mut self, players: usize, team_size: usize) -> Self {
+        self.players = players;
+        self.team_size = team_size;
+        self
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    // [height, width], in the same order as the coordinates of a tile
+    pub fn get_size(&self) -> [usize; 2] {
+        [self.height, self.width]
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_fleet(&self) -> &[ShipType] {
+        &self.fleet
+    }
+
+    pub fn get_adjacency(&self) -> Adjacency {
+        self.adjacency
+    }
+
+    pub fn is_salvo(&self) -> bool {
+        self.salvo
+    }
+
+    pub fn is_extra_shot(&self) -> bool {
+        self.extra_shot
+    }
+
+    pub fn has_weapons(&self) -> bool {
+        self.weapons
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.moving
+    }
+
+    pub fn get_players(&self) -> usize {
+        self.players
+    }
+
+    pub fn get_team_size(&self) -> usize {
+        self.team_size
+    }
+
+    pub fn count_teams(&self) -> usize {
+        self.players / self.team_size
+    }
+
+    // team of the player on the given seat
+    pub fn get_team(&self, player: usize) -> usize {
+        player % self.count_teams()
+    }
+
+    pub fn contains(&self, [i, j]: [usize; 2]) -> bool {
+        i < self.height && j < self.width
+    }
+
+    // the board has to be labelled, the whole fleet has to fit on it, a salvo turn can't be
+    // swapped for a weapon or a move, and the players have to split into even teams
+    pub fn validate(&self) -> Result<(), GameError> {
+        let sizes = 1..=MAX_BOARD_SIZE;
+        if !sizes.contains(&self.width) || !sizes.contains(&self.height) {
+            return Err(GameError::new(&format!(
+                "Board has to be between 1 and {MAX_BOARD_SIZE} tiles wide and high"
+            )));
+        }
+        if self.fleet.is_empty() {
+            return Err(GameError::new("Fleet has no ships"));
+        }
+        if !self.fleet.iter().all(|ship| ship.get_shape().fits(self.get_size())) {
+            return Err(GameError::new("Ship doesn't fit on the board"));
+        }
+        if !placement::fits_together(self) {
+            return Err(GameError::new("The whole fleet doesn't fit on the board"));
+        }
+        if self.salvo && self.weapons {
+            return Err(GameError::new("Special weapons can't be used in salvo games"));
+        }
+        if self.salvo && self.moving {
+            return Err(GameError::new("Ships can't be moved in salvo games"));
+        }
+        if !(2..=MAX_PLAYERS).contains(&self.players) {
+            return Err(GameError::new(&format!("A game needs between 2 and {MAX_PLAYERS} players")));
+        }
+        if self.team_size == 0 || !self.players.is_multiple_of(self.team_size) || self.count_teams() < 2 {
+            return Err(GameError::new("Players have to split into at least two teams of the same size"));
+        }
+        Ok(())
+    }
+}
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+// rulesets from before team games were a thing are duels
+fn default_players() -> usize {
+    2
+}
+
+fn default_team_size() -> usize {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        for name in Ruleset::PRESETS {
+            let ruleset = Ruleset::preset(name).expect("preset should exist");
+            assert_eq!(ruleset.get_name(), name);
+            assert!(ruleset.validate().is_ok());
+        }
+        assert!(Ruleset::preset("salvo").is_none());
+        assert!(Ruleset::preset("classic+sideways").is_none());
+        let salvo = Ruleset::preset("russian+salvo").expect("options should be accepted");
+        assert!(salvo.is_salvo());
+        assert_eq!(salvo.get_name(), "russian+salvo");
+        assert_eq!(salvo.get_fleet(), Ruleset::russian().get_fleet());
+        assert!(!salvo.is_extra_shot());
+        let both = Ruleset::preset("classic+extra-shot+salvo").expect("options should combine");
+        assert!(both.is_salvo() && both.is_extra_shot());
+        let touching = Ruleset::preset("large+diagonal").expect("options should be accepted");
+        assert_eq!(touching.get_adjacency(), Adjacency::Diagonal);
+        assert_eq!(both.get_adjacency(), Adjacency::NoTouching);
+        assert!(Ruleset::preset("house+weapons").is_some_and(|ruleset| ruleset.has_weapons()));
+        assert!(Ruleset::preset("classic+weapons+salvo").is_none());
+        assert!(Ruleset::classic().with_salvo(true).with_weapons(true).validate().is_err());
+        assert!(Ruleset::preset("classic+moving+weapons").is_some_and(|ruleset| ruleset.is_moving()));
+        assert!(Ruleset::preset("classic+moving+salvo").is_none());
+        assert!(Ruleset::classic().with_salvo(true).with_moving(true).validate().is_err());
+        let teams = Ruleset::preset("classic+2v2").expect("options should be accepted");
+        assert_eq!((teams.get_players(), teams.count_teams()), (4, 2));
+        assert_eq!([0, 1, 2, 3].map(|player| teams.get_team(player)), [0, 1, 0, 1]);
+        assert_eq!(Ruleset::classic().count_teams(), 2);
+        assert!(Ruleset::classic().with_teams(4, 3).validate().is_err());
+        assert!(Ruleset::classic().with_teams(2, 2).validate().is_err());
+        let free_for_all = Ruleset::preset("large+ffa5").expect("options should be accepted");
+        assert_eq!((free_for_all.get_players(), free_for_all.count_teams()), (5, 5));
+        assert!(Ruleset::classic().with_teams(7, 1).validate().is_err());
+        let russian = Ruleset::russian();
+        let sizes = russian.get_fleet().iter().map(|ship| ship.get_size()).collect::<Vec<_>>();
+        assert_eq!(sizes, [4, 3, 3, 2, 2, 2, 1, 1, 1, 1]);
+        assert!(Ruleset::new("tiny", [3, 3], &[ShipType::Carrier]).is_err());
+        assert!(Ruleset::new("huge", [27, 10], &FLEET).is_err());
+        assert!(Ruleset::new("flat", [2, 10], &[ShipType::PlusShip]).is_err());
+        assert!(Ruleset::new("narrow", [10, 2], &[ShipType::LShip]).is_ok());
+        assert!(Ruleset::new("crowded", [3, 3], &[ShipType::Destroyer; 2]).is_ok());
+        assert!(Ruleset::new("crowded", [3, 3], &[ShipType::Destroyer; 3]).is_err());
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let rules = [Adjacency::NoTouching, Adjacency::Diagonal, Adjacency::Any];
+        assert_eq!(rules.map(|rule| rule.forbids([3, 3], [4, 4])), [true, false, false]);
+        assert_eq!(rules.map(|rule| rule.forbids([3, 3], [3, 2])), [true, true, false]);
+        assert_eq!(rules.map(|rule| rule.forbids([3, 3], [5, 3])), [false, false, false]);
+    }
+}
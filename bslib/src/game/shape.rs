@@ -0,0 +1,141 @@
+// tiles a ship takes, relative to the top left corner of the smallest box around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    tiles: Vec<[usize; 2]>,
+}
+impl Shape {
+    // any tiles, moved up and left until the shape touches both edges
+    pub fn new(tiles: &[[usize; 2]]) -> Self {
+        let top = tiles.iter().map(|tile| tile[0]).min().unwrap_or(0);
+        let left = tiles.iter().map(|tile| tile[1]).min().unwrap_or(0);
+        let mut tiles = tiles.iter().map(|&[i, j]| [i - top, j - left]).collect::<Vec<_>>();
+        tiles.sort_unstable();
+        tiles.dedup();
+        Self { tiles }
+    }
+
+    // straight ship lying along a row
+    pub fn line(size: usize) -> Self {
+        Self {
+            tiles: (0..size).map(|j| [0, j]).collect(),
+        }
+    }
+
+    pub fn get_tiles(&self) -> &[[usize; 2]] {
+        &self.tiles
+    }
+
+    // number of tiles
+    pub fn get_size(&self) -> usize {
+        self.tiles.len()
+    }
+
+    // [height, width] of the box around the shape
+    pub fn get_dimensions(&self) -> [usize; 2] {
+        let height = self.tiles.iter().map(|tile| tile[0] + 1).max().unwrap_or(0);
+        let width = self.tiles.iter().map(|tile| tile[1] + 1).max().unwrap_or(0);
+        [height, width]
+    }
+
+    pub fn is_line(&self) -> bool {
+        self.get_dimensions().contains(&1)
+    }
+
+    // quarter turn clockwise
+    pub fn rotate(&self) -> Self {
+        let [height, _] = self.get_dimensions();
+        let tiles = self.tiles.iter().map(|&[i, j]| [j, height - 1 - i]).collect::<Vec<_>>();
+        Self::new(&tiles)
+    }
+
+    // flipped left to right
+    pub fn mirror(&self) -> Self {
+        let [_, width] = self.get_dimensions();
+        let tiles = self.tiles.iter().map(|&[i, j]| [i, width - 1 - j]).collect::<Vec<_>>();
+        Self::new(&tiles)
+    }
+
+    // every different way the shape can lie on a board - its rotations first, then the
+    // rotations of its mirror image
+    pub fn orientations(&self) -> Vec<Shape> {
+        let mut orientations: Vec<Shape> = Vec::new();
+        for start in [self.clone(), self.mirror()] {
+            let mut shape = start;
+            for _ in 0..4 {
+                let next = shape.rotate();
+                if !orientations.contains(&shape) {
+                    orientations.push(shape);
+                }
+                shape = next;
+            }
+        }
+        orientations
+    }
+
+    // whether the tiles are this shape in one of its orientations, each tile taken once
+    pub fn matches(&self, tiles: &[[usize; 2]]) -> bool {
+        if tiles.len() != self.get_size() {
+            return false;
+        }
+        let shape = Self::new(tiles);
+        if shape.get_size() != tiles.len() {
+            return false;
+        }
+        // every line of the right number of tiles is the same line, no need to turn it around
+        match self.is_line() {
+            true => shape.is_line(),
+            false => self.orientations().contains(&shape),
+        }
+    }
+
+    // whether some orientation fits on a board of the given size
+    pub fn fits(&self, [height, width]: [usize; 2]) -> bool {
+        self.orientations().iter().any(|orientation| {
+            let [rows, cols] = orientation.get_dimensions();
+            rows <= height && cols <= width
+        })
+    }
+
+    // tiles of the shape with the corner of its box at the given tile
+    pub fn at(&self, [i, j]: [usize; 2]) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.tiles.iter().map(move |&[k, l]| [i + k, j + l])
+    }
+
+    // fewest diagonals the shape covers in any orientation - it can't slip between shots
+    // placed this many diagonals apart
+    pub fn get_diagonal_span(&self) -> usize {
+        let span = |shape: &Shape| {
+            let diagonals = shape.tiles.iter().map(|[i, j]| i + j);
+            diagonals.clone().max().unwrap_or(0) + 1 - diagonals.min().unwrap_or(0)
+        };
+        self.orientations().iter().map(span).min().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientations() {
+        let elbow = Shape::new(&[[0, 0], [1, 0], [2, 0], [2, 1]]);
+        let tee = Shape::new(&[[0, 0], [0, 1], [0, 2], [1, 1]]);
+        let plus = Shape::new(&[[0, 1], [1, 0], [1, 1], [1, 2], [2, 1]]);
+        let counts = [Shape::line(1), Shape::line(3), elbow.clone(), tee.clone(), plus.clone()]
+            .map(|shape| shape.orientations().len());
+        assert_eq!(counts, [1, 2, 8, 4, 1]);
+        assert_eq!(Shape::line(3).rotate().get_dimensions(), [3, 1]);
+        assert_eq!(Shape::line(3).orientations()[0], Shape::line(3));
+
+        // rotated and mirrored, anywhere on the board
+        assert!(elbow.matches(&[[5, 5], [5, 6], [5, 7], [4, 7]]));
+        assert!(elbow.matches(&[[3, 4], [4, 4], [5, 4], [5, 3]]));
+        assert!(!elbow.matches(&[[3, 4], [4, 4], [5, 4], [5, 4]]));
+        assert!(!elbow.matches(&[[0, 0], [0, 1], [0, 2], [0, 3]]));
+        assert!(!tee.fits([2, 2]));
+        assert!(tee.fits([3, 2]));
+
+        assert_eq!(Shape::line(4).get_diagonal_span(), 4);
+        assert_eq!([elbow, tee, plus].map(|shape| shape.get_diagonal_span()), [3, 3, 3]);
+    }
+}
@@ -0,0 +1,165 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::error::GameError;
+use super::ShotResult;
+
+// side of the square bombardments and radar scans cover
+const AREA_SIDE: usize = 3;
+
+// abilities of the special weapons variant - a use takes the place of the turn's shot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Weapon {
+    // fires at every tile of a 3x3 area around the target
+    Bombardment,
+    // runs from the target to the right edge of the board and explodes on the first ship
+    Torpedo,
+    // tells whether a 3x3 area around the target hides a part of a ship that wasn't hit yet
+    Radar,
+}
+impl Weapon {
+    pub const ALL: [Weapon; 3] = [Self::Bombardment, Self::Torpedo, Self::Radar];
+
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Bombardment => "bombardment",
+            Self::Torpedo => "torpedo",
+            Self::Radar => "radar",
+        }
+    }
+
+    // uses every player gets in a game played with special weapons
+    pub fn get_uses(&self) -> usize {
+        match self {
+            Self::Bombardment => 1,
+            Self::Torpedo => 2,
+            Self::Radar => 2,
+        }
+    }
+
+    // tiles the weapon aimed at the target covers on a board of the given size, in the order
+    // a torpedo goes through them
+    pub fn area(&self, [i, j]: [usize; 2], [height, width]: [usize; 2]) -> Vec<[usize; 2]> {
+        match self {
+            Self::Torpedo => (j..width).map(|l| [i, l]).collect(),
+            Self::Bombardment | Self::Radar => {
+                let reach = AREA_SIDE / 2;
+                let rows = i.saturating_sub(reach)..(i + reach + 1).min(height);
+                rows.flat_map(|k| {
+                    let cols = j.saturating_sub(reach)..(j + reach + 1).min(width);
+                    cols.map(move |l| [k, l])
+                })
+                .collect()
+            }
+        }
+    }
+}
+impl Display for Weapon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_str())
+    }
+}
+impl FromStr for Weapon {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|weapon| weapon.get_str() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|weapon| weapon.get_str());
+                format!("unknown weapon `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+// uses of every weapon a player has left, none in games without special weapons
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Arsenal {
+    uses: [usize; 3],
+}
+impl Arsenal {
+    // every weapon with all of its uses
+    pub fn full() -> Self {
+        Self {
+            uses: Weapon::ALL.map(|weapon| weapon.get_uses()),
+        }
+    }
+
+    pub fn get_uses(&self, weapon: Weapon) -> usize {
+        self.uses[weapon as usize]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uses.iter().all(|&uses| uses == 0)
+    }
+
+    // spend one use of the weapon
+    pub fn take(&mut self, weapon: Weapon) -> Result<(), GameError> {
+        match self.uses[weapon as usize] {
+            0 => Err(GameError::new(&format!("There is no {weapon} left to use"))),
+            _ => {
+                self.uses[weapon as usize] -= 1;
+                Ok(())
+            }
+        }
+    }
+}
+
+// what a weapon did - the tiles it fired at with their results, and whether a radar found
+// something
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Strike {
+    shots: Vec<([usize; 2], ShotResult)>,
+    #[serde(default)]
+    detected: Option<bool>,
+}
+impl Strike {
+    pub fn new(shots: Vec<([usize; 2], ShotResult)>) -> Self {
+        Self { shots, detected: None }
+    }
+
+    pub fn scan(detected: bool) -> Self {
+        Self {
+            shots: Vec::new(),
+            detected: Some(detected),
+        }
+    }
+
+    pub fn get_shots(&self) -> &[([usize; 2], ShotResult)] {
+        &self.shots
+    }
+
+    // None for weapons that fire
+    pub fn get_detected(&self) -> Option<bool> {
+        self.detected
+    }
+
+    pub fn is_hit(&self) -> bool {
+        self.shots.iter().any(|&(_, result)| result != ShotResult::Miss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weapons() {
+        for weapon in Weapon::ALL {
+            assert_eq!(weapon.get_str().parse::<Weapon>(), Ok(weapon));
+        }
+        assert_eq!(Weapon::Radar.area([4, 4], [10, 10]).len(), 9);
+        assert_eq!(Weapon::Bombardment.area([0, 9], [10, 10]), [[0, 8], [0, 9], [1, 8], [1, 9]]);
+        assert_eq!(Weapon::Torpedo.area([2, 7], [10, 10]), [[2, 7], [2, 8], [2, 9]]);
+
+        let mut arsenal = Arsenal::full();
+        assert!(arsenal.take(Weapon::Bombardment).is_ok());
+        assert!(arsenal.take(Weapon::Bombardment).is_err());
+        assert_eq!(arsenal.get_uses(Weapon::Torpedo), 2);
+        assert!(Arsenal::default().is_empty());
+    }
+}
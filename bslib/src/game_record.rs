@@ -0,0 +1,229 @@
+use std::fs;
+use std::path::Path;
+
+use error::GameRecordError;
+use serde::{Deserialize, Serialize};
+
+use crate::game::movement::Direction;
+use crate::game::ruleset::Ruleset;
+use crate::game::ShipType;
+use crate::tcp_protocol::Jsonable;
+
+pub mod error;
+
+// ship placed on a board, stored as the list of tiles it occupies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedShip {
+    ship_type: ShipType,
+    tiles: Vec<[usize; 2]>,
+}
+impl PlacedShip {
+    pub fn new(ship_type: ShipType, tiles: Vec<[usize; 2]>) -> Self {
+        Self { ship_type, tiles }
+    }
+
+    pub fn get_type(&self) -> ShipType {
+        self.ship_type
+    }
+
+    pub fn get_tiles(&self) -> &[[usize; 2]] {
+        &self.tiles
+    }
+}
+
+// single shot - `player` is the index of the player that fired it, `opponent` the one whose
+// board it was fired at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ShotRecord {
+    player: usize,
+    #[serde(default)]
+    opponent: Option<usize>,
+    target: [usize; 2],
+    // set when the player moved their ship on the target instead of firing at it
+    #[serde(default)]
+    moved: Option<Direction>,
+}
+impl ShotRecord {
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    // records from before team games only had the one opponent
+    pub fn get_opponent(&self) -> usize {
+        self.opponent.unwrap_or(usize::from(self.player == 0))
+    }
+
+    pub fn get_target(&self) -> [usize; 2] {
+        self.target
+    }
+
+    pub fn get_move(&self) -> Option<Direction> {
+        self.moved
+    }
+}
+
+// everything needed to replay a finished (or interrupted) game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRecord {
+    players: Vec<String>,
+    // which of the players were bots
+    #[serde(default)]
+    bots: Vec<bool>,
+    // records from before rulesets were a thing are classic games
+    #[serde(default)]
+    ruleset: Ruleset,
+    fleets: Vec<Vec<PlacedShip>>,
+    shots: Vec<ShotRecord>,
+    // seed of every random choice made for the game, the same seed makes the same choices
+    #[serde(default)]
+    seed: Option<u64>,
+}
+impl Jsonable for GameRecord {}
+impl GameRecord {
+    pub fn new(players: Vec<String>) -> Self {
+        Self {
+            bots: vec![false; players.len()],
+            fleets: vec![Vec::new(); players.len()],
+            players,
+            ruleset: Ruleset::default(),
+            shots: Vec::new(),
+            seed: None,
+        }
+    }
+
+    pub fn set_player(&mut self, player: usize, nick: &str) {
+        self.players[player] = String::from(nick);
+    }
+
+    pub fn set_bot(&mut self, player: usize, bot: bool) {
+        self.bots[player] = bot;
+    }
+
+    pub fn set_ruleset(&mut self, ruleset: Ruleset) {
+        self.ruleset = ruleset;
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    pub fn add_ship(&mut self, player: usize, ship: PlacedShip) {
+        self.fleets[player].push(ship);
+    }
+
+    pub fn add_shot(&mut self, player: usize, opponent: usize, target: [usize; 2]) {
+        self.shots.push(ShotRecord {
+            player,
+            opponent: Some(opponent),
+            target,
+            moved: None,
+        });
+    }
+
+    // the player's ship on the tile was moved in the given direction
+    pub fn add_move(&mut self, player: usize, tile: [usize; 2], direction: Direction) {
+        self.shots.push(ShotRecord {
+            player,
+            opponent: None,
+            target: tile,
+            moved: Some(direction),
+        });
+    }
+
+    pub fn get_players(&self) -> &[String] {
+        &self.players
+    }
+
+    // records from before bots were a thing don't say, their players count as people
+    pub fn is_bot(&self, player: usize) -> bool {
+        self.bots.get(player).copied().unwrap_or_default()
+    }
+
+    pub fn get_ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    // records from before seeds were kept don't have one
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn get_fleet(&self, player: usize) -> &[PlacedShip] {
+        &self.fleets[player]
+    }
+
+    pub fn get_shots(&self) -> &[ShotRecord] {
+        &self.shots
+    }
+
+    // read and validate a record saved with `save`
+    pub fn load(path: &Path) -> Result<Self, GameRecordError> {
+        let record = Self::from_json(&fs::read_to_string(path)?)?;
+        record.validate()?;
+        Ok(record)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), GameRecordError> {
+        fs::write(path, Jsonable::to_string(self)?)?;
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), GameRecordError> {
+        self.ruleset.validate()?;
+        let on_board = |tile| self.ruleset.contains(tile);
+        for fleet in &self.fleets {
+            for ship in fleet {
+                if !ship.tiles.iter().copied().all(on_board) {
+                    return Err(GameRecordError::new("Ship placed outside of the board"));
+                }
+            }
+        }
+        if self.fleets.len() > self.players.len() {
+            return Err(GameRecordError::new("Fleet of an unknown player"));
+        }
+        for shot in &self.shots {
+            if shot.player >= self.players.len() || shot.get_opponent() >= self.players.len() {
+                return Err(GameRecordError::new("Shot fired by or at an unknown player"));
+            }
+            if !on_board(shot.target) {
+                return Err(GameRecordError::new("Shot fired outside of the board"));
+            }
+        }
+        Ok(())
+    }
+}
+impl Default for GameRecord {
+    fn default() -> Self {
+        Self::new(vec![String::new(); 2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut record = GameRecord::new(vec![String::from("alice"), String::from("bob")]);
+        record.add_ship(0, PlacedShip::new(ShipType::Destroyer, vec![[0, 0], [0, 1]]));
+        record.add_shot(1, 0, [0, 1]);
+        record.set_seed(42);
+        let json = Jsonable::to_string(&record).expect("failed to serialize a record");
+        let loaded = GameRecord::from_json(&json).expect("failed to deserialize a record");
+        assert_eq!(loaded.get_players(), record.get_players());
+        assert_eq!(loaded.get_fleet(0)[0].get_tiles(), &[[0, 0], [0, 1]]);
+        assert_eq!(loaded.get_shots()[0].get_target(), [0, 1]);
+        assert_eq!(loaded.get_seed(), Some(42));
+        assert!(loaded.validate().is_ok());
+    }
+
+    #[test]
+    fn test_rejects_shots_off_board() {
+        let mut record = GameRecord::default();
+        record.add_shot(0, 1, [10, 0]);
+        assert!(record.validate().is_err());
+        // on the larger board it's fine
+        record.set_ruleset(Ruleset::large());
+        assert!(record.validate().is_ok());
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+use std::io;
+
+use crate::game::error::GameError;
+
+#[derive(Debug)]
+pub struct GameRecordError {
+    msg: String,
+}
+impl GameRecordError {
+    pub fn new(msg: &str) -> Self {
+        Self { msg: String::from(msg) }
+    }
+}
+impl Display for GameRecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GameRecordError: {}", self.msg)
+    }
+}
+impl std::convert::From<io::Error> for GameRecordError {
+    fn from(value: io::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::convert::From<serde_json::Error> for GameRecordError {
+    fn from(value: serde_json::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::convert::From<GameError> for GameRecordError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::error::Error for GameRecordError {}
@@ -1,3 +1,8 @@
+pub mod bot;
+pub mod game;
+pub mod game_record;
+pub mod placement;
+pub mod strategy;
 pub mod tcp_protocol;
 
 // use player::Player;
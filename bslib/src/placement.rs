@@ -0,0 +1,220 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+use serde::Deserialize;
+
+use crate::game::board::Board;
+use crate::game::error::GameError;
+use crate::game::ruleset::Ruleset;
+use crate::strategy::density::{density, placements, random_placement};
+use crate::strategy::Tracker;
+
+// layouts compared when looking for the one least likely to be found by a density bot
+const ANTI_AI_CANDIDATES: usize = 64;
+// layouts drawn at random before a fleet that hardly fits is searched for instead
+const UNIFORM_ATTEMPTS: usize = 100_000;
+// ship placements the search tries before the fleet counts as not fitting on the board
+const SEARCH_STEPS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PlacementMode {
+    // every legal layout is equally likely
+    Uniform,
+    // prefers tiles a probability-density bot checks last
+    AntiAi,
+}
+impl PlacementMode {
+    pub const ALL: [PlacementMode; 2] = [Self::Uniform, Self::AntiAi];
+
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Uniform => "uniform",
+            Self::AntiAi => "anti-ai",
+        }
+    }
+}
+impl Display for PlacementMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_str())
+    }
+}
+impl FromStr for PlacementMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.get_str() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|mode| mode.get_str());
+                format!("unknown placement mode `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+// board with the ruleset's whole fleet placed at random following the placement rules, an
+// error when the fleet doesn't fit on the board
+pub fn random_fleet(ruleset: &Ruleset, mode: PlacementMode, rng: &mut dyn RngCore) -> Result<Board, GameError> {
+    let board = match mode {
+        PlacementMode::Uniform => uniform_fleet(ruleset, rng),
+        PlacementMode::AntiAi => {
+            let map = density(&Tracker::new(ruleset), false);
+            let score = |board: &Board| -> u32 {
+                let tiles = board.get_ships().iter().flat_map(|ship| ship.get_tiles());
+                tiles.map(|&[i, j]| map[i][j]).sum()
+            };
+            (0..ANTI_AI_CANDIDATES)
+                .map_while(|_| uniform_fleet(ruleset, rng))
+                .min_by_key(score)
+        }
+    };
+    // the search in random order comes first, so the layout still depends on the rng
+    board
+        .or_else(|| search_fleet(ruleset, Some(rng)))
+        .or_else(|| search_fleet(ruleset, None))
+        .ok_or_else(|| GameError::new("The whole fleet doesn't fit on the board"))
+}
+
+// whether there's a layout with every ship of the fleet on the board at once
+pub fn fits_together(ruleset: &Ruleset) -> bool {
+    search_fleet(ruleset, None).is_some()
+}
+
+// every ship goes on a random spot and the whole layout is thrown away on the first conflict,
+// so each legal layout has the same chance of coming out - None when no layout came out after
+// UNIFORM_ATTEMPTS tries
+fn uniform_fleet(ruleset: &Ruleset, rng: &mut dyn RngCore) -> Option<Board> {
+    let orientations = ruleset
+        .get_fleet()
+        .iter()
+        .map(|ship_type| ship_type.get_shape().orientations())
+        .collect::<Vec<_>>();
+    'layout: for _ in 0..UNIFORM_ATTEMPTS {
+        let mut board = Board::from_ruleset(ruleset);
+        for (&ship_type, orientations) in ruleset.get_fleet().iter().zip(&orientations) {
+            let Some(placement) = random_placement(orientations, ruleset.get_size(), rng) else {
+                continue 'layout;
+            };
+            if board.place_ship(ship_type, placement.tiles().collect()).is_err() {
+                continue 'layout;
+            }
+        }
+        return Some(board);
+    }
+    None
+}
+
+// places the ships one by one and takes back the last one when the next has no room left -
+// spots are tried in a random order with `rng`, from the top left otherwise, and the search
+// gives up after SEARCH_STEPS of them
+fn search_fleet(ruleset: &Ruleset, mut rng: Option<&mut dyn RngCore>) -> Option<Board> {
+    let spots = ruleset
+        .get_fleet()
+        .iter()
+        .map(|ship_type| {
+            let orientations = ship_type.get_shape().orientations();
+            let mut spots = placements(&orientations, ruleset.get_size())
+                .map(|placement| placement.tiles().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            if let Some(rng) = rng.as_mut() {
+                spots.shuffle(rng);
+            }
+            spots
+        })
+        .collect::<Vec<_>>();
+    let mut steps = SEARCH_STEPS;
+    place_rest(ruleset, Board::from_ruleset(ruleset), &spots, &mut steps)
+}
+
+fn place_rest(ruleset: &Ruleset, board: Board, spots: &[Vec<Vec<[usize; 2]>>], steps: &mut usize) -> Option<Board> {
+    let index = board.get_ships().len();
+    let Some(&ship_type) = ruleset.get_fleet().get(index) else {
+        return Some(board);
+    };
+    for tiles in &spots[index] {
+        if *steps == 0 {
+            return None;
+        }
+        *steps -= 1;
+        let mut next = board.clone();
+        if next.place_ship(ship_type, tiles.clone()).is_err() {
+            continue;
+        }
+        if let Some(done) = place_rest(ruleset, next, spots, steps) {
+            return Some(done);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ruleset::Adjacency;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_fleet_is_complete() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let presets = Ruleset::PRESETS.map(|name| Ruleset::preset(name).unwrap());
+        let cases = presets.into_iter().map(|ruleset| (ruleset, PlacementMode::Uniform));
+        // anti-ai goes through a lot of layouts, it's too slow to try with every preset
+        for (ruleset, mode) in cases.chain([(Ruleset::classic(), PlacementMode::AntiAi)]) {
+            let board = random_fleet(&ruleset, mode, &mut rng).expect("fleet should fit");
+            let ships = board.get_ships().iter().map(|ship| ship.get_type()).collect::<Vec<_>>();
+            assert_eq!(ships, ruleset.get_fleet());
+            assert_eq!(board.get_size(), ruleset.get_size());
+        }
+    }
+
+    #[test]
+    fn test_random_fleet_follows_adjacency() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let touching = Ruleset::preset("russian+touching").expect("preset should exist");
+        let mut touched = false;
+        for _ in 0..20 {
+            let board = random_fleet(&touching, PlacementMode::Uniform, &mut rng).expect("fleet should fit");
+            let ships = board.get_ships().to_vec();
+            assert!(Board::from_fleet(&ships, &touching).is_ok());
+            touched |= Board::from_fleet(&ships, &Ruleset::russian()).is_err();
+        }
+        assert!(touched, "ships never touched even though they're allowed to");
+    }
+
+    #[test]
+    fn test_fleet_that_hardly_fits() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // four destroyers only fit on a 3x3 board when they may touch, a ruleset that comes in
+        // over the network isn't validated yet
+        let json = r#"{"name":"x","width":3,"height":3,"fleet":["Destroyer","Destroyer","Destroyer","Destroyer"]}"#;
+        let ruleset: Ruleset = serde_json::from_str(json).expect("ruleset should parse");
+        assert!(!fits_together(&ruleset));
+        assert!(random_fleet(&ruleset, PlacementMode::Uniform, &mut rng).is_err());
+        assert!(random_fleet(&ruleset, PlacementMode::AntiAi, &mut rng).is_err());
+        let touching = ruleset.with_adjacency(Adjacency::Any);
+        assert!(fits_together(&touching));
+        let board = random_fleet(&touching, PlacementMode::Uniform, &mut rng).expect("fleet should fit");
+        assert_eq!(board.get_ships().len(), 4);
+    }
+
+    #[test]
+    fn test_anti_ai_avoids_likely_tiles() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let ruleset = Ruleset::classic();
+        let map = density(&Tracker::new(&ruleset), false);
+        let mut average = |mode| {
+            let total: u32 = (0..20)
+                .map(|_| random_fleet(&ruleset, mode, &mut rng).expect("fleet should fit"))
+                .flat_map(|board| board.get_ships().to_vec())
+                .flat_map(|ship| ship.get_tiles().to_vec())
+                .map(|[i, j]| map[i][j])
+                .sum();
+            total / 20
+        };
+        assert!(average(PlacementMode::AntiAi) < average(PlacementMode::Uniform));
+    }
+}
@@ -0,0 +1,435 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use rand::RngCore;
+
+use crate::game::ruleset::{Adjacency, Ruleset};
+use crate::game::{ShipType, ShotResult};
+use density::DensityStrategy;
+use hunt_target::HuntTargetStrategy;
+use random::RandomStrategy;
+
+pub mod density;
+pub mod hunt_target;
+pub mod random;
+
+// way of picking targets on the opponent's board
+pub trait Strategy: Send {
+    // next tile to fire at, None when there's nothing left to fire at
+    fn choose_shot(&mut self, rng: &mut dyn RngCore) -> Option<[usize; 2]>;
+    // result of a shot returned by choose_shot
+    fn on_result(&mut self, target: [usize; 2], result: ShotResult);
+
+    // several shots fired at once - tiles already picked count as misses until their real
+    // results come in through on_result, so none is picked twice
+    fn choose_salvo(&mut self, count: usize, rng: &mut dyn RngCore) -> Vec<[usize; 2]> {
+        let mut targets = Vec::with_capacity(count);
+        while targets.len() < count {
+            let Some(target) = self.choose_shot(rng) else {
+                break;
+            };
+            self.on_result(target, ShotResult::Miss);
+            targets.push(target);
+        }
+        targets
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Random,
+    #[default]
+    HuntTarget,
+    Density,
+}
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Random, Self::HuntTarget, Self::Density];
+
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::HuntTarget => "hunt-target",
+            Self::Density => "density",
+        }
+    }
+
+    pub fn strategy(&self, ruleset: &Ruleset) -> Box<dyn Strategy> {
+        match self {
+            Self::Random => Box::new(RandomStrategy::new(ruleset)),
+            Self::HuntTarget => Box::new(HuntTargetStrategy::new(ruleset)),
+            Self::Density => Box::new(DensityStrategy::new(ruleset)),
+        }
+    }
+}
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_str())
+    }
+}
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.get_str() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|difficulty| difficulty.get_str());
+                format!("unknown difficulty `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Unknown,
+    Miss,
+    Hit,
+    Sunk,
+    // never fired at, but can't hold a ship - the adjacency rule keeps it free next to a sunk
+    // one, or a radar found nothing there
+    Blocked,
+}
+
+// what a player knows about the opponent's board
+#[derive(Debug, Clone)]
+pub struct Tracker {
+    cells: Vec<Vec<Cell>>,
+    remaining: Vec<ShipType>,
+    adjacency: Adjacency,
+}
+impl Tracker {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        let [height, width] = ruleset.get_size();
+        Self {
+            cells: vec![vec![Cell::Unknown; width]; height],
+            remaining: ruleset.get_fleet().to_vec(),
+            adjacency: ruleset.get_adjacency(),
+        }
+    }
+
+    // [height, width] of the opponent's board
+    pub fn get_size(&self) -> [usize; 2] {
+        [self.cells.len(), self.cells.first().map_or(0, Vec::len)]
+    }
+
+    pub fn get(&self, [i, j]: [usize; 2]) -> Cell {
+        self.cells[i][j]
+    }
+
+    pub fn get_adjacency(&self) -> Adjacency {
+        self.adjacency
+    }
+
+    // ships that weren't sunk yet
+    pub fn get_remaining(&self) -> &[ShipType] {
+        &self.remaining
+    }
+
+    pub fn record(&mut self, target: [usize; 2], result: ShotResult) {
+        let [i, j] = target;
+        match result {
+            ShotResult::Miss => self.cells[i][j] = Cell::Miss,
+            ShotResult::Hit => self.cells[i][j] = Cell::Hit,
+            ShotResult::Sunk(ship_type) => {
+                self.cells[i][j] = Cell::Hit;
+                if let Some(index) = self.remaining.iter().position(|&ship| ship == ship_type) {
+                    self.remaining.remove(index);
+                }
+                for tile in self.sunk_tiles(target, ship_type) {
+                    self.cells[tile[0]][tile[1]] = Cell::Sunk;
+                    let blocked = neighbours(tile, self.get_size())
+                        .filter(|&next| self.adjacency.forbids(tile, next))
+                        .collect::<Vec<_>>();
+                    for [k, l] in blocked {
+                        if self.cells[k][l] == Cell::Unknown {
+                            self.cells[k][l] = Cell::Blocked;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // a radar scan of the tiles that found nothing leaves them without a ship
+    pub fn record_scan(&mut self, area: &[[usize; 2]], detected: bool) {
+        if detected {
+            return;
+        }
+        for &[i, j] in area {
+            if self.cells[i][j] == Cell::Unknown {
+                self.cells[i][j] = Cell::Blocked;
+            }
+        }
+    }
+
+    // the opponent moved an undamaged ship, which may now lie on tiles a radar found empty -
+    // only the tiles kept free by a sunk ship stay blocked, the rest are returned as unknown
+    pub fn record_move(&mut self) -> Vec<[usize; 2]> {
+        let stale = self
+            .tiles()
+            .filter(|&tile| self.get(tile) == Cell::Blocked)
+            .filter(|&tile| {
+                !neighbours(tile, self.get_size())
+                    .any(|next| self.get(next) == Cell::Sunk && self.adjacency.forbids(next, tile))
+            })
+            .collect::<Vec<_>>();
+        for &[i, j] in &stale {
+            self.cells[i][j] = Cell::Unknown;
+        }
+        stale
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = [usize; 2]> {
+        let [height, width] = self.get_size();
+        (0..height).flat_map(move |i| (0..width).map(move |j| [i, j]))
+    }
+
+    // tiles that could still hold a ship and weren't fired at
+    pub fn unknown(&self) -> impl Iterator<Item = [usize; 2]> + '_ {
+        self.tiles().filter(|&tile| self.get(tile) == Cell::Unknown)
+    }
+
+    // hits on ships that are still afloat
+    pub fn open_hits(&self) -> Vec<[usize; 2]> {
+        self.tiles().filter(|&tile| self.get(tile) == Cell::Hit).collect()
+    }
+
+    // tiles of the ship sunk by a shot at the tile - the hits connected to it, which is always
+    // the ship when ships can't touch along their sides, otherwise the only placement of its
+    // shape covered by hits, or just the tile when there's more than one
+    fn sunk_tiles(&self, tile: [usize; 2], ship_type: ShipType) -> Vec<[usize; 2]> {
+        let connected = self.connected_hits(tile);
+        if connected.len() == ship_type.get_size() as usize {
+            return connected;
+        }
+        let orientations = ship_type.get_shape().orientations();
+        let covered = density::placements(&orientations, self.get_size())
+            .filter(|placement| {
+                placement.tiles().any(|next| next == tile)
+                    && placement.tiles().all(|next| self.get(next) == Cell::Hit)
+            })
+            .collect::<Vec<_>>();
+        match covered.as_slice() {
+            [placement] => placement.tiles().collect(),
+            _ => vec![tile],
+        }
+    }
+
+    // hits reachable from the tile without crossing other cells
+    fn connected_hits(&self, tile: [usize; 2]) -> Vec<[usize; 2]> {
+        let mut found = vec![tile];
+        let mut index = 0;
+        while index < found.len() {
+            for next in orthogonal_neighbours(found[index], self.get_size()) {
+                if self.get(next) == Cell::Hit && !found.contains(&next) {
+                    found.push(next);
+                }
+            }
+            index += 1;
+        }
+        found
+    }
+}
+
+// pads a salvo the strategy cut short with tiles nobody has fired at yet - a strategy leaves
+// out the tiles it has ruled out, while the salvo size counts every tile that's still unshot
+pub fn fill_salvo(targets: &mut Vec<[usize; 2]>, count: usize, [height, width]: [usize; 2], fired: &[[usize; 2]]) {
+    let tiles = (0..height).flat_map(|i| (0..width).map(move |j| [i, j]));
+    let spare = tiles
+        .filter(|tile| !fired.contains(tile) && !targets.contains(tile))
+        .take(count.saturating_sub(targets.len()))
+        .collect::<Vec<_>>();
+    targets.extend(spare);
+}
+
+// every tile around the given one on a board of the given size, diagonals included
+pub fn neighbours(
+    [i, j]: [usize; 2],
+    [height, width]: [usize; 2],
+) -> impl Iterator<Item = [usize; 2]> {
+    let rows = i.saturating_sub(1)..(i + 2).min(height);
+    rows.flat_map(move |k| {
+        let cols = j.saturating_sub(1)..(j + 2).min(width);
+        cols.map(move |l| [k, l])
+    })
+    .filter(move |&tile| tile != [i, j])
+}
+
+pub fn orthogonal_neighbours(
+    [i, j]: [usize; 2],
+    [height, width]: [usize; 2],
+) -> impl Iterator<Item = [usize; 2]> {
+    let candidates = [
+        i.checked_sub(1).map(|k| [k, j]),
+        (i + 1 < height).then_some([i + 1, j]),
+        j.checked_sub(1).map(|l| [i, l]),
+        (j + 1 < width).then_some([i, j + 1]),
+    ];
+    candidates.into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::board::{Board, BOARD_SIZE};
+    use crate::game::shape::Shape;
+    use crate::placement::{random_fleet, PlacementMode};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn sample_board() -> Board {
+        let mut board = Board::new();
+        let ships = [
+            (ShipType::Carrier, vec![[0, 0], [0, 1], [0, 2], [0, 3], [0, 4]]),
+            (ShipType::Battleship, vec![[2, 9], [3, 9], [4, 9], [5, 9]]),
+            (ShipType::Cruiser, vec![[5, 2], [6, 2], [7, 2]]),
+            (ShipType::Submarine, vec![[9, 5], [9, 6], [9, 7]]),
+            (ShipType::Destroyer, vec![[4, 5], [4, 6]]),
+        ];
+        for (ship_type, tiles) in ships {
+            board.place_ship(ship_type, tiles).expect("failed to place a ship");
+        }
+        board
+    }
+
+    // shots needed to sink the whole sample fleet
+    fn play_out(difficulty: Difficulty, seed: u64) -> usize {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut board = sample_board();
+        let mut strategy = difficulty.strategy(&Ruleset::classic());
+        let mut shots = 0;
+        while !board.is_fleet_destroyed() {
+            let target = strategy
+                .choose_shot(&mut rng)
+                .expect("strategy ran out of shots before sinking the fleet");
+            let result = board.fire(target).expect("strategy fired at the same tile twice");
+            strategy.on_result(target, result);
+            shots += 1;
+        }
+        shots
+    }
+
+    #[test]
+    fn test_strategies_sink_the_fleet() {
+        for difficulty in Difficulty::ALL {
+            for seed in 0..5 {
+                assert!(play_out(difficulty, seed) <= BOARD_SIZE * BOARD_SIZE);
+            }
+        }
+    }
+
+    #[test]
+    fn test_strategies_sink_shaped_ships() {
+        let ruleset = Ruleset::house();
+        for difficulty in Difficulty::ALL {
+            for seed in 0..3 {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut board = random_fleet(&ruleset, PlacementMode::Uniform, &mut rng).expect("fleet should fit");
+                let mut strategy = difficulty.strategy(&ruleset);
+                while !board.is_fleet_destroyed() {
+                    let target = strategy
+                        .choose_shot(&mut rng)
+                        .expect("strategy ran out of shots before sinking the fleet");
+                    let result = board.fire(target).expect("strategy fired at the same tile twice");
+                    strategy.on_result(target, result);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_salvos_never_repeat_a_tile() {
+        for difficulty in Difficulty::ALL {
+            let mut rng = StdRng::seed_from_u64(0);
+            let mut board = sample_board();
+            let mut strategy = difficulty.strategy(&Ruleset::classic());
+            while !board.is_fleet_destroyed() {
+                let count = board.count_ships_afloat().min(board.count_unshot_tiles());
+                let targets = strategy.choose_salvo(count, &mut rng);
+                assert_eq!(targets.len(), count);
+                let results = targets
+                    .iter()
+                    .map(|&target| board.fire(target).expect("salvo fired at the same tile twice"))
+                    .collect::<Vec<_>>();
+                for (&target, result) in targets.iter().zip(results) {
+                    strategy.on_result(target, result);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_smarter_strategies_need_fewer_shots() {
+        let average = |difficulty| (0..20).map(|seed| play_out(difficulty, seed)).sum::<usize>() / 20;
+        let random = average(Difficulty::Random);
+        let hunt_target = average(Difficulty::HuntTarget);
+        let density = average(Difficulty::Density);
+        assert!(hunt_target < random, "hunt/target {hunt_target} vs random {random}");
+        assert!(density <= hunt_target, "density {density} vs hunt/target {hunt_target}");
+    }
+
+    #[test]
+    fn test_tracker_blocks_tiles_around_sunk_ships() {
+        let mut tracker = Tracker::new(&Ruleset::classic());
+        tracker.record([4, 5], ShotResult::Hit);
+        tracker.record([4, 6], ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(tracker.get([4, 5]), Cell::Sunk);
+        assert_eq!(tracker.get([3, 4]), Cell::Blocked);
+        assert_eq!(tracker.get([5, 7]), Cell::Blocked);
+        assert_eq!(tracker.get([6, 6]), Cell::Unknown);
+        assert!(!tracker.get_remaining().contains(&ShipType::Destroyer));
+
+        // corners of a sunk ship stay open when ships may touch diagonally
+        let mut tracker = Tracker::new(&Ruleset::classic().with_adjacency(Adjacency::Diagonal));
+        tracker.record([4, 5], ShotResult::Hit);
+        tracker.record([4, 6], ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(tracker.get([3, 5]), Cell::Blocked);
+        assert_eq!(tracker.get([3, 4]), Cell::Unknown);
+
+        // two touching ships, only the destroyer's tiles are sunk
+        let mut tracker = Tracker::new(&Ruleset::classic().with_adjacency(Adjacency::Any));
+        for tile in [[2, 2], [2, 3], [2, 4], [3, 2]] {
+            tracker.record(tile, ShotResult::Hit);
+        }
+        tracker.record([4, 2], ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(tracker.get([3, 2]), Cell::Sunk);
+        assert_eq!(tracker.get([2, 2]), Cell::Hit);
+        assert_eq!(tracker.get([5, 2]), Cell::Unknown);
+        assert_eq!(tracker.open_hits().len(), 3);
+    }
+
+    #[test]
+    fn test_random_placement_reaches_every_placement() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // not square, so mixing up rows and columns shows
+        let size = [6, 9];
+        for shape in [Shape::line(3), ShipType::LShip.get_shape()] {
+            let orientations = shape.orientations();
+            let all = density::placements(&orientations, size);
+            let mut seen = std::collections::HashSet::new();
+            for _ in 0..5000 {
+                let placement =
+                    density::random_placement(&orientations, size, &mut rng).expect("ship fits on the board");
+                assert!(placement.tiles().all(|[i, j]| i < 6 && j < 9));
+                assert!(shape.matches(&placement.tiles().collect::<Vec<_>>()));
+                seen.insert(placement.tiles().collect::<Vec<_>>());
+            }
+            assert_eq!(seen.len(), all.count());
+        }
+        assert!(density::random_placement(&Shape::line(10).orientations(), size, &mut rng).is_none());
+        // only fits along the rows
+        let orientations = Shape::line(7).orientations();
+        let placement =
+            density::random_placement(&orientations, size, &mut rng).expect("ship fits on the board");
+        assert!(placement.tiles().all(|[i, _]| i == placement.tiles().next().unwrap()[0]));
+    }
+
+    #[test]
+    fn test_difficulty_names() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(difficulty.get_str().parse::<Difficulty>(), Ok(difficulty));
+        }
+        assert!("impossible".parse::<Difficulty>().is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+
+use super::{neighbours, Cell, Strategy, Tracker};
+use crate::game::ruleset::Ruleset;
+use crate::game::shape::Shape;
+use crate::game::ShotResult;
+
+// extra weight of a placement for every open hit it covers
+const HIT_WEIGHT: u32 = 4;
+
+// fires at the tile covered by the most placements of the remaining ships that are still possible
+pub struct DensityStrategy {
+    tracker: Tracker,
+}
+impl DensityStrategy {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        Self {
+            tracker: Tracker::new(ruleset),
+        }
+    }
+}
+impl Strategy for DensityStrategy {
+    fn choose_shot(&mut self, rng: &mut dyn RngCore) -> Option<[usize; 2]> {
+        let targeting = !self.tracker.open_hits().is_empty();
+        let mut map = density(&self.tracker, targeting);
+        // knowledge that doesn't match any placement means hits were misread, hunt instead
+        if targeting && map.iter().flatten().all(|&count| count == 0) {
+            map = density(&self.tracker, false);
+        }
+        let best = self
+            .tracker
+            .unknown()
+            .map(|[i, j]| map[i][j])
+            .max()?;
+        let candidates = self
+            .tracker
+            .unknown()
+            .filter(|&[i, j]| map[i][j] == best)
+            .collect::<Vec<_>>();
+        candidates.choose(rng).copied()
+    }
+
+    fn on_result(&mut self, target: [usize; 2], result: ShotResult) {
+        self.tracker.record(target, result);
+    }
+}
+
+// for every tile, how many legal placements of the remaining ships cover it - when `targeting`
+// only placements going through open hits are counted
+pub fn density(tracker: &Tracker, targeting: bool) -> Vec<Vec<u32>> {
+    let board_size = tracker.get_size();
+    let mut map = vec![vec![0; board_size[1]]; board_size[0]];
+    let any_hits = !tracker.open_hits().is_empty();
+    for ship in tracker.get_remaining() {
+        let orientations = ship.get_shape().orientations();
+        for placement in placements(&orientations, board_size) {
+            let Some(hits) = placement_hits(tracker, &placement, any_hits) else {
+                continue;
+            };
+            if targeting && hits == 0 {
+                continue;
+            }
+            let weight = 1 + hits * HIT_WEIGHT;
+            for [i, j] in placement.tiles() {
+                if tracker.get([i, j]) == Cell::Unknown {
+                    map[i][j] += weight;
+                }
+            }
+        }
+    }
+    map
+}
+
+// ship in one of the orientations of its shape, with the corner of the box around it at `start`
+#[derive(Debug, Clone, Copy)]
+pub struct Placement<'a> {
+    orientation: &'a Shape,
+    start: [usize; 2],
+}
+impl<'a> Placement<'a> {
+    pub fn tiles(&self) -> impl Iterator<Item = [usize; 2]> + 'a {
+        self.orientation.at(self.start)
+    }
+
+    fn contains(&self, tile: [usize; 2]) -> bool {
+        self.tiles().any(|own| own == tile)
+    }
+}
+
+// corners of the box around an orientation that keep it on a board of the given size,
+// one row after another
+fn starts(orientation: &Shape, [height, width]: [usize; 2]) -> [usize; 2] {
+    let [rows, cols] = orientation.get_dimensions();
+    [(height + 1).saturating_sub(rows), (width + 1).saturating_sub(cols)]
+}
+
+// every placement on an empty board of the given size of a ship with these orientations of its
+// shape, in the same order - a straight ship lies along the rows first
+pub fn placements(orientations: &[Shape], board_size: [usize; 2]) -> impl Iterator<Item = Placement<'_>> {
+    orientations.iter().flat_map(move |orientation| {
+        let [rows, cols] = starts(orientation, board_size);
+        (0..rows * cols).map(move |index| Placement {
+            orientation,
+            start: [index / cols, index % cols],
+        })
+    })
+}
+
+// one of `placements(orientations, board_size)` picked with equal chances, without going through
+// all of them
+pub fn random_placement<'a>(
+    orientations: &'a [Shape],
+    board_size: [usize; 2],
+    rng: &mut dyn RngCore,
+) -> Option<Placement<'a>> {
+    let counts = orientations
+        .iter()
+        .map(|orientation| {
+            let [rows, cols] = starts(orientation, board_size);
+            rows * cols
+        })
+        .collect::<Vec<_>>();
+    let total = counts.iter().sum::<usize>();
+    if orientations.iter().all(|orientation| orientation.get_size() == 0) || total == 0 {
+        return None;
+    }
+    let mut index = rng.gen_range(0..total);
+    for (orientation, count) in orientations.iter().zip(counts) {
+        if index < count {
+            let [_, cols] = starts(orientation, board_size);
+            return Some(Placement {
+                orientation,
+                start: [index / cols, index % cols],
+            });
+        }
+        index -= count;
+    }
+    None
+}
+
+// number of open hits the placement covers, None if the ship can't be there
+fn placement_hits(tracker: &Tracker, placement: &Placement, any_hits: bool) -> Option<u32> {
+    let mut hits = 0;
+    for tile in placement.tiles() {
+        match tracker.get(tile) {
+            Cell::Unknown => (),
+            Cell::Hit => hits += 1,
+            Cell::Miss | Cell::Sunk | Cell::Blocked => return None,
+        }
+    }
+    if !any_hits {
+        return Some(hits);
+    }
+    // a hit right next to the ship would belong to a ship touching it, unless that's allowed
+    let adjacency = tracker.get_adjacency();
+    let mut surroundings = placement.tiles().flat_map(|own| {
+        neighbours(own, tracker.get_size()).filter(move |&tile| adjacency.forbids(own, tile))
+    });
+    if surroundings.any(|tile| tracker.get(tile) == Cell::Hit && !placement.contains(tile)) {
+        return None;
+    }
+    Some(hits)
+}
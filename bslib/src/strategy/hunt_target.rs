@@ -0,0 +1,78 @@
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::RngCore;
+
+use super::{orthogonal_neighbours, Cell, Strategy, Tracker};
+use crate::game::ruleset::Ruleset;
+use crate::game::ShotResult;
+
+// hunts on a checkerboard until something is hit, then fires around the hits until the ship sinks
+pub struct HuntTargetStrategy {
+    tracker: Tracker,
+}
+impl HuntTargetStrategy {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        Self {
+            tracker: Tracker::new(ruleset),
+        }
+    }
+
+    fn target(&self) -> Vec<[usize; 2]> {
+        let hits = self.tracker.open_hits();
+        let unknown = |tile: &[usize; 2]| self.tracker.get(*tile) == Cell::Unknown;
+        let size = self.tracker.get_size();
+        // with two hits in a line only the ends of that line are worth trying, unless a ship
+        // that isn't straight could bend away from it
+        let straight = self.tracker.get_remaining().iter().all(|ship| ship.get_shape().is_line());
+        if straight && hits.len() >= 2 {
+            let axis = if hits.iter().all(|hit| hit[0] == hits[0][0]) {
+                Some(1)
+            } else if hits.iter().all(|hit| hit[1] == hits[0][1]) {
+                Some(0)
+            } else {
+                None
+            };
+            if let Some(axis) = axis {
+                let ends = hits
+                    .iter()
+                    .flat_map(|&hit| orthogonal_neighbours(hit, size))
+                    .filter(|tile| tile[1 - axis] == hits[0][1 - axis] && unknown(tile))
+                    .collect::<Vec<_>>();
+                if !ends.is_empty() {
+                    return ends;
+                }
+            }
+        }
+        hits.iter()
+            .flat_map(|&hit| orthogonal_neighbours(hit, size))
+            .filter(unknown)
+            .collect()
+    }
+
+    fn hunt(&self, rng: &mut dyn RngCore) -> Option<[usize; 2]> {
+        // the smallest ship left can't fit between tiles of this spacing
+        let spacing = self
+            .tracker
+            .get_remaining()
+            .iter()
+            .map(|ship| ship.get_shape().get_diagonal_span())
+            .min()
+            .unwrap_or(1);
+        self.tracker
+            .unknown()
+            .filter(|[i, j]| (i + j) % spacing == 0)
+            .choose(rng)
+            .or_else(|| self.tracker.unknown().choose(rng))
+    }
+}
+impl Strategy for HuntTargetStrategy {
+    fn choose_shot(&mut self, rng: &mut dyn RngCore) -> Option<[usize; 2]> {
+        match self.target().choose(rng) {
+            Some(&tile) => Some(tile),
+            None => self.hunt(rng),
+        }
+    }
+
+    fn on_result(&mut self, target: [usize; 2], result: ShotResult) {
+        self.tracker.record(target, result);
+    }
+}
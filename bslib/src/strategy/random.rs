@@ -0,0 +1,30 @@
+use rand::seq::IteratorRandom;
+use rand::RngCore;
+
+use super::{Cell, Strategy, Tracker};
+use crate::game::ruleset::Ruleset;
+use crate::game::ShotResult;
+
+// fires at any tile it hasn't fired at yet
+pub struct RandomStrategy {
+    tracker: Tracker,
+}
+impl RandomStrategy {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        Self {
+            tracker: Tracker::new(ruleset),
+        }
+    }
+}
+impl Strategy for RandomStrategy {
+    fn choose_shot(&mut self, rng: &mut dyn RngCore) -> Option<[usize; 2]> {
+        self.tracker
+            .tiles()
+            .filter(|&tile| matches!(self.tracker.get(tile), Cell::Unknown | Cell::Blocked))
+            .choose(rng)
+    }
+
+    fn on_result(&mut self, target: [usize; 2], result: ShotResult) {
+        self.tracker.record(target, result);
+    }
+}
@@ -1,16 +1,24 @@
 use error::{PacketReaderError, RequestError};
 use serde::{Deserialize, Serialize};
-use tokio::io::{self, AsyncBufReadExt, AsyncWriteExt, ReadHalf};
+use tokio::io::{self, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf};
 use tokio::io::{BufReader, WriteHalf};
-use tokio::net::TcpStream;
 
 use self::error::PacketError;
+use crate::game::ruleset::Ruleset;
+use crate::game::movement::Direction;
+use crate::game::weapon::{Arsenal, Strike, Weapon};
+use crate::game::ShotResult;
+use crate::game_record::PlacedShip;
 
 pub mod error;
 
 pub const PACKET_HEADER: &str = "#bs";
 pub const PACKET_END: &str = "\n#end\n";
 
+// any stream packets can be sent over - tcp or unix socket
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
 pub trait Jsonable: Serialize + for <'a> Deserialize<'a> {
     fn from_json(json: &str) -> Result<Self, serde_json::Error> {
         serde_json::from_str::<Self>(json)
@@ -29,34 +37,626 @@ impl Jsonable for TestBody {}
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectBody {
     nick: String,
+    // name of the preset to play with, the server picks its default without one
+    #[serde(default)]
+    ruleset: Option<String>,
 }
 impl ConnectBody {
     pub fn new(nick: String) -> Self {
-        Self { nick }
+        Self { nick, ruleset: None }
+    }
+
+    pub fn with_ruleset(mut self, ruleset: Option<String>) -> Self {
+        self.ruleset = ruleset;
+        self
     }
 
     fn get_nick(&self) -> &str {
         &self.nick
     }
+
+    fn get_ruleset(&self) -> Option<&str> {
+        self.ruleset.as_deref()
+    }
 }
 impl Jsonable for ConnectBody {}
 
+// sent right after connecting and once more whenever someone takes an empty seat - in team
+// games teammates are announced the same way, `seat` tells them apart
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectRespBody {
-    opponent: bool
+    opponent: bool,
+    #[serde(default)]
+    opponent_nick: Option<String>,
+    #[serde(default)]
+    bot: bool,
+    #[serde(default)]
+    seat: usize,
+    // rules of the game the player was seated at
+    #[serde(default)]
+    ruleset: Ruleset,
 }
 impl ConnectRespBody {
     pub fn new(opponent: bool) -> Self {
-        Self { opponent }
+        Self {
+            opponent,
+            opponent_nick: None,
+            bot: false,
+            seat: 0,
+            ruleset: Ruleset::default(),
+        }
+    }
+
+    pub fn with_opponent(nick: String, bot: bool) -> Self {
+        Self {
+            opponent: true,
+            opponent_nick: Some(nick),
+            bot,
+            seat: 0,
+            ruleset: Ruleset::default(),
+        }
+    }
+
+    pub fn with_seat(mut self, seat: usize) -> Self {
+        self.seat = seat;
+        self
+    }
+
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    pub fn get_opponent(&self) -> bool {
+        self.opponent
+    }
+
+    pub fn get_opponent_nick(&self) -> Option<&str> {
+        self.opponent_nick.as_deref()
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot
+    }
+
+    pub fn get_seat(&self) -> usize {
+        self.seat
+    }
+
+    pub fn get_ruleset(&self) -> &Ruleset {
+        &self.ruleset
     }
 }
 impl Jsonable for ConnectRespBody {}
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlaceFleetBody {
+    ships: Vec<PlacedShip>,
+}
+impl PlaceFleetBody {
+    pub fn new(ships: Vec<PlacedShip>) -> Self {
+        Self { ships }
+    }
+
+    pub fn get_ships(&self) -> &[PlacedShip] {
+        &self.ships
+    }
+}
+impl Jsonable for PlaceFleetBody {}
+
+// both fleets are placed and the shooting begins
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameStartBody {
+    your_turn: bool,
+    // shots in the receiver's first salvo, 0 when the game isn't played with salvos
+    #[serde(default)]
+    salvo: usize,
+    // special weapons the receiver starts with
+    #[serde(default)]
+    arsenal: Arsenal,
+    // receiver's own seat, the one the other players' results refer to
+    #[serde(default)]
+    seat: usize,
+}
+impl GameStartBody {
+    pub fn new(your_turn: bool) -> Self {
+        Self {
+            your_turn,
+            salvo: 0,
+            arsenal: Arsenal::default(),
+            seat: 0,
+        }
+    }
+
+    pub fn with_salvo(mut self, salvo: usize) -> Self {
+        self.salvo = salvo;
+        self
+    }
+
+    pub fn with_arsenal(mut self, arsenal: Arsenal) -> Self {
+        self.arsenal = arsenal;
+        self
+    }
+
+    pub fn with_seat(mut self, seat: usize) -> Self {
+        self.seat = seat;
+        self
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+
+    pub fn get_salvo(&self) -> usize {
+        self.salvo
+    }
+
+    pub fn get_arsenal(&self) -> Arsenal {
+        self.arsenal
+    }
+
+    pub fn get_seat(&self) -> usize {
+        self.seat
+    }
+}
+impl Jsonable for GameStartBody {}
+
+// `opponent` is the seat fired at, it can be left out when there's only one opponent left
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FireBody {
+    target: [usize; 2],
+    #[serde(default)]
+    opponent: Option<usize>,
+}
+impl FireBody {
+    pub fn new(target: [usize; 2]) -> Self {
+        Self { target, opponent: None }
+    }
+
+    pub fn with_opponent(mut self, opponent: usize) -> Self {
+        self.opponent = Some(opponent);
+        self
+    }
+
+    pub fn get_target(&self) -> [usize; 2] {
+        self.target
+    }
+
+    pub fn get_opponent(&self) -> Option<usize> {
+        self.opponent
+    }
+}
+impl Jsonable for FireBody {}
+
+// sent to every player after every shot - `yours` tells whether the receiver fired it,
+// `player` and `opponent` are the seats of the one who fired and the one fired at
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FireResultBody {
+    yours: bool,
+    target: [usize; 2],
+    result: ShotResult,
+    your_turn: bool,
+    #[serde(default)]
+    player: usize,
+    #[serde(default)]
+    opponent: usize,
+}
+impl FireResultBody {
+    pub fn new(yours: bool, target: [usize; 2], result: ShotResult, your_turn: bool) -> Self {
+        Self {
+            yours,
+            target,
+            result,
+            your_turn,
+            player: 0,
+            opponent: 0,
+        }
+    }
+
+    pub fn with_seats(mut self, player: usize, opponent: usize) -> Self {
+        self.player = player;
+        self.opponent = opponent;
+        self
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn get_opponent(&self) -> usize {
+        self.opponent
+    }
+
+    pub fn is_yours(&self) -> bool {
+        self.yours
+    }
+
+    pub fn get_target(&self) -> [usize; 2] {
+        self.target
+    }
+
+    pub fn get_result(&self) -> ShotResult {
+        self.result
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+}
+impl Jsonable for FireResultBody {}
+
+// all shots of a turn in a salvo game, fired together or not at all
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalvoBody {
+    targets: Vec<[usize; 2]>,
+    #[serde(default)]
+    opponent: Option<usize>,
+}
+impl SalvoBody {
+    pub fn new(targets: Vec<[usize; 2]>) -> Self {
+        Self { targets, opponent: None }
+    }
+
+    pub fn with_opponent(mut self, opponent: usize) -> Self {
+        self.opponent = Some(opponent);
+        self
+    }
+
+    pub fn get_targets(&self) -> &[[usize; 2]] {
+        &self.targets
+    }
+
+    pub fn get_opponent(&self) -> Option<usize> {
+        self.opponent
+    }
+}
+impl Jsonable for SalvoBody {}
+
+// sent to every player after every salvo - `salvo` is how many shots the receiver fires
+// in their next one
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SalvoResultBody {
+    yours: bool,
+    shots: Vec<([usize; 2], ShotResult)>,
+    your_turn: bool,
+    salvo: usize,
+    #[serde(default)]
+    player: usize,
+    #[serde(default)]
+    opponent: usize,
+}
+impl SalvoResultBody {
+    pub fn new(yours: bool, shots: Vec<([usize; 2], ShotResult)>, your_turn: bool, salvo: usize) -> Self {
+        Self {
+            yours,
+            shots,
+            your_turn,
+            salvo,
+            player: 0,
+            opponent: 0,
+        }
+    }
+
+    pub fn with_seats(mut self, player: usize, opponent: usize) -> Self {
+        self.player = player;
+        self.opponent = opponent;
+        self
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn get_opponent(&self) -> usize {
+        self.opponent
+    }
+
+    pub fn is_yours(&self) -> bool {
+        self.yours
+    }
+
+    pub fn get_shots(&self) -> &[([usize; 2], ShotResult)] {
+        &self.shots
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+
+    pub fn get_salvo(&self) -> usize {
+        self.salvo
+    }
+}
+impl Jsonable for SalvoResultBody {}
+
+// special weapon used instead of the turn's shot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeaponBody {
+    weapon: Weapon,
+    target: [usize; 2],
+    #[serde(default)]
+    opponent: Option<usize>,
+}
+impl WeaponBody {
+    pub fn new(weapon: Weapon, target: [usize; 2]) -> Self {
+        Self {
+            weapon,
+            target,
+            opponent: None,
+        }
+    }
+
+    pub fn with_opponent(mut self, opponent: usize) -> Self {
+        self.opponent = Some(opponent);
+        self
+    }
+
+    pub fn get_opponent(&self) -> Option<usize> {
+        self.opponent
+    }
+
+    pub fn get_weapon(&self) -> Weapon {
+        self.weapon
+    }
+
+    pub fn get_target(&self) -> [usize; 2] {
+        self.target
+    }
+}
+impl Jsonable for WeaponBody {}
+
+// sent to every player after a weapon was used - `arsenal` is what the receiver has left
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeaponResultBody {
+    yours: bool,
+    weapon: Weapon,
+    target: [usize; 2],
+    strike: Strike,
+    your_turn: bool,
+    arsenal: Arsenal,
+    #[serde(default)]
+    player: usize,
+    #[serde(default)]
+    opponent: usize,
+}
+impl WeaponResultBody {
+    pub fn new(yours: bool, weapon: Weapon, target: [usize; 2], strike: Strike, your_turn: bool) -> Self {
+        Self {
+            yours,
+            weapon,
+            target,
+            strike,
+            your_turn,
+            arsenal: Arsenal::default(),
+            player: 0,
+            opponent: 0,
+        }
+    }
+
+    pub fn with_arsenal(mut self, arsenal: Arsenal) -> Self {
+        self.arsenal = arsenal;
+        self
+    }
+
+    pub fn with_seats(mut self, player: usize, opponent: usize) -> Self {
+        self.player = player;
+        self.opponent = opponent;
+        self
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn get_opponent(&self) -> usize {
+        self.opponent
+    }
+
+    pub fn is_yours(&self) -> bool {
+        self.yours
+    }
+
+    pub fn get_weapon(&self) -> Weapon {
+        self.weapon
+    }
+
+    pub fn get_target(&self) -> [usize; 2] {
+        self.target
+    }
+
+    pub fn get_strike(&self) -> &Strike {
+        &self.strike
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+
+    pub fn get_arsenal(&self) -> Arsenal {
+        self.arsenal
+    }
+}
+impl Jsonable for WeaponResultBody {}
+
+// move of the sender's undamaged ship on the tile instead of the turn's shot
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveBody {
+    tile: [usize; 2],
+    direction: Direction,
+}
+impl MoveBody {
+    pub fn new(tile: [usize; 2], direction: Direction) -> Self {
+        Self { tile, direction }
+    }
+
+    pub fn get_tile(&self) -> [usize; 2] {
+        self.tile
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+}
+impl Jsonable for MoveBody {}
+
+// sent to every player after a ship was moved - only its owner and their team are told which
+// ship and where, `player` is the owner's seat
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveResultBody {
+    yours: bool,
+    #[serde(default)]
+    moved: Option<([usize; 2], Direction)>,
+    your_turn: bool,
+    #[serde(default)]
+    player: usize,
+}
+impl MoveResultBody {
+    pub fn new(yours: bool, your_turn: bool) -> Self {
+        Self {
+            yours,
+            moved: None,
+            your_turn,
+            player: 0,
+        }
+    }
+
+    pub fn with_player(mut self, player: usize) -> Self {
+        self.player = player;
+        self
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn with_move(mut self, tile: [usize; 2], direction: Direction) -> Self {
+        self.moved = Some((tile, direction));
+        self
+    }
+
+    pub fn is_yours(&self) -> bool {
+        self.yours
+    }
+
+    // tile of the moved ship before the move and its direction, None for the opponents
+    pub fn get_move(&self) -> Option<([usize; 2], Direction)> {
+        self.moved
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+}
+impl Jsonable for MoveResultBody {}
+
+// sent to the players that stay when someone leaves a game that goes on without them -
+// `your_turn` when the leaving player's turn passed on to the receiver, `salvo` is the size of
+// the receiver's next salvo, 0 without salvos
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerLeftBody {
+    player: usize,
+    your_turn: bool,
+    #[serde(default)]
+    salvo: usize,
+}
+impl PlayerLeftBody {
+    pub fn new(player: usize, your_turn: bool) -> Self {
+        Self {
+            player,
+            your_turn,
+            salvo: 0,
+        }
+    }
+
+    pub fn with_salvo(mut self, salvo: usize) -> Self {
+        self.salvo = salvo;
+        self
+    }
+
+    pub fn get_player(&self) -> usize {
+        self.player
+    }
+
+    pub fn is_your_turn(&self) -> bool {
+        self.your_turn
+    }
+
+    pub fn get_salvo(&self) -> usize {
+        self.salvo
+    }
+}
+impl Jsonable for PlayerLeftBody {}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameOverBody {
+    won: bool,
+    reason: String,
+}
+impl GameOverBody {
+    pub fn new(won: bool, reason: String) -> Self {
+        Self { won, reason }
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.won
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+}
+impl Jsonable for GameOverBody {}
+
+// request was understood but couldn't be carried out, e.g. firing out of turn
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    msg: String,
+}
+impl ErrorBody {
+    pub fn new(msg: String) -> Self {
+        Self { msg }
+    }
+
+    pub fn get_msg(&self) -> &str {
+        &self.msg
+    }
+}
+impl Jsonable for ErrorBody {}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShutdownBody {
+    reason: String,
+}
+impl ShutdownBody {
+    pub fn new(reason: String) -> Self {
+        Self { reason }
+    }
+
+    pub fn get_reason(&self) -> &str {
+        &self.reason
+    }
+}
+impl Jsonable for ShutdownBody {}
+
 #[derive(PartialEq, Eq, Debug)]
 pub enum ProtocolCommand {
     Test,
     Connect,
     ConnectResp,
+    Shutdown,
+    PlaceFleet,
+    GameStart,
+    Fire,
+    FireResult,
+    Salvo,
+    SalvoResult,
+    Weapon,
+    WeaponResult,
+    Move,
+    MoveResult,
+    PlayerLeft,
+    GameOver,
+    Error,
 }
 impl ProtocolCommand {
     pub fn get_str(&self) -> Option<&str> {
@@ -64,6 +664,20 @@ impl ProtocolCommand {
             Self::Connect => Some("connect"),
             Self::ConnectResp => Some("connect_resp"),
             Self::Test => Some("test"),
+            Self::Shutdown => Some("shutdown"),
+            Self::PlaceFleet => Some("place_fleet"),
+            Self::GameStart => Some("game_start"),
+            Self::Fire => Some("fire"),
+            Self::FireResult => Some("fire_result"),
+            Self::Salvo => Some("salvo"),
+            Self::SalvoResult => Some("salvo_result"),
+            Self::Weapon => Some("weapon"),
+            Self::WeaponResult => Some("weapon_result"),
+            Self::Move => Some("move"),
+            Self::MoveResult => Some("move_result"),
+            Self::PlayerLeft => Some("player_left"),
+            Self::GameOver => Some("game_over"),
+            Self::Error => Some("error"),
         }
     }
 
@@ -71,6 +685,20 @@ impl ProtocolCommand {
         match cmd {
             "connect" => Some(Self::Connect),
             "connect_resp" => Some(Self::ConnectResp),
+            "shutdown" => Some(Self::Shutdown),
+            "place_fleet" => Some(Self::PlaceFleet),
+            "game_start" => Some(Self::GameStart),
+            "fire" => Some(Self::Fire),
+            "fire_result" => Some(Self::FireResult),
+            "salvo" => Some(Self::Salvo),
+            "salvo_result" => Some(Self::SalvoResult),
+            "weapon" => Some(Self::Weapon),
+            "weapon_result" => Some(Self::WeaponResult),
+            "move" => Some(Self::Move),
+            "move_result" => Some(Self::MoveResult),
+            "player_left" => Some(Self::PlayerLeft),
+            "game_over" => Some(Self::GameOver),
+            "error" => Some(Self::Error),
             _ => None,
         }
     }
@@ -81,6 +709,20 @@ pub enum PacketBody {
     Test(Box<TestBody>),
     Connect(Box<ConnectBody>),
     ConnectResp(Box<ConnectRespBody>),
+    Shutdown(Box<ShutdownBody>),
+    PlaceFleet(Box<PlaceFleetBody>),
+    GameStart(Box<GameStartBody>),
+    Fire(Box<FireBody>),
+    FireResult(Box<FireResultBody>),
+    Salvo(Box<SalvoBody>),
+    SalvoResult(Box<SalvoResultBody>),
+    Weapon(Box<WeaponBody>),
+    WeaponResult(Box<WeaponResultBody>),
+    Move(Box<MoveBody>),
+    MoveResult(Box<MoveResultBody>),
+    PlayerLeft(Box<PlayerLeftBody>),
+    GameOver(Box<GameOverBody>),
+    Error(Box<ErrorBody>),
 }
 impl PacketBody {
     pub fn get_cmd(&self) -> ProtocolCommand {
@@ -88,6 +730,20 @@ impl PacketBody {
             Self::Test(_) => ProtocolCommand::Test,
             Self::Connect(_) => ProtocolCommand::Connect,
             Self::ConnectResp(_) => ProtocolCommand::ConnectResp,
+            Self::Shutdown(_) => ProtocolCommand::Shutdown,
+            Self::PlaceFleet(_) => ProtocolCommand::PlaceFleet,
+            Self::GameStart(_) => ProtocolCommand::GameStart,
+            Self::Fire(_) => ProtocolCommand::Fire,
+            Self::FireResult(_) => ProtocolCommand::FireResult,
+            Self::Salvo(_) => ProtocolCommand::Salvo,
+            Self::SalvoResult(_) => ProtocolCommand::SalvoResult,
+            Self::Weapon(_) => ProtocolCommand::Weapon,
+            Self::WeaponResult(_) => ProtocolCommand::WeaponResult,
+            Self::Move(_) => ProtocolCommand::Move,
+            Self::MoveResult(_) => ProtocolCommand::MoveResult,
+            Self::PlayerLeft(_) => ProtocolCommand::PlayerLeft,
+            Self::GameOver(_) => ProtocolCommand::GameOver,
+            Self::Error(_) => ProtocolCommand::Error,
         }
     }
 
@@ -96,14 +752,34 @@ impl PacketBody {
             Self::Test(body) => body.to_string(),
             Self::Connect(body) => body.to_string(),
             Self::ConnectResp(body) => body.to_string(),
+            Self::Shutdown(body) => body.to_string(),
+            Self::PlaceFleet(body) => body.to_string(),
+            Self::GameStart(body) => body.to_string(),
+            Self::Fire(body) => body.to_string(),
+            Self::FireResult(body) => body.to_string(),
+            Self::Salvo(body) => body.to_string(),
+            Self::SalvoResult(body) => body.to_string(),
+            Self::Weapon(body) => body.to_string(),
+            Self::WeaponResult(body) => body.to_string(),
+            Self::Move(body) => body.to_string(),
+            Self::MoveResult(body) => body.to_string(),
+            Self::PlayerLeft(body) => body.to_string(),
+            Self::GameOver(body) => body.to_string(),
+            Self::Error(body) => body.to_string(),
         }
     }
 
     pub fn get_nick(&self) -> Result<&str, PacketError> {
         match self {
-            Self::Test(_) => Err(PacketError::new("No such field on this type of body")),
             Self::Connect(body) => Ok(body.get_nick()),
-            Self::ConnectResp(_) => Err(PacketError::new("No such field on this type of body"))
+            _ => Err(PacketError::new("No such field on this type of body")),
+        }
+    }
+
+    pub fn get_ruleset(&self) -> Result<Option<&str>, PacketError> {
+        match self {
+            Self::Connect(body) => Ok(body.get_ruleset()),
+            _ => Err(PacketError::new("No such field on this type of body")),
         }
     }
 }
@@ -130,6 +806,15 @@ impl<S: BodyState> Packet<S> {
     }
 }
 impl Packet<Ready> {
+    // packet with the command matching the body
+    pub fn from_body(body: PacketBody) -> Self {
+        Packet {
+            command: body.get_cmd(),
+            body: Some(body),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
     pub fn as_bytes(&self) -> Result<Box<[u8]>, serde_json::Error> {
         if let Some(body) = &self.body {
             let req = PACKET_HEADER.to_string()
@@ -138,7 +823,6 @@ impl Packet<Ready> {
                 + "\n"
                 + body.to_string()?.as_str()
                 + PACKET_END;
-                println!("packet as string: {:#?}", req);
             let req = req.into_bytes();
             Ok(req.into_boxed_slice())
         } else {
@@ -176,14 +860,21 @@ impl Packet<NotReady> {
     }
 }
 
-pub struct Response {}
+pub struct Response {
+    packet: Packet<Ready>,
+}
+impl Response {
+    pub fn get_packet(&self) -> &Packet<Ready> {
+        &self.packet
+    }
+}
 
-pub struct Requester {
-    packet_reader: PacketReader,
-    write_half: WriteHalf<TcpStream>,
+pub struct Requester<S> {
+    packet_reader: PacketReader<ReadHalf<S>>,
+    write_half: WriteHalf<S>,
 }
-impl Requester {
-    pub fn new(stream: TcpStream) -> Self {
+impl<S: AsyncRead + AsyncWrite> Requester<S> {
+    pub fn new(stream: S) -> Self {
         let (read_half, write_half) = tokio::io::split(stream);
         let packet_reader = PacketReader::new(BufReader::new(read_half));
         Self {
@@ -193,34 +884,61 @@ impl Requester {
     }
 
     pub async fn send_request(&mut self, request: Packet<Ready>) -> Result<Response, RequestError> {
-        println!("request: {:#?}", request);
         self.write_half.write_all(&request.as_bytes()?).await?;
-        pause();
         self.write_half.flush().await?;
         let response = self.packet_reader.read_packet().await?;
-        println!("{:#?}", response);
         match response {
-            Some(_) => Ok(Response {}),
+            Some(packet) => match packet.get_body()? {
+                PacketBody::Shutdown(body) => Err(RequestError::new(format!(
+                    "Server is shutting down: {}",
+                    body.get_reason()
+                ))),
+                PacketBody::Error(body) => Err(RequestError::new(String::from(body.get_msg()))),
+                _ => Ok(Response { packet }),
+            },
             None => Err(RequestError::new(String::from("Response not received"))),
         }
     }
-}
 
-fn pause() {
-    dbg!("Pausing! Press enter to continue...");
+    // send a packet without waiting for anything in return
+    pub async fn send(&mut self, packet: Packet<Ready>) -> Result<(), RequestError> {
+        self.write_half.write_all(&packet.as_bytes()?).await?;
+        self.write_half.flush().await?;
+        Ok(())
+    }
+
+    // next packet pushed by the server, None when the connection was closed
+    pub async fn receive(&mut self) -> Result<Option<Packet<Ready>>, RequestError> {
+        Ok(self.packet_reader.read_packet().await?)
+    }
 
-    let mut buffer = String::new();
+    // split into reading and writing side, so packets can be received on another task
+    pub fn into_split(self) -> (PacketReader<ReadHalf<S>>, PacketWriter<WriteHalf<S>>) {
+        (
+            self.packet_reader,
+            PacketWriter {
+                write_half: self.write_half,
+            },
+        )
+    }
+}
 
-    std::io::stdin()
-        .read_line(&mut buffer)
-        .expect("Failed to read line");
+pub struct PacketWriter<W> {
+    write_half: W,
+}
+impl<W: AsyncWrite + Unpin> PacketWriter<W> {
+    pub async fn send(&mut self, packet: Packet<Ready>) -> Result<(), RequestError> {
+        self.write_half.write_all(&packet.as_bytes()?).await?;
+        self.write_half.flush().await?;
+        Ok(())
+    }
 }
 
-pub struct PacketReader {
-    reader: BufReader<ReadHalf<TcpStream>>,
+pub struct PacketReader<R> {
+    reader: BufReader<R>,
 }
-impl PacketReader {
-    pub fn new(reader: BufReader<ReadHalf<TcpStream>>) -> Self {
+impl<R: AsyncRead + Unpin> PacketReader<R> {
+    pub fn new(reader: BufReader<R>) -> Self {
         Self { reader }
     }
 
@@ -235,7 +953,6 @@ impl PacketReader {
             .unwrap_or_else(|| panic!("failed to split a request: {:}", buf));
 
         let raw_body = self.read_body().await?;
-        println!("raw_body: {:#?}", raw_body);
         if header != PACKET_HEADER {
             return Err(PacketReaderError::new(String::from("Wrong packet header")));
         }
@@ -255,11 +972,66 @@ impl PacketReader {
                     let body = Box::new(ConnectRespBody::from_json(&raw_body)?);
                     Packet::new(cmd).load_body(PacketBody::ConnectResp(body))?
                 }
+                ProtocolCommand::Shutdown => {
+                    let body = Box::new(ShutdownBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Shutdown(body))?
+                }
+                ProtocolCommand::PlaceFleet => {
+                    let body = Box::new(PlaceFleetBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::PlaceFleet(body))?
+                }
+                ProtocolCommand::GameStart => {
+                    let body = Box::new(GameStartBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::GameStart(body))?
+                }
+                ProtocolCommand::Fire => {
+                    let body = Box::new(FireBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Fire(body))?
+                }
+                ProtocolCommand::FireResult => {
+                    let body = Box::new(FireResultBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::FireResult(body))?
+                }
+                ProtocolCommand::Salvo => {
+                    let body = Box::new(SalvoBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Salvo(body))?
+                }
+                ProtocolCommand::SalvoResult => {
+                    let body = Box::new(SalvoResultBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::SalvoResult(body))?
+                }
+                ProtocolCommand::Weapon => {
+                    let body = Box::new(WeaponBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Weapon(body))?
+                }
+                ProtocolCommand::WeaponResult => {
+                    let body = Box::new(WeaponResultBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::WeaponResult(body))?
+                }
+                ProtocolCommand::Move => {
+                    let body = Box::new(MoveBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Move(body))?
+                }
+                ProtocolCommand::MoveResult => {
+                    let body = Box::new(MoveResultBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::MoveResult(body))?
+                }
+                ProtocolCommand::PlayerLeft => {
+                    let body = Box::new(PlayerLeftBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::PlayerLeft(body))?
+                }
+                ProtocolCommand::GameOver => {
+                    let body = Box::new(GameOverBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::GameOver(body))?
+                }
+                ProtocolCommand::Error => {
+                    let body = Box::new(ErrorBody::from_json(&raw_body)?);
+                    Packet::new(cmd).load_body(PacketBody::Error(body))?
+                }
             })),
             None => Err(PacketReaderError::new(String::from("Wrong command name"))),
         };
         buf.clear();
-        println!("packet: {:#?}", packet);
         packet
     }
 
@@ -38,6 +38,13 @@ impl std::convert::From<PacketReaderError> for RequestError {
         }
     }
 }
+impl std::convert::From<PacketError> for RequestError {
+    fn from(value: PacketError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
 impl std::convert::From<serde_json::Error> for RequestError {
     fn from(value: serde_json::Error) -> Self {
         Self {
@@ -1,43 +1,522 @@
-use bslib::tcp_protocol::{ConnectBody, Packet, PacketBody, ProtocolCommand, Requester};
-use client_board::{error::PlacingShipsError, OwnBoard, Ship, ShipType};
+use std::io::stdin;
+
+use bslib::game::ruleset::Ruleset;
+use bslib::game::weapon::{Arsenal, Strike, Weapon};
+use bslib::game::{Ship, ShotResult};
+use bslib::placement::PlacementMode;
+use bslib::tcp_protocol::{
+    Connection, ConnectBody, FireBody, Packet, PacketBody, PlaceFleetBody, ProtocolCommand, Ready,
+    MoveBody, Requester, SalvoBody, WeaponBody,
+};
+use client_board::{encode_indexes, error::PlacingShipsError, side_by_side, turn_prompt, OwnBoard};
 use error::ConnectionError;
-use tokio::net::TcpStream;
+use table::Table;
+use tokio::sync::mpsc;
+
+use crate::tui::app::{Action, App};
+use crate::theme::Theme;
+use crate::tui::Tui;
 
-mod client_board;
-mod error;
+pub mod client_board;
+pub mod error;
+pub mod table;
 
-pub struct ClientPlayer<'a> {
+const LOG_LINES: usize = 6;
+
+pub struct ClientPlayer {
+    ruleset: Ruleset,
     ships: Vec<Ship>,
-    own_board: OwnBoard<'a>,
-    requester: Requester,
+    own_board: OwnBoard,
+    table: Table,
+    requester: Requester<Box<dyn Connection>>,
+    log: Vec<String>,
+    theme: Theme,
 }
-impl<'a> ClientPlayer<'a> {
-    pub fn new(stream: TcpStream) -> Self {
+impl ClientPlayer {
+    pub fn new(stream: Box<dyn Connection>) -> Self {
+        let ruleset = Ruleset::default();
         Self {
-            ships: vec![
-                Ship::new(ShipType::Carrier),
-                Ship::new(ShipType::Battleship),
-                Ship::new(ShipType::Cruiser),
-                Ship::new(ShipType::Submarine),
-                Ship::new(ShipType::Destroyer),
-            ],
-            own_board: OwnBoard::new(),
+            ships: ruleset.get_fleet().iter().map(|&ship_type| Ship::new(ship_type)).collect(),
+            own_board: OwnBoard::new(&ruleset),
+            table: Table::new(&ruleset, vec![String::new(); ruleset.get_players()]),
+            ruleset,
             requester: Requester::new(stream),
+            log: Vec::new(),
+            theme: Theme::default(),
         }
     }
 
-    pub fn set_up(&'a mut self) -> Result<(), PlacingShipsError> {
-        self.own_board.place_ships(&self.ships)?;
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.own_board = self.own_board.with_theme(theme);
+        self.table = self.table.with_theme(theme);
+        self.theme = theme;
+        self
+    }
+
+    // place ships by hand, or start from a generated layout when auto placing is on
+    pub fn set_up(&mut self, auto_place: Option<PlacementMode>) -> Result<(), PlacingShipsError> {
+        match auto_place {
+            Some(mode) => self.own_board.auto_place(&self.ships, mode, &mut rand::thread_rng())?,
+            None => self.own_board.place_ships(&self.ships)?,
+        }
         Ok(())
     }
 
-    pub async fn connect(&mut self, nick: String) -> Result<(), ConnectionError> {
+    // join a game with the given ruleset preset, or the server's default one
+    pub async fn connect(&mut self, nick: String, ruleset: Option<String>) -> Result<(), ConnectionError> {
         let body =
-            PacketBody::Connect(Box::new(ConnectBody::new(nick)));
-        let _res = self
+            PacketBody::Connect(Box::new(ConnectBody::new(nick.clone()).with_ruleset(ruleset)));
+        let res = self
             .requester
             .send_request(Packet::new(ProtocolCommand::Connect).load_body(body)?)
             .await?;
+        if let PacketBody::ConnectResp(body) = res.get_packet().get_body()? {
+            self.set_ruleset(body.get_ruleset().clone())?;
+        }
+        // our nick takes the first seat until the table is full and we know which one is ours
+        self.table.set_nick(0, nick);
+        self.read_opponent(res.get_packet().get_body()?);
+        Ok(())
+    }
+
+    // boards and fleet follow the rules the server picked for the game
+    fn set_ruleset(&mut self, ruleset: Ruleset) -> Result<(), ConnectionError> {
+        ruleset
+            .validate()
+            .map_err(|e| ConnectionError::new(&format!("Server sent invalid rules - {e}")))?;
+        self.ships = ruleset.get_fleet().iter().map(|&ship_type| Ship::new(ship_type)).collect();
+        self.own_board = OwnBoard::new(&ruleset).with_theme(self.theme);
+        self.table = Table::new(&ruleset, vec![String::new(); ruleset.get_players()]).with_theme(self.theme);
+        self.log.push(format!("Playing with {} rules", ruleset.get_name()));
+        self.ruleset = ruleset;
+        Ok(())
+    }
+
+    // the server tells us with another connect_resp whenever someone (or a bot) takes an empty
+    // seat - once they're all taken, the one left is ours
+    pub async fn wait_for_opponent(&mut self) -> Result<(), ConnectionError> {
+        let empty = |player: &ClientPlayer| {
+            player.table.get_players().iter().filter(|nick| nick.is_empty()).count()
+        };
+        if empty(self) > 0 {
+            let opponents = if self.table.get_players().len() > 2 { "opponents" } else { "an opponent" };
+            println!("Waiting for {}...", opponents);
+        }
+        while empty(self) > 0 {
+            let packet = self.receive().await?;
+            self.read_opponent(packet.get_body()?);
+        }
         Ok(())
     }
+
+    fn read_opponent(&mut self, body: &PacketBody) {
+        let PacketBody::ConnectResp(body) = body else {
+            return;
+        };
+        let Some(nick) = body.get_opponent_nick() else {
+            return;
+        };
+        let label = if body.is_bot() { " (bot)" } else { "" };
+        match self.table.get_players().len() {
+            2 => self.log.push(format!("Playing against {}{}", nick, label)),
+            _ => self.log.push(format!("{}{} joined the game", nick, label)),
+        }
+        self.table.sit(body.get_seat(), String::from(nick));
+    }
+
+    // name for a player in the log - only there's just one opponent in a duel
+    fn describe_player(&self, player: usize) -> String {
+        match (player == self.table.get_seat(), self.table.get_players().len()) {
+            (true, _) => String::from("you"),
+            (false, 2) => String::from("opponent"),
+            (false, _) => self.table.get_players()[player].clone(),
+        }
+    }
+
+    // send the fleet and play until the game is over, returns whether we won
+    pub async fn play(&mut self) -> Result<bool, ConnectionError> {
+        let ships = self.own_board.get_board().get_ships().to_vec();
+        let body = PacketBody::PlaceFleet(Box::new(PlaceFleetBody::new(ships)));
+        self.requester.send(Packet::from_body(body)).await?;
+        self.render("Waiting for the opponent to place their ships...");
+        // shots in our next salvo, stays 0 in games without salvos
+        let mut salvo = 0;
+        // special weapons we have left, empty in games without them
+        let mut arsenal = Arsenal::default();
+
+        loop {
+            let packet = self.receive().await?;
+            let your_turn = match packet.get_body()? {
+                PacketBody::GameStart(body) => {
+                    let first = if body.is_your_turn() { "You fire" } else { "Opponent fires" };
+                    self.log.push(format!("{} first", first));
+                    salvo = body.get_salvo();
+                    arsenal = body.get_arsenal();
+                    body.is_your_turn()
+                }
+                PacketBody::FireResult(body) => {
+                    self.record_shot(body.get_player(), body.get_opponent(), body.get_target(), body.get_result());
+                    body.is_your_turn()
+                }
+                PacketBody::SalvoResult(body) => {
+                    for &(target, result) in body.get_shots() {
+                        self.record_shot(body.get_player(), body.get_opponent(), target, result);
+                    }
+                    salvo = body.get_salvo();
+                    body.is_your_turn()
+                }
+                PacketBody::WeaponResult(body) => {
+                    let [player, opponent] = [body.get_player(), body.get_opponent()];
+                    self.record_strike(player, opponent, body.get_weapon(), body.get_target(), body.get_strike());
+                    arsenal = body.get_arsenal();
+                    body.is_your_turn()
+                }
+                PacketBody::MoveResult(body) => {
+                    match (body.get_player() == self.table.get_seat(), body.get_move()) {
+                        (true, Some((tile, direction))) => {
+                            self.own_board.shift_ship(tile, direction);
+                            self.log.push(format!("you move the ship at {} {}", encode_indexes(tile), direction));
+                        }
+                        _ => {
+                            self.table.record_move(body.get_player());
+                            self.log.push(format!("{} moves a ship", self.describe_player(body.get_player())));
+                        }
+                    }
+                    body.is_your_turn()
+                }
+                PacketBody::PlayerLeft(body) => {
+                    self.record_leave(body.get_player());
+                    salvo = body.get_salvo();
+                    body.is_your_turn()
+                }
+                // only our shots can be rejected during the game, so it's still our turn
+                PacketBody::Error(body) => {
+                    self.log.push(format!("{} - trying again", body.get_msg()));
+                    true
+                }
+                PacketBody::GameOver(body) => {
+                    let outcome = if body.is_won() { "You won!" } else { "You lost!" };
+                    self.log.push(format!("{} - {}", outcome, body.get_reason()));
+                    self.render("Game over");
+                    return Ok(body.is_won());
+                }
+                PacketBody::Shutdown(body) => {
+                    return Err(ConnectionError::new(&format!(
+                        "Server is shutting down: {}",
+                        body.get_reason()
+                    )))
+                }
+                _ => false,
+            };
+            if !your_turn && self.own_board.get_board().is_fleet_destroyed() {
+                self.render("Your fleet is sunk - watching the rest of the game...");
+                continue;
+            }
+            if !your_turn {
+                self.render("Opponent's turn...");
+                continue;
+            }
+            let body = match salvo {
+                0 => self.choose_turn(arsenal)?,
+                count => {
+                    let targets = self.choose_salvo(count)?;
+                    let body = SalvoBody::new(targets).with_opponent(self.table.get_opponent());
+                    PacketBody::Salvo(Box::new(body))
+                }
+            };
+            self.requester.send(Packet::from_body(body)).await?;
+        }
+    }
+
+    // place the fleet and play on the full screen interface, returns whether we won
+    pub async fn play_tui(
+        self,
+        nick: String,
+        auto_place: Option<PlacementMode>,
+        mouse: bool,
+    ) -> Result<bool, ConnectionError> {
+        let seat = self.table.get_seat();
+        let mut players = self.table.get_players().to_vec();
+        players[seat] = nick;
+        let mut app = App::new(players, &self.ruleset, auto_place)
+            .with_seat(seat)
+            .with_mouse(mouse)
+            .with_theme(self.theme);
+        for line in self.log {
+            app.log(line);
+        }
+        // packets are read on their own task, so waiting for one doesn't get in the way of key presses
+        let (mut reader, mut writer) = self.requester.into_split();
+        let (tx, mut packets) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let packet = reader.read_packet().await;
+                let closed = !matches!(packet, Ok(Some(_)));
+                if tx.send(packet).is_err() || closed {
+                    break;
+                }
+            }
+        });
+        let mut tui = Tui::start(mouse)?;
+
+        loop {
+            tui.draw(&mut app)?;
+            let packet = tokio::select! {
+                input = tui.next_input() => {
+                    let Some(input) = input else {
+                        return Err(ConnectionError::new("Terminal input has closed"));
+                    };
+                    let body = match app.handle_input(input) {
+                        Action::Quit => return Ok(app.get_outcome().unwrap_or(false)),
+                        Action::FleetPlaced => {
+                            let ships = app.get_own_board().get_ships().to_vec();
+                            PacketBody::PlaceFleet(Box::new(PlaceFleetBody::new(ships)))
+                        }
+                        Action::Fire(target) => {
+                            PacketBody::Fire(Box::new(FireBody::new(target).with_opponent(app.get_opponent())))
+                        }
+                        Action::Salvo(targets) => {
+                            PacketBody::Salvo(Box::new(SalvoBody::new(targets).with_opponent(app.get_opponent())))
+                        }
+                        Action::Weapon(weapon, target) => {
+                            let body = WeaponBody::new(weapon, target).with_opponent(app.get_opponent());
+                            PacketBody::Weapon(Box::new(body))
+                        }
+                        Action::Move(tile, direction) => PacketBody::Move(Box::new(MoveBody::new(tile, direction))),
+                        Action::None => continue,
+                    };
+                    writer.send(Packet::from_body(body)).await?;
+                    continue;
+                }
+                packet = packets.recv(), if app.get_outcome().is_none() => packet,
+            };
+            let packet = match packet {
+                Some(Ok(Some(packet))) => packet,
+                Some(Err(e)) => return Err(e.into()),
+                Some(Ok(None)) | None => {
+                    return Err(ConnectionError::new("Server has closed the connection"))
+                }
+            };
+            match packet.get_body()? {
+                PacketBody::GameStart(body) => {
+                    app.set_salvo(body.get_salvo());
+                    app.set_arsenal(body.get_arsenal());
+                    app.start(body.is_your_turn());
+                }
+                PacketBody::FireResult(body) => {
+                    app.shot(body.get_player(), body.get_opponent(), body.get_target(), body.get_result());
+                    app.set_turn(body.is_your_turn());
+                }
+                PacketBody::SalvoResult(body) => {
+                    for &(target, result) in body.get_shots() {
+                        app.shot(body.get_player(), body.get_opponent(), target, result);
+                    }
+                    app.set_salvo(body.get_salvo());
+                    app.set_turn(body.is_your_turn());
+                }
+                PacketBody::WeaponResult(body) => {
+                    let [player, opponent] = [body.get_player(), body.get_opponent()];
+                    app.weapon_result(player, opponent, body.get_weapon(), body.get_target(), body.get_strike());
+                    app.set_arsenal(body.get_arsenal());
+                    app.set_turn(body.is_your_turn());
+                }
+                PacketBody::MoveResult(body) => {
+                    app.move_result(body.get_player(), body.get_move());
+                    app.set_turn(body.is_your_turn());
+                }
+                PacketBody::PlayerLeft(body) => {
+                    app.player_left(body.get_player());
+                    app.set_salvo(body.get_salvo());
+                    // it may already have been our turn, which goes on then
+                    if body.is_your_turn() {
+                        app.set_turn(true);
+                    }
+                }
+                PacketBody::Error(body) => app.reject(body.get_msg()),
+                PacketBody::GameOver(body) => app.game_over(body.is_won(), body.get_reason()),
+                PacketBody::Shutdown(body) => {
+                    return Err(ConnectionError::new(&format!(
+                        "Server is shutting down: {}",
+                        body.get_reason()
+                    )))
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // shots at us land on our fleet, the others fill in what we know about the fleet fired at -
+    // once our opponent is out of ships, we go on to the next one
+    fn record_shot(&mut self, player: usize, opponent: usize, target: [usize; 2], result: ShotResult) {
+        if opponent == self.table.get_seat() {
+            self.own_board.receive_shot(target);
+        }
+        self.table.record_shot(opponent, target, result);
+        let verb = if player == self.table.get_seat() { "fire" } else { "fires" };
+        let target = self.table.describe_target(opponent, target);
+        self.log.push(format!("{} {} at {} - {}", self.describe_player(player), verb, target, result));
+        self.table.move_on();
+    }
+
+    // the game goes on without a player that left, we pick another opponent if they were ours
+    fn record_leave(&mut self, player: usize) {
+        self.table.leave(player);
+        self.log.push(format!("{} has left the game", self.table.get_players()[player]));
+        self.table.move_on();
+    }
+
+    fn record_strike(&mut self, player: usize, opponent: usize, weapon: Weapon, target: [usize; 2], strike: &Strike) {
+        let verb = if player == self.table.get_seat() { "use" } else { "uses" };
+        let area = self.table.describe_target(opponent, target);
+        self.log.push(format!("{} {} the {} at {}", self.describe_player(player), verb, weapon, area));
+        for &(tile, result) in strike.get_shots() {
+            self.record_shot(player, opponent, tile, result);
+        }
+        if let Some(detected) = strike.get_detected() {
+            let found = if detected { "found a ship" } else { "found nothing" };
+            self.log.push(format!("the radar {}", found));
+            self.table.record_scan(opponent, &weapon.area(target, self.ruleset.get_size()), detected);
+        }
+    }
+
+    // `target bob` picks the opponent the next shots go to, returns whether the input was one
+    fn pick_opponent(&mut self, input: &str) -> bool {
+        let Some(nick) = input.trim().strip_prefix("target") else {
+            return false;
+        };
+        let nick = nick.trim();
+        let players = self.table.get_players();
+        match self.table.rivals_afloat().into_iter().find(|&seat| players[seat] == nick) {
+            Some(seat) => {
+                self.table.select_opponent(seat);
+            }
+            None => self.log.push(format!("There's no opponent called {} with ships left", nick)),
+        }
+        true
+    }
+
+    // whether we have already fired at the tile on our opponent's board
+    fn is_shot(&self, target: [usize; 2]) -> bool {
+        self.table.get_target(self.table.get_opponent()).is_shot(target)
+    }
+
+    // who we fire at and how to pick someone else, when there's more than one opponent left
+    fn opponent_prompt(&self) -> String {
+        match self.table.rivals_afloat().len() {
+            0 | 1 => String::new(),
+            _ => format!(
+                "Firing at {} - enter >>target nick<< to fire at someone else\n",
+                self.table.get_players()[self.table.get_opponent()]
+            ),
+        }
+    }
+
+    async fn receive(&mut self) -> Result<Packet<Ready>, ConnectionError> {
+        self.requester
+            .receive()
+            .await?
+            .ok_or(ConnectionError::new("Server has closed the connection"))
+    }
+
+    // a tile to fire at, a special weapon aimed at a tile while there are any left, or a move
+    // of one of our ships in games where ships move
+    fn choose_turn(&mut self, arsenal: Arsenal) -> Result<PacketBody, ConnectionError> {
+        loop {
+            let prompt = turn_prompt(arsenal, self.ruleset.is_moving());
+            self.render(&format!("{}{}", self.opponent_prompt(), prompt));
+            let mut buf = String::new();
+            match stdin().read_line(&mut buf) {
+                Ok(0) => return Err(ConnectionError::new("Input closed before the game has ended")),
+                Ok(_) => (),
+                Err(e) => {
+                    self.log.push(format!("Couldn't read form stdin! - {}", e));
+                    continue;
+                }
+            }
+            if self.pick_opponent(&buf) {
+                continue;
+            }
+            if self.ruleset.is_moving() && buf.trim_start().starts_with("move") {
+                match OwnBoard::decode_move(&buf, self.ruleset.get_size()) {
+                    Ok((tile, direction)) => return Ok(PacketBody::Move(Box::new(MoveBody::new(tile, direction)))),
+                    Err(e) => self.log.push(format!("{} - trying again", e)),
+                }
+                continue;
+            }
+            match OwnBoard::decode_weapon(&buf, self.ruleset.get_size()) {
+                Ok((None, target)) if self.is_shot(target) => {
+                    self.log.push(format!("You have already fired at {}", encode_indexes(target)))
+                }
+                Ok((Some(weapon), _)) if arsenal.get_uses(weapon) == 0 => {
+                    self.log.push(format!("You have no {} left", weapon))
+                }
+                Ok((Some(weapon), target)) => {
+                    let body = WeaponBody::new(weapon, target).with_opponent(self.table.get_opponent());
+                    return Ok(PacketBody::Weapon(Box::new(body)));
+                }
+                Ok((None, target)) => {
+                    let body = FireBody::new(target).with_opponent(self.table.get_opponent());
+                    return Ok(PacketBody::Fire(Box::new(body)));
+                }
+                Err(e) => self.log.push(format!("{} - trying again", e)),
+            }
+        }
+    }
+
+    fn choose_salvo(&mut self, count: usize) -> Result<Vec<[usize; 2]>, ConnectionError> {
+        loop {
+            self.render(&format!(
+                "{}Your turn - enter {} tiles to fire at in one salvo like this >>b7 c3<<:",
+                self.opponent_prompt(),
+                count
+            ));
+            let mut buf = String::new();
+            match stdin().read_line(&mut buf) {
+                Ok(0) => return Err(ConnectionError::new("Input closed before the game has ended")),
+                Ok(_) => (),
+                Err(e) => {
+                    self.log.push(format!("Couldn't read form stdin! - {}", e));
+                    continue;
+                }
+            }
+            if self.pick_opponent(&buf) {
+                continue;
+            }
+            match OwnBoard::decode_salvo(&buf, count, self.ruleset.get_size()) {
+                Ok(targets) => match targets.iter().find(|&&target| self.is_shot(target)) {
+                    Some(&target) => {
+                        self.log.push(format!("You have already fired at {}", encode_indexes(target)))
+                    }
+                    None => return Ok(targets),
+                },
+                Err(e) => self.log.push(format!("{} - trying again", e)),
+            }
+        }
+    }
+
+    // our fleet and then the opponents' boards, two to a row - the one we fire at is marked
+    // when there's more than one
+    fn render(&self, prompt: &str) {
+        let mut boards = vec![(String::from("Your fleet"), self.own_board.to_string())];
+        let players = self.table.get_players();
+        for seat in self.table.rivals() {
+            let title = match (players[seat].as_str(), seat == self.table.get_opponent() && players.len() > 2) {
+                ("", _) => String::from("Opponent"),
+                (nick, true) => format!("{} (target)", nick),
+                (nick, false) => String::from(nick),
+            };
+            boards.push((title, self.table.get_target(seat).to_string()));
+        }
+        print!("\x1B[2J\x1B[1;1H");
+        for row in boards.chunks(2) {
+            match row {
+                [(first, left), (second, right)] => print!("{}", side_by_side([first, second], left, right)),
+                _ => print!("{}\n{}", row[0].0, row[0].1),
+            }
+        }
+        for line in &self.log[self.log.len().saturating_sub(LOG_LINES)..] {
+            println!("{}", line);
+        }
+        println!("{}", prompt);
+    }
 }
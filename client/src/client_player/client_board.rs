@@ -1,43 +1,191 @@
 use crate::client_player::client_board::error::{PlacingShipsError, UserInputError};
-use std::{fmt::Display, io::stdin};
+use crate::theme::{visible_width, Mark, Theme};
+use bslib::game::board::Board;
+use bslib::game::movement::Direction;
+use bslib::game::ruleset::Ruleset;
+use bslib::game::weapon::{Arsenal, Weapon};
+use bslib::game::Ship;
+use bslib::placement::{random_fleet, PlacementMode};
+use rand::Rng;
+use std::{
+    fmt::Display,
+    io::{stdin, BufRead},
+};
 
 pub mod error;
+pub mod target_board;
 
-const COORDINATES_LETTERS: &str = "abcdefghij";
+// rows are labelled with letters, only as many as the board has rows are used
+pub const COORDINATES_LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
 
 // player's board with their ships
-pub struct OwnBoard<'a> {
-    board: [[OwnTile<'a>; 10]; 10],
+pub struct OwnBoard {
+    board: Board,
+    ruleset: Ruleset,
     ships_placed: bool,
+    theme: Theme,
 }
-impl<'a> OwnBoard<'a> {
+impl OwnBoard {
     // create new instance
-    pub fn new() -> Self {
+    pub fn new(ruleset: &Ruleset) -> Self {
         OwnBoard {
-            board: [[OwnTile::new(); 10]; 10],
+            board: Board::from_ruleset(ruleset),
+            ruleset: ruleset.clone(),
             ships_placed: false,
+            theme: Theme::default(),
         }
     }
+    // wrap a board that already has the ruleset's ships
+    pub fn from_board(board: Board, ruleset: &Ruleset) -> Self {
+        OwnBoard {
+            board,
+            ruleset: ruleset.clone(),
+            ships_placed: true,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn get_board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn into_board(self) -> Board {
+        self.board
+    }
+    // mark a shot fired by the opponent
+    pub fn receive_shot(&mut self, target: [usize; 2]) {
+        let _ = self.board.fire(target);
+    }
+    // follow a move of our ship the server has accepted
+    pub fn shift_ship(&mut self, tile: [usize; 2], direction: Direction) {
+        let _ = self.board.move_ship(tile, direction);
+    }
     // prompt user to place their ships
-    pub fn place_ships(&mut self, ships: &'a Vec<Ship>) -> Result<(), PlacingShipsError> {
+    pub fn place_ships(&mut self, ships: &[Ship]) -> Result<(), PlacingShipsError> {
+        self.place_ships_from(ships, &mut stdin().lock())
+    }
+    // place ships reading the coordinates from given input instead of stdin
+    pub fn place_ships_from(
+        &mut self,
+        ships: &[Ship],
+        input: &mut impl BufRead,
+    ) -> Result<(), PlacingShipsError> {
         if self.ships_placed {
             return Err(PlacingShipsError::new(String::from(
                 "Ships were already placed",
             )));
         };
         for ship in ships {
-            self.place_ship(ship)?
+            self.place_ship(ship, input)?
+        }
+        self.ships_placed = true;
+        self.print_board("Ships placed", None);
+        Ok(())
+    }
+    // offer a generated layout that the user can accept, reroll or edit
+    pub fn auto_place(
+        &mut self,
+        ships: &[Ship],
+        mode: PlacementMode,
+        rng: &mut impl Rng,
+    ) -> Result<(), PlacingShipsError> {
+        self.auto_place_from(ships, mode, rng, &mut stdin().lock())
+    }
+    // auto_place reading the answers from given input instead of stdin
+    pub fn auto_place_from(
+        &mut self,
+        ships: &[Ship],
+        mode: PlacementMode,
+        rng: &mut impl Rng,
+        input: &mut impl BufRead,
+    ) -> Result<(), PlacingShipsError> {
+        if self.ships_placed {
+            return Err(PlacingShipsError::new(String::from(
+                "Ships were already placed",
+            )));
+        };
+        self.board = random_fleet(&self.ruleset, mode, rng)?;
+        let prompt = "Generated layout - >>a<< accept, >>r<< reroll, >>e<< move one ship, >>m<< place ships yourself:";
+        let mut err_msg: Option<String> = None;
+        loop {
+            self.print_board(prompt, err_msg.take());
+            match Self::read_answer(input)?.as_str() {
+                "a" => break,
+                "r" => self.board = random_fleet(&self.ruleset, mode, rng)?,
+                "e" => self.move_ship(input)?,
+                "m" => {
+                    self.board = Board::from_ruleset(&self.ruleset);
+                    return self.place_ships_from(ships, input);
+                }
+                other => err_msg = Some(format!("Unknown option >>{}<< - trying again...\n", other)),
+            }
         }
         self.ships_placed = true;
         self.print_board("Ships placed", None);
         Ok(())
     }
+    // take one ship off the board and let the user place it again
+    fn move_ship(&mut self, input: &mut impl BufRead) -> Result<(), PlacingShipsError> {
+        let placed = self.board.get_ships().to_vec();
+        let list = placed
+            .iter()
+            .enumerate()
+            .map(|(k, ship)| format!("{} - {}", k + 1, ship.get_type()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let prompt = format!("Which ship do you want to move? ({})", list);
+        let mut err_msg: Option<String> = None;
+        let index = loop {
+            self.print_board(&prompt, err_msg.take());
+            match Self::read_answer(input)?.parse::<usize>() {
+                Ok(number) if (1..=placed.len()).contains(&number) => break number - 1,
+                _ => err_msg = Some(String::from("There is no such ship - trying again...\n")),
+            }
+        };
+        let mut board = Board::from_ruleset(&self.ruleset);
+        for (k, ship) in placed.iter().enumerate() {
+            if k != index {
+                board.place_ship(ship.get_type(), ship.get_tiles().to_vec())?;
+            }
+        }
+        self.board = board;
+        self.place_ship(&Ship::new(placed[index].get_type()), input)
+    }
+    // read one trimmed line of the user's answer
+    fn read_answer(input: &mut impl BufRead) -> Result<String, PlacingShipsError> {
+        let mut buf = String::new();
+        match input.read_line(&mut buf) {
+            Ok(0) => Err(PlacingShipsError::new(String::from(
+                "Input closed before all ships were placed",
+            ))),
+            Ok(_) => Ok(buf.trim().to_lowercase()),
+            Err(e) => Err(PlacingShipsError::new(format!("Couldn't read form stdin! - {}", e))),
+        }
+    }
     // method used by place_ships to place one ship
-    fn place_ship(&mut self, ship: &'a Ship) -> Result<(), PlacingShipsError> {
+    fn place_ship(
+        &mut self,
+        ship: &Ship,
+        input: &mut impl BufRead,
+    ) -> Result<(), PlacingShipsError> {
         // print!("\x1B[2J\x1B[1;1H");
         let mut err_msg: Option<String> = None;
-        let prompt = format!("Place your {} ({} tiles long) - enter tiles coordinates like this >>a1-a3<<:",
-        ship, ship.size);
+        let shape = ship.get_type().get_shape();
+        let prompt = match ship.get_size() {
+            1 => format!("Place your {} (1 tile) - enter its coordinates like this >>a1<<:", ship),
+            size if shape.is_line() => format!("Place your {} ({} tiles long) - enter tiles coordinates like this >>a1-a3<<:",
+            ship, size),
+            size => {
+                let example = shape.at([0, 0]).map(encode_indexes).collect::<Vec<_>>();
+                format!("Place your {} ({} tiles, turned or flipped any way) - enter all its tiles like this >>{}<<:",
+                ship, size, example.join(" "))
+            }
+        };
         loop {
             // println!("{}", self);
             // println!(
@@ -47,11 +195,19 @@ impl<'a> OwnBoard<'a> {
 
             self.print_board(&prompt, err_msg);
             let mut buf = String::new();
-            if let Err(e) = stdin().read_line(&mut buf) {
-                err_msg = Some(format!("Couldn't read form stdin! - {} - Trying again...\n", e));
-                continue;
+            match input.read_line(&mut buf) {
+                Ok(0) => {
+                    return Err(PlacingShipsError::new(String::from(
+                        "Input closed before all ships were placed",
+                    )))
+                }
+                Ok(_) => (),
+                Err(e) => {
+                    err_msg = Some(format!("Couldn't read form stdin! - {} - Trying again...\n", e));
+                    continue;
+                }
             }
-            let coordinates = Self::decode_ship_placing_input(buf.trim(), ship);
+            let coordinates = Self::decode_ship_placing_input(buf.trim(), ship, self.ruleset.get_size());
             match coordinates {
                 Ok(coordinates) => {
                     if let Err(e) = self.place_on_tiles(&coordinates, ship) {
@@ -75,14 +231,31 @@ impl<'a> OwnBoard<'a> {
     fn decode_ship_placing_input(
         input: &str,
         ship: &Ship,
+        size: [usize; 2],
     ) -> Result<Vec<[usize; 2]>, UserInputError> {
+        // any ship can be given tile by tile, it's the only way for ships that aren't straight
+        let tiles = input.split_whitespace().collect::<Vec<_>>();
+        if tiles.len() > 1 {
+            return tiles.into_iter().map(|tile| Self::decode_indexes(tile, size)).collect();
+        }
+        if !ship.get_type().get_shape().is_line() {
+            return Err(UserInputError::new(String::from(
+                "Enter every tile of this ship, separated with spaces",
+            )));
+        }
         if !input.contains('-') {
+            if ship.get_size() == 1 {
+                return Ok(vec![Self::decode_indexes(input, size)?]);
+            }
             return Err(UserInputError::new(String::from("Missing hyphen")));
         }
         let fields = input.split('-');
         let mut decoded_indexes = Vec::new();
         for field in fields {
-            decoded_indexes.push(Self::decode_indexes(field)?)
+            decoded_indexes.push(Self::decode_indexes(field, size)?)
+        }
+        if decoded_indexes.len() != 2 {
+            return Err(UserInputError::new(String::from("Wrong format")));
         }
         let (changing_coord, unchanging_coord): (usize, usize) =
             if decoded_indexes[0][0] == decoded_indexes[1][0] {
@@ -106,8 +279,8 @@ impl<'a> OwnBoard<'a> {
                     decoded_indexes[0][changing_coord],
                 )
             };
-        if greater - lesser == ship.size as usize - 1 {
-            for i in 1..ship.size - 1 {
+        if greater - lesser == ship.get_size() as usize - 1 {
+            for i in 1..ship.get_size() - 1 {
                 if unchanging_coord == 0 {
                     decoded_indexes.insert(
                         i as usize,
@@ -125,60 +298,84 @@ impl<'a> OwnBoard<'a> {
                 "This range is either too long or too short for this ship",
             )));
         }
+        // >>a1-a1<< for a ship of one tile
+        decoded_indexes.dedup();
         Ok(decoded_indexes)
     }
 
-    fn decode_indexes(indexes: &str) -> Result<[usize; 2], UserInputError> {
+    // decode a single tile like >>b7<< on a board of the given size
+    pub fn decode_indexes(indexes: &str, [height, width]: [usize; 2]) -> Result<[usize; 2], UserInputError> {
         let chars = indexes.chars().collect::<Vec<_>>();
         if (chars.len() == 3 || chars.len() == 2)
-            && COORDINATES_LETTERS.contains(chars[0])
+            && chars[0].is_ascii_lowercase()
             && chars[1].is_ascii_digit()
         {
             if chars.len() == 3 && !chars[2].is_ascii_digit() {
-                println!("{:#?}", chars);
                 return Err(UserInputError::new(String::from("Wrong format")));
             }
             let (i, j) = chars.split_at(1);
-            let i = COORDINATES_LETTERS
+            let i = COORDINATES_LETTERS[..height]
                 .find(i)
                 .ok_or(UserInputError::new(String::from(
                     "Such letters are not allowed in coordinates",
                 )))?;
             let j: usize = j.iter().collect::<String>().parse()?;
-            Ok([i, j - 1])
+            match j {
+                j if (1..=width).contains(&j) => Ok([i, j - 1]),
+                _ => Err(UserInputError::new(String::from(
+                    "Such numbers are not allowed in coordinates",
+                ))),
+            }
         } else {
             Err(UserInputError::new(String::from("Wrong format")))
         }
     }
 
-    fn place_on_tiles(
-        &mut self,
-        coordinates: &Vec<[usize; 2]>,
-        ship: &'a Ship,
-    ) -> Result<(), PlacingShipsError> {
-        for [i, j] in coordinates {
-            if self.board[*i][*j].ship.is_some() {
-                return Err(PlacingShipsError::new(String::from("Tile is not empty")));
-            }
-
-            let top = if *i > 0 { i - 1 } else { 0 };
-            let down = if *i < 9 { i + 1 } else { 9 };
-            let left = if *j > 0 { j - 1 } else { 0 };
-            let right = if *j < 9 { j + 1 } else { 9 };
+    // decode a salvo of exactly `count` different tiles like >>b7 c3 j10<<
+    pub fn decode_salvo(input: &str, count: usize, size: [usize; 2]) -> Result<Vec<[usize; 2]>, UserInputError> {
+        let targets = input
+            .split_whitespace()
+            .map(|tile| Self::decode_indexes(tile, size))
+            .collect::<Result<Vec<_>, _>>()?;
+        if targets.len() != count {
+            return Err(UserInputError::new(format!("The salvo needs exactly {count} tiles")));
+        }
+        if targets.iter().enumerate().any(|(index, target)| targets[..index].contains(target)) {
+            return Err(UserInputError::new(String::from("The same tile can't be fired at twice")));
+        }
+        Ok(targets)
+    }
 
-            for k in top..down + 1 {
-                for l in left..right + 1 {
-                    if self.board[k][l].ship.is_some() {
-                        return Err(PlacingShipsError::new(String::from(
-                            "The tile is next to another ship",
-                        )));
-                    }
-                }
+    // a single tile to fire at, or a special weapon followed by the tile it's aimed at like `radar b7`
+    pub fn decode_weapon(input: &str, size: [usize; 2]) -> Result<(Option<Weapon>, [usize; 2]), UserInputError> {
+        match input.split_whitespace().collect::<Vec<_>>()[..] {
+            [tile] => Ok((None, Self::decode_indexes(tile, size)?)),
+            [weapon, tile] => {
+                let weapon = weapon.parse::<Weapon>().map_err(UserInputError::new)?;
+                Ok((Some(weapon), Self::decode_indexes(tile, size)?))
             }
+            _ => Err(UserInputError::new(String::from("Wrong format"))),
         }
-        for [i, j] in coordinates {
-            self.board[*i][*j].ship = Some(ship);
+    }
+
+    // a ship to move given by one of its tiles and the direction to move it in, like `move b7 up`
+    pub fn decode_move(input: &str, size: [usize; 2]) -> Result<([usize; 2], Direction), UserInputError> {
+        match input.split_whitespace().collect::<Vec<_>>()[..] {
+            ["move", tile, direction] => {
+                let direction = direction.parse::<Direction>().map_err(UserInputError::new)?;
+                Ok((Self::decode_indexes(tile, size)?, direction))
+            }
+            _ => Err(UserInputError::new(String::from("Wrong format"))),
         }
+    }
+
+    // put a ship on given tiles, checking that it doesn't overlap other ships or break the adjacency rule
+    pub fn place_on_tiles(
+        &mut self,
+        coordinates: &[[usize; 2]],
+        ship: &Ship,
+    ) -> Result<(), PlacingShipsError> {
+        self.board.place_ship(ship.get_type(), coordinates.to_vec())?;
         Ok(())
     }
 
@@ -191,135 +388,203 @@ impl<'a> OwnBoard<'a> {
         println!("{}", prompt);
     }
 }
-impl Display for OwnBoard<'_> {
+impl Display for OwnBoard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut string = String::from("  | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10|\n-------------------------------------------\n");
-        let chars = COORDINATES_LETTERS.chars().collect::<Vec<_>>();
-        for (i, row) in self.board.iter().enumerate() {
-            string += chars[i].to_string().as_str();
-            string += " |";
-            for tile in row {
-                string += format!("{}|", tile).as_str();
+        write_board(f, self.theme, self.board.get_size(), |tile| {
+            let tile = self.board.get_tile(tile);
+            match (tile.is_shot(), tile.has_ship()) {
+                (true, false) => Mark::Miss,
+                (true, true) => Mark::Hit,
+                (false, false) => Mark::Water,
+                (false, true) => Mark::Ship,
             }
-            string += "\n-------------------------------------------\n";
-        }
-        write!(f, "{}", string)
+        })
     }
 }
 
-#[derive(Clone, Copy)]
-struct OwnTile<'a> {
-    shot: bool,
-    ship: Option<&'a Ship>,
+// format a tile the way users type it, e.g. >>b7<<
+pub fn encode_indexes([i, j]: [usize; 2]) -> String {
+    format!("{}{}", COORDINATES_LETTERS.as_bytes()[i] as char, j + 1)
 }
-impl<'a> OwnTile<'a> {
-    fn new() -> Self {
-        OwnTile {
-            shot: false,
-            ship: None,
-        }
+
+// what a player can type in a turn without salvos - a shot, and a weapon or a move when the
+// game has them
+pub fn turn_prompt(arsenal: Arsenal, moving: bool) -> String {
+    let mut prompt = String::from("Your turn - enter a tile to fire at like this >>b7<<");
+    if !arsenal.is_empty() {
+        let uses = Weapon::ALL.map(|weapon| format!("{} {}", weapon, arsenal.get_uses(weapon)));
+        prompt += &format!(", a weapon and a tile like this >>radar b7<< ({} left)", uses.join(", "));
     }
-}
-impl Display for OwnTile<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.shot {
-            true => match self.ship {
-                None => write!(f, " * "),
-                Some(_) => write!(f, " X "),
-            },
-            false => match self.ship {
-                None => write!(f, "   "),
-                Some(_) => write!(f, " @ "),
-            },
-        }
+    if moving {
+        prompt += ", or a ship to move like this >>move b7 up<<";
     }
+    prompt + ":"
 }
 
-#[derive(PartialEq, Eq, Debug)]
-pub enum ShipType {
-    Carrier,
-    Battleship,
-    Cruiser,
-    Submarine,
-    Destroyer,
-}
-
-#[derive(PartialEq, Eq, Debug)]
-pub struct Ship {
-    ship_type: ShipType,
-    size: u8,
-    name: &'static str,
-    destroyed: bool,
-}
-impl Ship {
-    pub fn new(ship_type: ShipType) -> Self {
-        let size = Ship::get_ship_size(&ship_type);
-        let name = Ship::get_ship_name(&ship_type);
-        Ship {
-            ship_type,
-            size,
-            name,
-            destroyed: false,
+fn write_board(
+    f: &mut std::fmt::Formatter<'_>,
+    theme: Theme,
+    [height, width]: [usize; 2],
+    mark: impl Fn([usize; 2]) -> Mark,
+) -> std::fmt::Result {
+    // box drawing grid, e.g. ├───┼───┤, or the ascii one made of dashes
+    let line = |left: &str, middle: &str, right: &str| {
+        format!("  {}{}───{}\n", left, format!("───{}", middle).repeat(width - 1), right)
+    };
+    let (mut string, separator, bottom, wall) = match theme.is_unicode() {
+        true => {
+            let mut header = String::from("   ");
+            for j in 1..=width {
+                header += format!("{:^3} ", j).as_str();
+            }
+            (
+                header.trim_end().to_string() + "\n" + line("┌", "┬", "┐").as_str(),
+                line("├", "┼", "┤"),
+                line("└", "┴", "┘"),
+                "│",
+            )
         }
-    }
-
-    fn get_ship_size(ship_type: &ShipType) -> u8 {
-        const CARRIER_SIZE: u8 = 5;
-        const BATTLESHIP_SIZE: u8 = 4;
-        const CRUISER_SIZE: u8 = 3;
-        const SUBMARINE_SIZE: u8 = 3;
-        const DESTROYER_SIZE: u8 = 2;
-
-        match ship_type {
-            ShipType::Carrier => CARRIER_SIZE,
-            ShipType::Battleship => BATTLESHIP_SIZE,
-            ShipType::Cruiser => CRUISER_SIZE,
-            ShipType::Submarine => SUBMARINE_SIZE,
-            ShipType::Destroyer => DESTROYER_SIZE,
+        false => {
+            let separator = format!("{}\n", "-".repeat(4 * width + 3));
+            let mut header = String::from("  |");
+            for j in 1..=width {
+                header += format!(" {:<2}|", j).as_str();
+            }
+            (
+                header + "\n" + separator.as_str(),
+                separator.clone(),
+                separator,
+                "|",
+            )
         }
-    }
-
-    fn get_ship_name(ship_type: &ShipType) -> &'static str {
-        const CARRIER_NAME: &str = "carrier";
-        const BATTLESHIP_NAME: &str = "battleship";
-        const CRUISER_NAME: &str = "cruiser";
-        const SUBMARINE_NAME: &str = "submarine";
-        const DESTROYER_NAME: &str = "destroyer";
-
-        match ship_type {
-            ShipType::Carrier => CARRIER_NAME,
-            ShipType::Battleship => BATTLESHIP_NAME,
-            ShipType::Cruiser => CRUISER_NAME,
-            ShipType::Submarine => SUBMARINE_NAME,
-            ShipType::Destroyer => DESTROYER_NAME,
+    };
+    for (i, letter) in COORDINATES_LETTERS.chars().take(height).enumerate() {
+        string += format!("{} {}", letter, wall).as_str();
+        for j in 0..width {
+            string += format!("{}{}", theme.paint(mark([i, j])), wall).as_str();
         }
+        string += "\n";
+        string += if i + 1 < height { separator.as_str() } else { bottom.as_str() };
     }
+    write!(f, "{}", string)
 }
-impl Display for Ship {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.name)
+
+// put two rendered boards next to each other with a title above each
+pub fn side_by_side(titles: [&str; 2], left: &str, right: &str) -> String {
+    // three spaces between the boards, whatever their size
+    let column = left.lines().map(visible_width).max().unwrap_or_default() + 3;
+    let mut string = format!("{:<column$}{}\n", titles[0], titles[1]);
+    for (left, right) in left.lines().zip(right.lines()) {
+        // escape codes don't take any space, so pad by the visible width
+        let padding = " ".repeat(column.saturating_sub(visible_width(left)));
+        string += format!("{}{}{}\n", left, padding, right).as_str();
     }
+    string
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bslib::game::ShipType;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::io::Cursor;
 
     #[test]
     fn test_placing() {
-        let mut my_board = OwnBoard::new();
+        let mut my_board = OwnBoard::new(&Ruleset::classic());
         let battleship = Ship::new(ShipType::Battleship);
         let carrier = Ship::new(ShipType::Carrier);
         let submarine = Ship::new(ShipType::Submarine);
         let destroyer = Ship::new(ShipType::Destroyer);
         let cruiser = Ship::new(ShipType::Cruiser);
         let ships = vec![carrier, battleship, cruiser, submarine, destroyer];
+        let mut input = Cursor::new("a1-a5\nc1-c4\ne1-e3\ng1-g3\ni1-i2\n");
         println!(
             "{:?}",
             my_board
-                .place_ships(&ships)
+                .place_ships_from(&ships, &mut input)
                 .expect("place ships nie działa")
         );
         println!("{}", my_board);
     }
+
+    #[test]
+    fn test_auto_placing() {
+        let ruleset = Ruleset::classic();
+        let ships = ruleset.get_fleet().iter().map(|&ship_type| Ship::new(ship_type)).collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(3);
+        let layout = random_fleet(&ruleset, PlacementMode::Uniform, &mut rng.clone()).expect("fleet should fit");
+        let destroyer = layout.get_ships()[4].get_tiles();
+        // unknown option, move the destroyer back where it was, accept
+        let answers = format!(
+            "x\ne\n5\n{}-{}\na\n",
+            encode_indexes(destroyer[0]),
+            encode_indexes(destroyer[1])
+        );
+        let mut my_board = OwnBoard::new(&ruleset);
+        my_board
+            .auto_place_from(&ships, PlacementMode::Uniform, &mut rng, &mut Cursor::new(answers))
+            .expect("failed to accept the generated layout");
+        let board = my_board.into_board();
+        assert_eq!(board.get_ships().len(), ruleset.get_fleet().len());
+        assert_eq!(board.get_ships()[4].get_tiles(), destroyer);
+
+        let mut my_board = OwnBoard::new(&ruleset);
+        let mut input = Cursor::new("r\nm\na1-a5\nc1-c4\ne1-e3\ng1-g3\ni1-i2\n");
+        my_board
+            .auto_place_from(&ships, PlacementMode::AntiAi, &mut rng, &mut input)
+            .expect("failed to place ships manually");
+        assert_eq!(my_board.into_board().get_ships()[0].get_tiles()[0], [0, 0]);
+    }
+
+    #[test]
+    fn test_coordinates_follow_the_board_size() {
+        let large = Ruleset::large().get_size();
+        assert_eq!(OwnBoard::decode_indexes("o15", large).expect("o15 is on a large board"), [14, 14]);
+        assert!(OwnBoard::decode_indexes("k1", Ruleset::classic().get_size()).is_err());
+        assert!(OwnBoard::decode_indexes("p1", large).is_err());
+        assert_eq!(
+            OwnBoard::decode_salvo(" a1  o15 ", 2, large).expect("salvo should be valid"),
+            [[0, 0], [14, 14]]
+        );
+        assert!(OwnBoard::decode_salvo("a1 a1", 2, large).is_err());
+        assert!(OwnBoard::decode_salvo("a1 b1", 3, large).is_err());
+        assert_eq!(OwnBoard::decode_weapon("b7", large).ok(), Some((None, [1, 6])));
+        assert_eq!(
+            OwnBoard::decode_weapon("radar b7", large).ok(),
+            Some((Some(Weapon::Radar), [1, 6]))
+        );
+        assert!(OwnBoard::decode_weapon("laser b7", large).is_err());
+        assert_eq!(OwnBoard::decode_move("move o15 up", large).ok(), Some(([14, 14], Direction::Up)));
+        assert!(OwnBoard::decode_move("move o15 sideways", large).is_err());
+
+        // patrol boats take a single tile
+        let mut my_board = OwnBoard::new(&Ruleset::russian());
+        let ships = [Ship::new(ShipType::PatrolBoat), Ship::new(ShipType::PatrolBoat)];
+        my_board
+            .place_ships_from(&ships, &mut Cursor::new("a1
+c3-c3
+"))
+            .expect("failed to place patrol boats");
+        assert_eq!(my_board.into_board().get_ships()[1].get_tiles(), [[2, 2]]);
+    }
+
+    #[test]
+    fn test_placing_shaped_ships() {
+        let mut my_board = OwnBoard::new(&Ruleset::house());
+        let ships = [Ship::new(ShipType::LShip), Ship::new(ShipType::Destroyer)];
+        // a range can't describe an L, the one given tile by tile has to be its shape
+        let mut input = Cursor::new("a1-c1
+a1 b1 c1 c3
+c1 a1 b1 c2
+e5 e6
+");
+        my_board
+            .place_ships_from(&ships, &mut input)
+            .expect("failed to place shaped ships");
+        let board = my_board.into_board();
+        assert_eq!(board.get_ships()[0].get_tiles(), [[2, 0], [0, 0], [1, 0], [2, 1]]);
+        assert_eq!(board.get_ships()[1].get_tiles(), [[4, 4], [4, 5]]);
+    }
 }
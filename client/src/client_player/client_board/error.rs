@@ -1,3 +1,4 @@
+use bslib::game::error::GameError;
 use core::fmt::Display;
 use std::num::ParseIntError;
 
@@ -15,6 +16,13 @@ impl Display for PlacingShipsError {
         write!(f, "PlacingShipsError: {}", self.msg)
     }
 }
+impl std::convert::From<GameError> for PlacingShipsError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct UserInputError {
@@ -0,0 +1,124 @@
+use std::fmt::Display;
+
+use bslib::game::ruleset::Ruleset;
+use bslib::game::{ShipType, ShotResult};
+use bslib::strategy::{Cell, Tracker};
+
+use super::write_board;
+use crate::theme::{Mark, Theme};
+
+// what we know about the opponent's board from our own shots - tiles next to a sunk ship
+// can't hold another ship, so they're marked as impossible
+pub struct TargetBoard {
+    tracker: Tracker,
+    // tiles that were ruled out until the opponent moved a ship
+    stale: Vec<[usize; 2]>,
+    theme: Theme,
+}
+impl TargetBoard {
+    pub fn new(ruleset: &Ruleset) -> Self {
+        Self {
+            tracker: Tracker::new(ruleset),
+            stale: Vec::new(),
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn get_tile(&self, tile: [usize; 2]) -> Cell {
+        self.tracker.get(tile)
+    }
+
+    // opponent's ships that weren't sunk yet
+    pub fn get_remaining(&self) -> &[ShipType] {
+        self.tracker.get_remaining()
+    }
+
+    // ruled out once, but not since the opponent moved a ship
+    pub fn is_stale(&self, tile: [usize; 2]) -> bool {
+        self.get_tile(tile) == Cell::Unknown && self.stale.contains(&tile)
+    }
+
+    pub fn is_shot(&self, tile: [usize; 2]) -> bool {
+        matches!(self.get_tile(tile), Cell::Miss | Cell::Hit | Cell::Sunk)
+    }
+
+    pub fn record(&mut self, target: [usize; 2], result: ShotResult) {
+        self.tracker.record(target, result);
+    }
+
+    // a radar that found nothing rules out every tile it scanned
+    pub fn record_scan(&mut self, area: &[[usize; 2]], detected: bool) {
+        self.tracker.record_scan(area, detected);
+    }
+
+    // the opponent moved one of their ships somewhere we can't see
+    pub fn record_move(&mut self) {
+        for tile in self.tracker.record_move() {
+            if !self.stale.contains(&tile) {
+                self.stale.push(tile);
+            }
+        }
+    }
+}
+impl Display for TargetBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_board(f, self.theme, self.tracker.get_size(), |tile| match self.get_tile(tile) {
+            Cell::Unknown if self.is_stale(tile) => Mark::Stale,
+            Cell::Unknown => Mark::Water,
+            Cell::Miss => Mark::Miss,
+            Cell::Hit => Mark::Hit,
+            Cell::Sunk => Mark::Sunk,
+            Cell::Blocked => Mark::Blocked,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sinking_marks_surroundings() {
+        let ruleset = Ruleset::classic();
+        let mut board = TargetBoard::new(&ruleset).with_theme(Theme::Ascii);
+        board.record([4, 4], ShotResult::Miss);
+        board.record([0, 1], ShotResult::Hit);
+        assert!(board.is_shot([0, 1]));
+        assert_eq!(board.get_tile([1, 1]), Cell::Unknown);
+
+        board.record([0, 0], ShotResult::Sunk(ShipType::Destroyer));
+        assert_eq!(board.get_tile([0, 0]), Cell::Sunk);
+        assert_eq!(board.get_tile([0, 1]), Cell::Sunk);
+        for tile in [[0, 2], [1, 0], [1, 1], [1, 2]] {
+            assert_eq!(board.get_tile(tile), Cell::Blocked);
+            assert!(!board.is_shot(tile));
+        }
+        assert_eq!(board.get_remaining().len(), ruleset.get_fleet().len() - 1);
+
+        let rendered = board.to_string();
+        let row_a = rendered.lines().find(|line| line.starts_with('a')).unwrap();
+        assert_eq!(row_a, "a | # | # | - |   |   |   |   |   |   |   |");
+        assert!(rendered.lines().any(|line| line.starts_with("e |   |   |   |   | * |")));
+    }
+
+    #[test]
+    fn test_moves_make_scans_stale() {
+        let mut board = TargetBoard::new(&Ruleset::classic().with_moving(true)).with_theme(Theme::Ascii);
+        board.record([0, 0], ShotResult::Hit);
+        board.record([0, 1], ShotResult::Sunk(ShipType::Destroyer));
+        board.record_scan(&[[5, 5], [5, 6]], false);
+        board.record_move();
+        // a sunk ship still keeps its surroundings free, the scan is out of date
+        assert_eq!(board.get_tile([1, 1]), Cell::Blocked);
+        assert!(board.is_stale([5, 5]) && board.is_stale([5, 6]));
+        assert!(board.to_string().lines().any(|line| line.starts_with("f |   |   |   |   |   | ? | ? |")));
+
+        board.record([5, 5], ShotResult::Miss);
+        assert!(!board.is_stale([5, 5]));
+    }
+}
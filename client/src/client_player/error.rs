@@ -1,10 +1,19 @@
 use bslib::tcp_protocol::error::ProtocolError;
 use core::fmt::Display;
 
+use crate::tui::error::TuiError;
+
 #[derive(Debug, Clone)]
 pub struct ConnectionError {
     msg: String,
 }
+impl ConnectionError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
 impl Display for ConnectionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "ConnectionError: {}", self.msg)
@@ -24,3 +33,10 @@ impl<T: ProtocolError> From<T> for ConnectionError {
         }
     }
 }
+impl From<TuiError> for ConnectionError {
+    fn from(value: TuiError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use bslib::game::ruleset::Ruleset;
+use bslib::game::ShotResult;
+
+use super::client_board::encode_indexes;
+use super::client_board::target_board::TargetBoard;
+use crate::theme::Theme;
+
+// who sits where at our game and what we know about their fleets - the line based and the full
+// screen client both pick the opponent they fire at here
+pub struct Table {
+    ruleset: Ruleset,
+    // nicks by seat, empty for seats nobody has taken yet
+    players: Vec<String>,
+    seat: usize,
+    // what we know about the fleet on every seat, only the opponents' ones are shown
+    targets: Vec<TargetBoard>,
+    // seat of the opponent our shots go to
+    opponent: usize,
+    // seats of the players that left a game that goes on without them
+    left: Vec<bool>,
+}
+impl Table {
+    pub fn new(ruleset: &Ruleset, players: Vec<String>) -> Self {
+        let mut table = Self {
+            ruleset: ruleset.clone(),
+            targets: players.iter().map(|_| TargetBoard::new(ruleset)).collect(),
+            left: vec![false; players.len()],
+            players,
+            seat: 0,
+            opponent: 0,
+        };
+        table.opponent = table.rivals().first().copied().unwrap_or_default();
+        table
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.targets = self.targets.into_iter().map(|target| target.with_theme(theme)).collect();
+        self
+    }
+
+    // our seat at the table, the first one unless the server says otherwise
+    pub fn with_seat(mut self, seat: usize) -> Self {
+        self.seat = seat;
+        self.opponent = self.rivals().first().copied().unwrap_or_default();
+        self
+    }
+
+    pub fn get_players(&self) -> &[String] {
+        &self.players
+    }
+
+    pub fn get_seat(&self) -> usize {
+        self.seat
+    }
+
+    // seat of the opponent the next shot goes to
+    pub fn get_opponent(&self) -> usize {
+        self.opponent
+    }
+
+    pub fn get_target(&self, seat: usize) -> &TargetBoard {
+        &self.targets[seat]
+    }
+
+    pub fn set_nick(&mut self, seat: usize, nick: String) {
+        self.players[seat] = nick;
+    }
+
+    // another player takes the seat our nick was keeping, which moves us on to the next empty one
+    pub fn sit(&mut self, seat: usize, nick: String) {
+        if seat >= self.players.len() {
+            return;
+        }
+        if seat == self.seat {
+            let ours = std::mem::take(&mut self.players[seat]);
+            self.seat = self.players.iter().position(|nick| nick.is_empty()).unwrap_or(seat);
+            self.players[self.seat] = ours;
+        }
+        self.players[seat] = nick;
+        self.opponent = self.rivals().first().copied().unwrap_or_default();
+    }
+
+    // seats of the other teams, whose boards we fire at
+    pub fn rivals(&self) -> Vec<usize> {
+        let team = self.ruleset.get_team(self.seat);
+        (0..self.players.len()).filter(|&seat| self.ruleset.get_team(seat) != team).collect()
+    }
+
+    // opponents that are still playing and have ships to fire at
+    pub fn rivals_afloat(&self) -> Vec<usize> {
+        let rivals = self.rivals().into_iter();
+        rivals
+            .filter(|&seat| !self.left[seat] && !self.targets[seat].get_remaining().is_empty())
+            .collect()
+    }
+
+    // shots at us land on our own board, which isn't kept here
+    pub fn record_shot(&mut self, opponent: usize, target: [usize; 2], result: ShotResult) {
+        if opponent != self.seat {
+            self.targets[opponent].record(target, result);
+        }
+    }
+
+    pub fn record_scan(&mut self, opponent: usize, area: &[[usize; 2]], detected: bool) {
+        if opponent != self.seat {
+            self.targets[opponent].record_scan(area, detected);
+        }
+    }
+
+    pub fn record_move(&mut self, player: usize) {
+        self.targets[player].record_move();
+    }
+
+    pub fn leave(&mut self, player: usize) {
+        self.left[player] = true;
+    }
+
+    // once our opponent is out of ships or gone, we go on to the next one - returns whether we did
+    pub fn move_on(&mut self) -> bool {
+        match self.rivals_afloat().contains(&self.opponent) {
+            true => false,
+            false => self.next_opponent(),
+        }
+    }
+
+    // the next opponent that still has ships, going around the table - returns whether there was one
+    pub fn next_opponent(&mut self) -> bool {
+        let afloat = self.rivals_afloat();
+        let next = afloat.iter().find(|&&seat| seat > self.opponent).or(afloat.first());
+        match next {
+            Some(&seat) => self.select_opponent(seat),
+            None => false,
+        }
+    }
+
+    // only opponents that still have ships can be picked, returns whether the opponent changed
+    pub fn select_opponent(&mut self, seat: usize) -> bool {
+        if seat == self.opponent || !self.rivals_afloat().contains(&seat) {
+            return false;
+        }
+        self.opponent = seat;
+        true
+    }
+
+    // just the tile in a duel, with whose board it's on when there are more players
+    pub fn describe_target(&self, opponent: usize, target: [usize; 2]) -> String {
+        match (self.players.len(), opponent == self.seat) {
+            (2, _) => encode_indexes(target),
+            (_, true) => format!("your {}", encode_indexes(target)),
+            (_, false) => format!("{}'s {}", self.players[opponent], encode_indexes(target)),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bslib::placement::PlacementMode;
+use error::ConfigError;
+use serde::Deserialize;
+
+use crate::theme::Theme;
+
+pub mod error;
+
+const DEFAULT_SERVER: &str = "127.0.0.1:8000";
+
+// defaults read from the config file - every field can be overridden on the command line
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    pub server: Option<String>,
+    pub nick: Option<String>,
+    pub unix_socket: Option<PathBuf>,
+    pub auto_place: Option<PlacementMode>,
+    pub plain: Option<bool>,
+    pub mouse: Option<bool>,
+    pub theme: Option<Theme>,
+    pub ruleset: Option<String>,
+}
+impl ClientConfig {
+    // location of the config file in the user's config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("battleships").join("client.toml"))
+    }
+
+    // load the config, falling back to defaults when the file doesn't exist - unless it's
+    // `required`, i.e. its path was given explicitly
+    pub fn load(path: &Path, required: bool) -> Result<Self, ConfigError> {
+        if !required && !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path).map_err(|e| ConfigError::new(&format!("{}: {e}", path.display())))?;
+        toml::from_str(&contents).map_err(|e| ConfigError::new(&format!("{}: {e}", path.display())))
+    }
+
+    // fill fields that weren't provided on the command line, `overrides` holds the ones that were
+    pub fn merge(self, overrides: ClientConfig) -> Settings {
+        Settings {
+            server: overrides
+                .server
+                .or(self.server)
+                .unwrap_or_else(|| String::from(DEFAULT_SERVER)),
+            nick: overrides.nick.or(self.nick),
+            unix_socket: overrides.unix_socket.or(self.unix_socket),
+            auto_place: overrides.auto_place.or(self.auto_place),
+            plain: overrides.plain.or(self.plain).unwrap_or(false),
+            mouse: overrides.mouse.or(self.mouse).unwrap_or(true),
+            theme: overrides.theme.or(self.theme).unwrap_or_default(),
+            ruleset: overrides.ruleset.or(self.ruleset),
+        }
+    }
+}
+
+// final settings used by the client
+#[derive(Debug)]
+pub struct Settings {
+    pub server: String,
+    pub nick: Option<String>,
+    pub unix_socket: Option<PathBuf>,
+    pub auto_place: Option<PlacementMode>,
+    pub plain: bool,
+    pub mouse: bool,
+    pub theme: Theme,
+    // preset asked for when joining a game, the server's default when not set
+    pub ruleset: Option<String>,
+}
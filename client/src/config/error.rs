@@ -0,0 +1,26 @@
+use core::fmt::Display;
+use std::io;
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    msg: String,
+}
+impl ConfigError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConfigError: {}", self.msg)
+    }
+}
+impl From<io::Error> for ConfigError {
+    fn from(value: io::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
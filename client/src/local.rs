@@ -0,0 +1,365 @@
+use std::io::{stdin, BufRead};
+use std::path::Path;
+
+use bslib::game::ruleset::Ruleset;
+use bslib::game::board::Board;
+use bslib::game::error::GameError;
+use bslib::game::{Game, Ship, ShotResult};
+use bslib::game_record::GameRecord;
+use bslib::placement::{random_fleet, PlacementMode};
+use bslib::strategy::{fill_salvo, Difficulty, Strategy};
+use error::LocalGameError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::client_player::client_board::target_board::TargetBoard;
+use crate::client_player::client_board::{encode_indexes, side_by_side, turn_prompt, OwnBoard};
+use crate::tui::app::{Action, App};
+use crate::theme::Theme;
+use crate::tui::Tui;
+
+pub mod error;
+
+const HUMAN: usize = 0;
+const COMPUTER: usize = 1;
+const COMPUTER_NICK: &str = "computer";
+const LOG_LINES: usize = 6;
+
+// computer opponent - picks targets with a strategy of the chosen difficulty
+struct Computer {
+    strategy: Box<dyn Strategy>,
+    // tiles fired at so far, for salvos the strategy cuts short
+    fired: Vec<[usize; 2]>,
+}
+impl Computer {
+    fn new(difficulty: Difficulty, ruleset: &Ruleset) -> Self {
+        Self {
+            strategy: difficulty.strategy(ruleset),
+            fired: Vec::new(),
+        }
+    }
+
+    // a single shot, or a whole salvo in games played with salvos
+    fn take_turn(&mut self, game: &mut Game, rng: &mut impl Rng) -> Result<Vec<([usize; 2], ShotResult)>, LocalGameError> {
+        let targets = match game.get_record().get_ruleset().is_salvo() {
+            true => {
+                let count = game.get_salvo_size(COMPUTER);
+                let mut targets = self.strategy.choose_salvo(count, rng);
+                let size = game.get_record().get_ruleset().get_size();
+                fill_salvo(&mut targets, count, size, &self.fired);
+                targets
+            }
+            false => {
+                let target = self
+                    .strategy
+                    .choose_shot(rng)
+                    .ok_or(LocalGameError::new("Computer has run out of tiles to fire at"))?;
+                vec![target]
+            }
+        };
+        let results = fire(game, COMPUTER, &targets)?;
+        for &(target, result) in &results {
+            self.strategy.on_result(target, result);
+            self.fired.push(target);
+        }
+        Ok(results)
+    }
+}
+
+// how the computer plays and what's kept of the game - every random choice, the computer's
+// included, comes from `seed` when it's pinned, so the same seed and moves give the same game
+pub struct LocalSettings<'a> {
+    pub difficulty: Difficulty,
+    pub seed: Option<u64>,
+    pub record_path: Option<&'a Path>,
+}
+
+// play a whole game against the computer without connecting to a server
+pub fn run(
+    nick: String,
+    ruleset: Ruleset,
+    auto_place: Option<PlacementMode>,
+    theme: Theme,
+    settings: LocalSettings,
+) -> Result<(), LocalGameError> {
+    check_duel(&ruleset)?;
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let ships = ruleset.get_fleet().iter().map(|&ship_type| Ship::new(ship_type)).collect::<Vec<_>>();
+    let mut own_board = OwnBoard::new(&ruleset).with_theme(theme);
+    match auto_place {
+        Some(mode) => own_board.auto_place(&ships, mode, &mut rng)?,
+        None => own_board.place_ships(&ships)?,
+    }
+
+    let mut computer = Computer::new(settings.difficulty, &ruleset);
+    let boards = [own_board.into_board(), random_fleet(&ruleset, PlacementMode::Uniform, &mut rng)?];
+    let first = rng.gen_range(0..2);
+    let players = [nick, String::from(COMPUTER_NICK)];
+    let mut log = vec![format!("{} fires first", players[first])];
+    let mut game = start_game(&players, &ruleset, boards, first, seed);
+    let mut target_board = TargetBoard::new(&ruleset).with_theme(theme);
+    let mut input = stdin().lock();
+
+    while game.get_winner().is_none() {
+        if game.get_turn() == COMPUTER {
+            for (target, result) in computer.take_turn(&mut game, &mut rng)? {
+                log.push(format!("{} fires at {} - {}", COMPUTER_NICK, encode_indexes(target), result));
+            }
+            continue;
+        }
+
+        render(&game, &target_board, &players, &log, &ruleset, theme, false);
+        let salvo = next_salvo(&game);
+        match salvo {
+            0 => println!("{}", turn_prompt(game.get_arsenal(HUMAN), ruleset.is_moving())),
+            count => println!("Your turn - enter {} tiles to fire at in one salvo like this >>b7 c3<<:", count),
+        }
+        let mut buf = String::new();
+        if input.read_line(&mut buf)? == 0 {
+            return Err(LocalGameError::new("Input closed before the game has ended"));
+        }
+        if ruleset.is_moving() && buf.trim_start().starts_with("move") {
+            match OwnBoard::decode_move(&buf, ruleset.get_size()) {
+                Ok((tile, direction)) => match game.move_ship(HUMAN, tile, direction) {
+                    Ok(()) => log.push(format!("you move the ship at {} {}", encode_indexes(tile), direction)),
+                    Err(e) => log.push(format!("{} - trying again", e)),
+                },
+                Err(e) => log.push(format!("{} - trying again", e)),
+            }
+            continue;
+        }
+        let turn = match salvo {
+            0 => OwnBoard::decode_weapon(&buf, ruleset.get_size()).map(|(weapon, target)| (weapon, vec![target])),
+            count => OwnBoard::decode_salvo(&buf, count, ruleset.get_size()).map(|targets| (None, targets)),
+        };
+        let (weapon, targets) = match turn {
+            Ok(turn) => turn,
+            Err(e) => {
+                log.push(format!("{} - trying again", e));
+                continue;
+            }
+        };
+        if let Some(weapon) = weapon {
+            match game.use_weapon(HUMAN, COMPUTER, weapon, targets[0]) {
+                Ok(strike) => {
+                    log.push(format!("you use the {} at {}", weapon, encode_indexes(targets[0])));
+                    for &(target, result) in strike.get_shots() {
+                        target_board.record(target, result);
+                        log.push(format!("you fire at {} - {}", encode_indexes(target), result))
+                    }
+                    if let Some(detected) = strike.get_detected() {
+                        let area = weapon.area(targets[0], ruleset.get_size());
+                        target_board.record_scan(&area, detected);
+                        log.push(format!("the radar {}", if detected { "found a ship" } else { "found nothing" }));
+                    }
+                }
+                Err(e) => log.push(format!("{} - trying again", e)),
+            }
+            continue;
+        }
+        match fire(&mut game, HUMAN, &targets) {
+            Ok(results) => {
+                for (target, result) in results {
+                    target_board.record(target, result);
+                    log.push(format!("you fire at {} - {}", encode_indexes(target), result))
+                }
+            }
+            Err(e) => log.push(format!("{} - trying again", e)),
+        }
+    }
+
+    let winner = game.get_winner().unwrap_or(HUMAN);
+    log.push(match winner {
+        HUMAN => String::from("You won!"),
+        _ => String::from("You lost!"),
+    });
+    render(&game, &target_board, &players, &log, &ruleset, theme, true);
+
+    if let Some(path) = settings.record_path {
+        game.get_record().save(path)?;
+        println!("Game saved to {}", path.display());
+    }
+    Ok(())
+}
+
+// same game as `run`, played on the full screen interface
+pub async fn run_tui(
+    nick: String,
+    ruleset: Ruleset,
+    auto_place: Option<PlacementMode>,
+    mouse: bool,
+    theme: Theme,
+    settings: LocalSettings<'_>,
+) -> Result<(), LocalGameError> {
+    check_duel(&ruleset)?;
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let players = [nick, String::from(COMPUTER_NICK)];
+    let mut app = App::new(players.to_vec(), &ruleset, auto_place)
+        .with_seed(rng.gen())
+        .with_mouse(mouse)
+        .with_theme(theme);
+    let mut tui = Tui::start(mouse)?;
+    let mut game: Option<Game> = None;
+    let mut computer = Computer::new(settings.difficulty, &ruleset);
+
+    loop {
+        if let Some(game) = game.as_mut().filter(|game| game.get_winner().is_none()) {
+            if game.get_turn() == COMPUTER {
+                for (target, result) in computer.take_turn(game, &mut rng)? {
+                    app.shot(COMPUTER, HUMAN, target, result);
+                }
+                end_turn(&mut app, game);
+                continue;
+            }
+        }
+
+        tui.draw(&mut app)?;
+        let Some(input) = tui.next_input().await else {
+            return Err(LocalGameError::new("Terminal input has closed"));
+        };
+        match app.handle_input(input) {
+            Action::Quit => break,
+            Action::FleetPlaced => {
+                let boards = [
+                    app.get_own_board().clone(),
+                    random_fleet(&ruleset, PlacementMode::Uniform, &mut rng)?,
+                ];
+                let first = rng.gen_range(0..2);
+                let started = start_game(&players, &ruleset, boards, first, seed);
+                app.set_salvo(next_salvo(&started));
+                app.set_arsenal(started.get_arsenal(HUMAN));
+                app.start(first == HUMAN);
+                game = Some(started);
+            }
+            Action::Fire(target) => {
+                if let Some(game) = game.as_mut() {
+                    human_turn(&mut app, game, &[target]);
+                }
+            }
+            Action::Salvo(targets) => {
+                if let Some(game) = game.as_mut() {
+                    human_turn(&mut app, game, &targets);
+                }
+            }
+            Action::Weapon(weapon, target) => {
+                if let Some(game) = game.as_mut() {
+                    match game.use_weapon(HUMAN, COMPUTER, weapon, target) {
+                        Ok(strike) => {
+                            app.weapon_result(HUMAN, COMPUTER, weapon, target, &strike);
+                            app.set_arsenal(game.get_arsenal(HUMAN));
+                            end_turn(&mut app, game);
+                        }
+                        Err(e) => app.reject(&format!("{}", e)),
+                    }
+                }
+            }
+            Action::Move(tile, direction) => {
+                if let Some(game) = game.as_mut() {
+                    match game.move_ship(HUMAN, tile, direction) {
+                        Ok(()) => {
+                            app.move_result(HUMAN, Some((tile, direction)));
+                            end_turn(&mut app, game);
+                        }
+                        Err(e) => app.reject(&format!("{}", e)),
+                    }
+                }
+            }
+            Action::None => (),
+        }
+    }
+    drop(tui);
+
+    if let (Some(path), Some(game)) = (settings.record_path, game.filter(|game| game.get_winner().is_some())) {
+        game.get_record().save(path)?;
+        println!("Game saved to {}", path.display());
+    }
+    Ok(())
+}
+
+// the human's shot or salvo, a rejected one can be aimed again
+fn human_turn(app: &mut App, game: &mut Game, targets: &[[usize; 2]]) {
+    match fire(game, HUMAN, targets) {
+        Ok(results) => {
+            for (target, result) in results {
+                app.shot(HUMAN, COMPUTER, target, result);
+            }
+            end_turn(app, game);
+        }
+        Err(e) => app.reject(&format!("{}", e)),
+    }
+}
+
+// targets of a turn - a single shot, or a salvo in games played with salvos
+fn fire(game: &mut Game, player: usize, targets: &[[usize; 2]]) -> Result<Vec<([usize; 2], ShotResult)>, GameError> {
+    let opponent = 1 - player;
+    let results = match (game.get_record().get_ruleset().is_salvo(), targets) {
+        (false, &[target]) => vec![game.fire(player, opponent, target)?],
+        _ => game.fire_salvo(player, opponent, targets)?,
+    };
+    Ok(targets.iter().copied().zip(results).collect())
+}
+
+// shots in the human's next salvo, 0 in games without salvos
+fn next_salvo(game: &Game) -> usize {
+    match game.get_record().get_ruleset().is_salvo() {
+        true => game.get_salvo_size(HUMAN),
+        false => 0,
+    }
+}
+
+fn end_turn(app: &mut App, game: &Game) {
+    match game.get_winner() {
+        Some(winner) => app.game_over(winner == HUMAN, "all ships were sunk"),
+        None => {
+            app.set_salvo(next_salvo(game));
+            app.set_turn(game.get_turn() == HUMAN);
+        }
+    }
+}
+
+// the computer is the only opponent, so there's no room for teams
+fn check_duel(ruleset: &Ruleset) -> Result<(), LocalGameError> {
+    match ruleset.get_players() {
+        2 => Ok(()),
+        _ => Err(LocalGameError::new("Local games are played against a single computer opponent")),
+    }
+}
+
+// game with both fleets placed, recorded under the chosen ruleset along with its seed
+fn start_game(
+    players: &[String; 2],
+    ruleset: &Ruleset,
+    boards: [Board; 2],
+    first: usize,
+    seed: u64,
+) -> Game {
+    let mut record = GameRecord::new(players.to_vec());
+    record.set_ruleset(ruleset.clone());
+    record.set_seed(seed);
+    Game::from_record(record, boards.into(), first)
+}
+
+fn render(
+    game: &Game,
+    target_board: &TargetBoard,
+    players: &[String; 2],
+    log: &[String],
+    ruleset: &Ruleset,
+    theme: Theme,
+    reveal: bool,
+) {
+    let own = OwnBoard::from_board(game.get_board(HUMAN).clone(), ruleset).with_theme(theme).to_string();
+    let opponent = match reveal {
+        true => OwnBoard::from_board(game.get_board(COMPUTER).clone(), ruleset)
+            .with_theme(theme)
+            .to_string(),
+        false => target_board.to_string(),
+    };
+    print!("\x1B[2J\x1B[1;1H");
+    print!("{}", side_by_side([&players[HUMAN], &players[COMPUTER]], &own, &opponent));
+    for line in &log[log.len().saturating_sub(LOG_LINES)..] {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::client_player::client_board::error::PlacingShipsError;
+use crate::tui::error::TuiError;
+use bslib::game::error::GameError;
+use bslib::game_record::error::GameRecordError;
+use core::fmt::Display;
+use std::io;
+
+#[derive(Debug, Clone)]
+pub struct LocalGameError {
+    msg: String,
+}
+impl LocalGameError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for LocalGameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "LocalGameError: {}", self.msg)
+    }
+}
+impl From<PlacingShipsError> for LocalGameError {
+    fn from(value: PlacingShipsError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<GameError> for LocalGameError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<GameRecordError> for LocalGameError {
+    fn from(value: GameRecordError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<io::Error> for LocalGameError {
+    fn from(value: io::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<TuiError> for LocalGameError {
+    fn from(value: TuiError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
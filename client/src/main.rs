@@ -1,19 +1,233 @@
-use std::io::stdin;
+use std::io::{self, stdin};
+use std::path::PathBuf;
+use std::process::ExitCode;
 
+use bslib::bot::{BotRunner, StrategyBot};
+use bslib::game::ruleset::Ruleset;
+use bslib::placement::PlacementMode;
+use bslib::strategy::Difficulty;
+use bslib::tcp_protocol::Connection;
+use clap::{Parser, Subcommand};
+use client_player::error::ConnectionError;
 use client_player::ClientPlayer;
+use config::{ClientConfig, Settings};
+use local::LocalSettings;
+use theme::Theme;
 use tokio::net::TcpStream;
 
 mod client_player;
+mod config;
+mod local;
+mod replay;
+mod theme;
+mod tui;
+
+#[derive(Parser)]
+#[command(version, about = "Terminal battleships client")]
+struct Cli {
+    /// Server address (host:port), defaults to 127.0.0.1:8000
+    #[arg(long, global = true)]
+    server: Option<String>,
+    /// Nick shown to other players, asked for when not set
+    #[arg(long, global = true)]
+    nick: Option<String>,
+    /// Connect through a unix socket instead of tcp
+    #[arg(long, global = true)]
+    unix_socket: Option<PathBuf>,
+    /// Generate the fleet layout (uniform or anti-ai) and offer to accept, reroll or edit it
+    #[arg(long, global = true)]
+    auto_place: Option<PlacementMode>,
+    /// Use the line based interface instead of the full screen one
+    #[arg(long, global = true)]
+    plain: bool,
+    /// Don't capture the mouse in the full screen interface, only use the keyboard
+    #[arg(long, global = true)]
+    no_mouse: bool,
+    /// How boards look: color, high-contrast, colorblind, ascii or unicode
+    #[arg(long, global = true)]
+    theme: Option<Theme>,
+    /// Rules to play with: classic, russian, large or house, with options like +salvo
+    #[arg(long, global = true, value_parser = parse_ruleset)]
+    ruleset: Option<String>,
+    /// Config file to read defaults from
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Play against other people on a server (default)
+    Play,
+    /// Step through a recorded game
+    Replay { file: PathBuf },
+    /// Let a bot play on a server
+    Bot {
+        /// How well the bot aims: random, hunt-target or density
+        #[arg(long, default_value = "density")]
+        difficulty: Difficulty,
+        /// How the bot places its fleet: uniform or anti-ai
+        #[arg(long, default_value = "anti-ai")]
+        placement: PlacementMode,
+        /// Number of games to play, 0 keeps playing until the server is gone
+        #[arg(long, default_value_t = 1)]
+        games: usize,
+    },
+    /// Play against the computer without a server
+    Local {
+        /// How well the computer aims: random, hunt-target or density
+        #[arg(long, default_value = "hunt-target")]
+        difficulty: Difficulty,
+        /// Save the finished game to this file so it can be replayed
+        #[arg(long)]
+        record: Option<PathBuf>,
+        /// Seed for the computer and every other random choice, the same seed and moves
+        /// play the same game again
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+}
 
 #[tokio::main]
-async fn main() {
-    let stream = TcpStream::connect("127.0.0.1:8000").await.unwrap();
-    println!("Hello, world!");
-    let nick = get_nick();
-    let mut player = ClientPlayer::new(stream);
-    player.connect(nick).await.expect("failed to connect to a game");
-
-    player.set_up().expect("failed to set up a board");
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let config = match (cli.config, ClientConfig::default_path()) {
+        (Some(path), _) => ClientConfig::load(&path, true),
+        (None, Some(path)) => ClientConfig::load(&path, false),
+        (None, None) => Ok(ClientConfig::default()),
+    };
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            println!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let settings = config.merge(ClientConfig {
+        server: cli.server,
+        nick: cli.nick,
+        unix_socket: cli.unix_socket,
+        auto_place: cli.auto_place,
+        plain: cli.plain.then_some(true),
+        mouse: cli.no_mouse.then_some(false),
+        theme: cli.theme,
+        ruleset: cli.ruleset,
+    });
+    if let Some(name) = settings.ruleset.as_deref() {
+        if let Err(e) = parse_ruleset(name) {
+            println!("{e}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    match cli.command.unwrap_or(Command::Play) {
+        Command::Play => {
+            if let Err(e) = play(settings).await {
+                println!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Replay { file } => {
+            if let Err(e) = replay::run(&file, settings.theme) {
+                println!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Local { difficulty, record, seed } => {
+            let nick = settings.nick.unwrap_or_else(get_nick);
+            let ruleset = settings.ruleset.as_deref().and_then(Ruleset::preset).unwrap_or_default();
+            let local_settings = LocalSettings {
+                difficulty,
+                seed,
+                record_path: record.as_deref(),
+            };
+            let result = match settings.plain {
+                true => local::run(nick, ruleset, settings.auto_place, settings.theme, local_settings),
+                false => {
+                    local::run_tui(
+                        nick,
+                        ruleset,
+                        settings.auto_place,
+                        settings.mouse,
+                        settings.theme,
+                        local_settings,
+                    )
+                    .await
+                }
+            };
+            if let Err(e) = result {
+                println!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        Command::Bot {
+            difficulty,
+            placement,
+            games,
+        } => {
+            let nick = settings.nick.clone().unwrap_or_else(|| format!("{difficulty}-bot"));
+            let bot = StrategyBot::new(difficulty, placement);
+            let mut runner = BotRunner::new(bot, &nick)
+                .games((games > 0).then_some(games))
+                .ruleset(settings.ruleset.clone());
+            match runner.run(|| open_connection(&settings)).await {
+                Ok(summary) => println!("{nick} {summary}"),
+                Err(e) => {
+                    println!("{e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+async fn play(settings: Settings) -> Result<(), ConnectionError> {
+    let stream = open_connection(&settings)
+        .await
+        .map_err(|e| ConnectionError::new(&format!("failed to connect to the server - {e}")))?;
+    let nick = settings.nick.unwrap_or_else(get_nick);
+    let mut player = ClientPlayer::new(stream).with_theme(settings.theme);
+    player
+        .connect(nick.clone(), settings.ruleset)
+        .await
+        .map_err(|e| ConnectionError::new(&format!("failed to connect to a game - {e}")))?;
+    player
+        .wait_for_opponent()
+        .await
+        .map_err(|e| ConnectionError::new(&format!("failed to find an opponent - {e}")))?;
+
+    if !settings.plain {
+        let won = player.play_tui(nick, settings.auto_place, settings.mouse).await?;
+        println!("{}", if won { "You won!" } else { "You lost!" });
+        return Ok(());
+    }
+    player
+        .set_up(settings.auto_place)
+        .map_err(|e| ConnectionError::new(&format!("failed to set up a board - {e}")))?;
+    player.play().await?;
+    Ok(())
+}
+
+async fn open_connection(settings: &Settings) -> io::Result<Box<dyn Connection>> {
+    if let Some(path) = &settings.unix_socket {
+        #[cfg(unix)]
+        return Ok(Box::new(tokio::net::UnixStream::connect(path).await?));
+        #[cfg(not(unix))]
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unix sockets are not supported here: {}", path.display()),
+        ));
+    }
+    Ok(Box::new(TcpStream::connect(&settings.server).await?))
+}
+
+fn parse_ruleset(name: &str) -> Result<String, String> {
+    match Ruleset::preset(name) {
+        Some(_) => Ok(String::from(name)),
+        None => Err(format!("unknown ruleset `{name}`, expected one of: {}", Ruleset::describe_presets())),
+    }
 }
 
 fn get_nick() -> String {
@@ -22,8 +236,10 @@ fn get_nick() -> String {
         let mut buf = String::new();
         if stdin().read_line(&mut buf).is_err() {
             continue;
-        } else {
-            return buf;
+        }
+        let nick = buf.trim();
+        if !nick.is_empty() {
+            return String::from(nick);
         }
     }
 }
@@ -0,0 +1,265 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use bslib::game::board::Board;
+use bslib::game::ShotResult;
+use bslib::game_record::GameRecord;
+use error::ReplayError;
+
+use crate::client_player::client_board::{encode_indexes, side_by_side, OwnBoard};
+use crate::theme::Theme;
+
+pub mod error;
+
+const MIN_DELAY_MS: u64 = 125;
+const MAX_DELAY_MS: u64 = 4000;
+const CONTROLS: &str =
+    "[enter/n] next  [b] back  [p] play/pause  [+/-] speed  [s <n>] go to shot  [q] quit";
+
+// steps through a recorded game, rebuilding both boards for the current position
+pub struct Replay {
+    record: GameRecord,
+    position: usize,
+    theme: Theme,
+}
+impl Replay {
+    pub fn new(record: GameRecord) -> Self {
+        Self {
+            record,
+            position: 0,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let replay = Self::new(GameRecord::load(path)?);
+        // make sure the recorded fleets can be put on the boards before showing anything
+        replay.boards()?;
+        Ok(replay)
+    }
+
+    pub fn len(&self) -> usize {
+        self.record.get_shots().len()
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.len()
+    }
+
+    pub fn step_forward(&mut self) -> bool {
+        if self.is_at_end() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    pub fn step_back(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    pub fn go_to(&mut self, position: usize) {
+        self.position = position.min(self.len());
+    }
+
+    // every player's board with ships placed and all shots up to the current position applied,
+    // along with the result of the last of those shots
+    fn boards(&self) -> Result<(Vec<Board>, Option<ShotResult>), ReplayError> {
+        let ruleset = self.record.get_ruleset();
+        let mut boards = vec![Board::from_ruleset(ruleset); self.record.get_players().len()];
+        for (player, board) in boards.iter_mut().enumerate() {
+            for placed in self.record.get_fleet(player) {
+                board.place_ship(placed.get_type(), placed.get_tiles().to_vec())?;
+            }
+        }
+        let mut last_result = None;
+        for shot in &self.record.get_shots()[..self.position] {
+            last_result = match shot.get_move() {
+                Some(direction) => {
+                    boards[shot.get_player()].move_ship(shot.get_target(), direction)?;
+                    None
+                }
+                None => Some(boards[shot.get_opponent()].fire(shot.get_target())?),
+            };
+        }
+        Ok((boards, last_result))
+    }
+
+    // description of the shot or move that led to the current position
+    fn describe_last_shot(&self, result: Option<ShotResult>) -> String {
+        let Some(index) = self.position.checked_sub(1) else {
+            return String::from("Game start");
+        };
+        let shot = self.record.get_shots()[index];
+        let players = self.record.get_players();
+        let player = &players[shot.get_player()];
+        let target = match players.len() {
+            2 => encode_indexes(shot.get_target()),
+            // with more boards than two it matters whose board was fired at
+            _ => format!("{}'s {}", players[shot.get_opponent()], encode_indexes(shot.get_target())),
+        };
+        match (shot.get_move(), result) {
+            (Some(direction), _) => format!("{} moves the ship at {} {}", player, target, direction),
+            (None, Some(result)) => format!("{} fires at {} - {}", player, target, result),
+            (None, None) => format!("{} fires at {}", player, target),
+        }
+    }
+
+    fn render(&self, status: &str) -> Result<(), ReplayError> {
+        let (boards, result) = self.boards()?;
+        let last_shot = self.describe_last_shot(result);
+        let boards = boards
+            .into_iter()
+            .map(|board| OwnBoard::from_board(board, self.record.get_ruleset()).with_theme(self.theme).to_string())
+            .collect::<Vec<_>>();
+
+        print!("\x1B[2J\x1B[1;1H");
+        // two boards to a row, the last one is on its own when there's an odd number of them
+        for (boards, players) in boards.chunks(2).zip(self.record.get_players().chunks(2)) {
+            match (boards, players) {
+                ([left, right], [first, second]) => print!("{}", side_by_side([first, second], left, right)),
+                _ => print!("{}\n{}", players[0], boards[0]),
+            }
+        }
+        println!("Shot {}/{}: {}", self.position, self.len(), last_shot);
+        println!("{}", status);
+        println!("{}", CONTROLS);
+        Ok(())
+    }
+}
+
+// interactive replay loop - input is read on a separate thread so playback can run while waiting
+pub fn run(path: &Path, theme: Theme) -> Result<(), ReplayError> {
+    let mut replay = Replay::load(path)?.with_theme(theme);
+    let input = spawn_input_reader();
+    let mut playing = false;
+    let mut delay = 1000;
+
+    loop {
+        let status = match playing {
+            true => format!("Playing ({} ms per shot)", delay),
+            false => String::from("Paused"),
+        };
+        replay.render(&status)?;
+
+        let line = if playing {
+            match input.recv_timeout(Duration::from_millis(delay)) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    if !replay.step_forward() {
+                        playing = false;
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        } else {
+            match input.recv() {
+                Ok(line) => line,
+                Err(_) => return Ok(()),
+            }
+        };
+
+        match line.trim() {
+            "" | "n" => {
+                replay.step_forward();
+            }
+            "b" => {
+                replay.step_back();
+            }
+            "p" => playing = !playing && !replay.is_at_end(),
+            "+" => delay = (delay / 2).max(MIN_DELAY_MS),
+            "-" => delay = (delay * 2).min(MAX_DELAY_MS),
+            "q" => return Ok(()),
+            cmd => {
+                if let Some(Ok(position)) = cmd.strip_prefix("s ").map(|n| n.trim().parse()) {
+                    replay.go_to(position);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_input_reader() -> Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let stdin = std::io::stdin();
+        loop {
+            let mut buf = String::new();
+            match stdin.read_line(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send(buf).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bslib::game::movement::Direction;
+    use bslib::game::ruleset::Ruleset;
+    use bslib::game::ShipType;
+    use bslib::game_record::PlacedShip;
+
+    fn sample_record() -> GameRecord {
+        let mut record = GameRecord::new(vec![String::from("alice"), String::from("bob")]);
+        record.add_ship(0, PlacedShip::new(ShipType::Destroyer, vec![[0, 0], [0, 1]]));
+        record.add_ship(1, PlacedShip::new(ShipType::Destroyer, vec![[5, 5], [6, 5]]));
+        record.add_shot(0, 1, [5, 5]);
+        record.add_shot(1, 0, [9, 9]);
+        record.add_shot(0, 1, [6, 5]);
+        record
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut replay = Replay::new(sample_record());
+        assert!(!replay.step_back());
+        assert!(replay.step_forward());
+        assert!(replay.step_forward());
+        assert!(replay.step_forward());
+        assert!(!replay.step_forward());
+        assert!(replay.is_at_end());
+        replay.go_to(1);
+        assert_eq!(replay.position, 1);
+        let (_, result) = replay.boards().expect("failed to rebuild boards");
+        assert_eq!(replay.describe_last_shot(result), "alice fires at f6 - hit");
+        replay.go_to(3);
+        let (_, result) = replay.boards().expect("failed to rebuild boards");
+        assert_eq!(replay.describe_last_shot(result), "alice fires at g6 - destroyer sunk");
+    }
+
+    #[test]
+    fn test_replaying_moves() {
+        let mut record = sample_record();
+        record.set_ruleset(Ruleset::classic().with_moving(true));
+        record.add_move(0, [0, 0], Direction::Down);
+        record.add_shot(1, 0, [0, 0]);
+        let mut replay = Replay::new(record);
+        replay.go_to(4);
+        let (boards, result) = replay.boards().expect("failed to rebuild boards");
+        assert_eq!(replay.describe_last_shot(result), "alice moves the ship at a1 down");
+        assert_eq!(boards[0].get_ships()[0].get_tiles(), [[1, 0], [1, 1]]);
+        // the shot after the move lands where the ship used to be
+        replay.go_to(5);
+        let (_, result) = replay.boards().expect("failed to rebuild boards");
+        assert_eq!(replay.describe_last_shot(result), "bob fires at a1 - miss");
+    }
+}
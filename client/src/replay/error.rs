@@ -0,0 +1,27 @@
+use bslib::game::error::GameError;
+use bslib::game_record::error::GameRecordError;
+use core::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct ReplayError {
+    msg: String,
+}
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReplayError: {}", self.msg)
+    }
+}
+impl From<GameRecordError> for ReplayError {
+    fn from(value: GameRecordError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<GameError> for ReplayError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use ratatui::crossterm::style::{style, Attribute, Stylize};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use serde::Deserialize;
+
+// okabe-ito colors, told apart with every common kind of color blindness
+const ORANGE: Color = Color::Rgb(230, 159, 0);
+const SKY_BLUE: Color = Color::Rgb(86, 180, 233);
+const BLUE: Color = Color::Rgb(0, 114, 178);
+const VERMILLION: Color = Color::Rgb(213, 94, 0);
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+// what a single tile shows - own and target boards use the same marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Water,
+    Ship,
+    Hit,
+    Miss,
+    Sunk,
+    Blocked,
+    // ruled out before the opponent moved a ship, so it might hold one again
+    Stale,
+}
+
+// how boards are drawn, both in the full screen interface and as plain text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Color,
+    HighContrast,
+    Colorblind,
+    Ascii,
+    Unicode,
+}
+impl Theme {
+    pub const ALL: [Theme; 5] = [
+        Self::Color,
+        Self::HighContrast,
+        Self::Colorblind,
+        Self::Ascii,
+        Self::Unicode,
+    ];
+
+    pub fn get_str(&self) -> &'static str {
+        match self {
+            Self::Color => "color",
+            Self::HighContrast => "high-contrast",
+            Self::Colorblind => "colorblind",
+            Self::Ascii => "ascii",
+            Self::Unicode => "unicode",
+        }
+    }
+
+    // box drawing characters for grids and borders instead of ascii
+    pub fn is_unicode(&self) -> bool {
+        *self == Self::Unicode
+    }
+
+    pub fn symbol(&self, mark: Mark) -> &'static str {
+        match (self, mark) {
+            (Self::Unicode, Mark::Water) => " · ",
+            (_, Mark::Water) => "   ",
+            (Self::Unicode, Mark::Ship) => " ■ ",
+            (_, Mark::Ship) => " @ ",
+            (Self::Unicode, Mark::Hit) => " ✕ ",
+            (_, Mark::Hit) => " X ",
+            (Self::Unicode, Mark::Miss) => " • ",
+            (Self::HighContrast, Mark::Miss) => " o ",
+            (_, Mark::Miss) => " * ",
+            (Self::Unicode, Mark::Sunk) => " ▣ ",
+            (_, Mark::Sunk) => " # ",
+            (Self::Unicode, Mark::Blocked) => " ░ ",
+            (Self::HighContrast, Mark::Blocked) => " . ",
+            (_, Mark::Blocked) => " - ",
+            (_, Mark::Stale) => " ? ",
+        }
+    }
+
+    pub fn style(&self, mark: Mark) -> Style {
+        let style = Style::new();
+        match self {
+            Self::Ascii => style,
+            Self::Color | Self::Unicode => match mark {
+                Mark::Water | Mark::Blocked => style.fg(Color::DarkGray),
+                Mark::Ship => style,
+                Mark::Hit => style.fg(Color::Red),
+                Mark::Miss => style.fg(Color::LightBlue),
+                Mark::Sunk => style.fg(Color::LightRed).add_modifier(Modifier::BOLD),
+                Mark::Stale => style.fg(Color::Yellow),
+            },
+            Self::HighContrast => match mark {
+                Mark::Water => style,
+                Mark::Blocked | Mark::Stale => style.fg(Color::Gray),
+                Mark::Ship | Mark::Miss => style.fg(Color::White).add_modifier(Modifier::BOLD),
+                Mark::Hit => style.fg(Color::Black).bg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                Mark::Sunk => style.fg(Color::Black).bg(Color::LightRed).add_modifier(Modifier::BOLD),
+            },
+            Self::Colorblind => match mark {
+                Mark::Water | Mark::Blocked => style.fg(Color::DarkGray),
+                Mark::Ship => style,
+                Mark::Stale => style.fg(Color::Gray),
+                Mark::Hit => style.fg(ORANGE),
+                Mark::Miss => style.fg(SKY_BLUE),
+                Mark::Sunk => style.fg(VERMILLION).add_modifier(Modifier::BOLD),
+            },
+        }
+    }
+
+    // tiles of a ship that is being placed, showing whether it can go there
+    pub fn preview(&self, valid: bool) -> (&'static str, Style) {
+        let style = Style::new();
+        match (self, valid) {
+            (Self::Ascii, true) => (" + ", style),
+            (Self::Ascii, false) => (" ! ", style),
+            (Self::HighContrast, true) => (" @ ", style.fg(Color::Black).bg(Color::White)),
+            (Self::Colorblind, true) => (" @ ", style.bg(BLUE)),
+            (Self::Colorblind, false) => (" @ ", style.bg(ORANGE)),
+            (_, true) => (self.symbol(Mark::Ship), style.bg(Color::Green)),
+            (_, false) => (self.symbol(Mark::Ship), style.bg(Color::Red)),
+        }
+    }
+
+    // border of the board that is being played on
+    pub fn active_border(&self) -> Style {
+        match self {
+            Self::Ascii | Self::HighContrast => Style::new().add_modifier(Modifier::BOLD),
+            Self::Colorblind => Style::new().fg(SKY_BLUE),
+            Self::Color | Self::Unicode => Style::new().fg(Color::Cyan),
+        }
+    }
+
+    pub fn border(&self) -> border::Set {
+        match self {
+            Self::Ascii => ASCII_BORDER,
+            Self::HighContrast => border::THICK,
+            Self::Unicode => border::ROUNDED,
+            Self::Color | Self::Colorblind => border::PLAIN,
+        }
+    }
+
+    // the mark's symbol with escape codes for its style, as is when the style is empty
+    pub fn paint(&self, mark: Mark) -> String {
+        paint(self.symbol(mark), self.style(mark))
+    }
+}
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.get_str())
+    }
+}
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|theme| theme.get_str() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.map(|theme| theme.get_str());
+                format!("unknown theme `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+pub fn paint(symbol: &str, ratatui_style: Style) -> String {
+    if ratatui_style == Style::new() {
+        return String::from(symbol);
+    }
+    let mut content = style(symbol);
+    if let Some(fg) = ratatui_style.fg {
+        content = content.with(fg.into());
+    }
+    if let Some(bg) = ratatui_style.bg {
+        content = content.on(bg.into());
+    }
+    if ratatui_style.add_modifier.contains(Modifier::BOLD) {
+        content = content.attribute(Attribute::Bold);
+    }
+    content.to_string()
+}
+
+// number of characters a line takes on the screen, leaving out escape codes
+pub fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            // escape codes end with a letter, like \x1B[31m
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        width += 1;
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_themes() {
+        for theme in Theme::ALL {
+            assert_eq!(theme.get_str().parse::<Theme>(), Ok(theme));
+            for mark in [Mark::Water, Mark::Ship, Mark::Hit, Mark::Miss, Mark::Sunk, Mark::Blocked, Mark::Stale] {
+                assert_eq!(visible_width(&theme.paint(mark)), 3);
+            }
+        }
+        // dumb terminals get neither escape codes nor anything outside ascii, and only the
+        // unicode theme draws symbols outside ascii
+        for mark in [Mark::Water, Mark::Ship, Mark::Hit, Mark::Miss, Mark::Sunk, Mark::Blocked, Mark::Stale] {
+            assert!(Theme::default().symbol(mark).is_ascii());
+            assert!(Theme::Ascii.paint(mark).is_ascii());
+            assert!(!Theme::Ascii.paint(mark).contains('\x1B'));
+        }
+        assert!("neon".parse::<Theme>().is_err());
+        assert_eq!(visible_width("\x1B[38;5;9m X \x1B[39m|"), 4);
+    }
+}
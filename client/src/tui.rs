@@ -0,0 +1,93 @@
+use std::io::stdout;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use app::App;
+use error::TuiError;
+use ratatui::crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, KeyEventKind, MouseEvent,
+};
+use ratatui::crossterm::execute;
+use ratatui::DefaultTerminal;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+pub mod app;
+pub mod error;
+
+// how often the screen is refreshed when nothing happens, keeps the turn clock going
+const TICK: Duration = Duration::from_millis(250);
+
+pub enum Input {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    Tick,
+}
+
+// full screen terminal, restored to normal when dropped
+pub struct Tui {
+    terminal: DefaultTerminal,
+    inputs: UnboundedReceiver<Input>,
+    stop: Arc<AtomicBool>,
+    mouse: bool,
+}
+impl Tui {
+    // without `mouse` the terminal keeps handling the mouse itself, e.g. to select text
+    pub fn start(mouse: bool) -> Result<Self, TuiError> {
+        let terminal = ratatui::try_init()?;
+        // Self isn't built yet, so nothing else puts the terminal back if this fails
+        if mouse {
+            if let Err(e) = execute!(stdout(), EnableMouseCapture) {
+                let _ = ratatui::try_restore();
+                return Err(e.into());
+            }
+        }
+        let (tx, inputs) = mpsc::unbounded_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+        // crossterm only reads blocking, so terminal events are forwarded from their own thread
+        thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                let input = match event::poll(TICK) {
+                    Ok(true) => match event::read() {
+                        Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => Input::Key(key),
+                        Ok(Event::Mouse(mouse)) => Input::Mouse(mouse),
+                        Ok(_) => Input::Tick,
+                        Err(_) => break,
+                    },
+                    Ok(false) => Input::Tick,
+                    Err(_) => break,
+                };
+                if tx.send(input).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            terminal,
+            inputs,
+            stop,
+            mouse,
+        })
+    }
+
+    pub fn draw(&mut self, app: &mut App) -> Result<(), TuiError> {
+        self.terminal.draw(|frame| app.draw(frame))?;
+        Ok(())
+    }
+
+    // next key press, mouse event or tick, None when the terminal can't be read anymore
+    pub async fn next_input(&mut self) -> Option<Input> {
+        self.inputs.recv().await
+    }
+}
+impl Drop for Tui {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if self.mouse {
+            let _ = execute!(stdout(), DisableMouseCapture);
+        }
+        let _ = ratatui::try_restore();
+    }
+}
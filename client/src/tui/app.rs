@@ -0,0 +1,1074 @@
+use std::time::Instant;
+
+use bslib::game::board::Board;
+use bslib::game::movement::Direction;
+use bslib::game::ruleset::Ruleset;
+use bslib::game::shape::Shape;
+use bslib::game::weapon::{Arsenal, Strike, Weapon};
+use bslib::game::{ShipType, ShotResult};
+use bslib::placement::{random_fleet, PlacementMode};
+use bslib::strategy::Cell;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+
+use crate::client_player::table::Table;
+use crate::client_player::client_board::{encode_indexes, COORDINATES_LETTERS};
+use crate::theme::{Mark, Theme};
+
+use super::Input;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    // index into the orientations of the next ship's shape
+    Placing { orientation: usize },
+    Waiting,
+    Firing,
+    // picking one of our ships to move instead of firing, then the direction to move it in
+    Moving { ship: Option<[usize; 2]> },
+    Over,
+}
+
+// what the game loop has to do after a key press
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    None,
+    Quit,
+    FleetPlaced,
+    Fire([usize; 2]),
+    Salvo(Vec<[usize; 2]>),
+    Weapon(Weapon, [usize; 2]),
+    Move([usize; 2], Direction),
+}
+
+// state of the full screen client - every board, the log and whose turn it is
+pub struct App {
+    // nicks by seat, ours included, and the opponent we fire at
+    table: Table,
+    ruleset: Ruleset,
+    placement: PlacementMode,
+    // generates the fleet layouts
+    rng: StdRng,
+    own: Board,
+    cursor: [usize; 2],
+    // shots in this turn's salvo and the tiles aimed at so far, 0 in games without salvos
+    salvo: usize,
+    aimed: Vec<[usize; 2]>,
+    // special weapons we have left and the one the next turn uses instead of a shot
+    arsenal: Arsenal,
+    weapon: Option<Weapon>,
+    phase: Phase,
+    started: bool,
+    status: String,
+    won: Option<bool>,
+    log: Vec<String>,
+    turn_started: Instant,
+    mouse: bool,
+    theme: Theme,
+    // where the boards were drawn last time, ours and then the opponents' ones, to find the
+    // tile under the mouse
+    board_areas: Vec<Rect>,
+    drag: Option<[usize; 2]>,
+}
+impl App {
+    // `auto_place` starts from a generated layout instead of an empty board
+    pub fn new(players: Vec<String>, ruleset: &Ruleset, auto_place: Option<PlacementMode>) -> Self {
+        let mut app = Self {
+            table: Table::new(ruleset, players),
+            ruleset: ruleset.clone(),
+            placement: auto_place.unwrap_or(PlacementMode::Uniform),
+            rng: StdRng::from_entropy(),
+            own: Board::from_ruleset(ruleset),
+            cursor: [0, 0],
+            salvo: 0,
+            aimed: Vec::new(),
+            arsenal: Arsenal::default(),
+            weapon: None,
+            phase: Phase::Placing { orientation: 0 },
+            started: false,
+            status: String::new(),
+            won: None,
+            log: Vec::new(),
+            turn_started: Instant::now(),
+            mouse: false,
+            theme: Theme::default(),
+            board_areas: Vec::new(),
+            drag: None,
+        };
+        if auto_place.is_some() {
+            app.generate();
+        }
+        app
+    }
+
+    // our seat at the table, the first one unless the server says otherwise
+    pub fn with_seat(mut self, seat: usize) -> Self {
+        self.table = self.table.with_seat(seat);
+        self
+    }
+
+    // generated layouts come from the seed, the one made up front included
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        if !self.own.get_ships().is_empty() {
+            self.generate();
+        }
+        self
+    }
+
+    // mention mouse controls in the help line
+    pub fn with_mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    pub fn get_own_board(&self) -> &Board {
+        &self.own
+    }
+
+    // seat of the opponent the next shot goes to
+    pub fn get_opponent(&self) -> usize {
+        self.table.get_opponent()
+    }
+
+    // whether we won, None until the game is over
+    pub fn get_outcome(&self) -> Option<bool> {
+        self.won
+    }
+
+    pub fn log(&mut self, msg: String) {
+        self.log.push(msg);
+    }
+
+    pub fn handle_input(&mut self, input: Input) -> Action {
+        match input {
+            Input::Key(key) => self.handle_key(key),
+            Input::Mouse(mouse) => self.handle_mouse(mouse),
+            Input::Tick => Action::None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+            return Action::Quit;
+        }
+        if let (Phase::Moving { ship: Some(tile) }, Some(direction)) = (self.phase, arrow(key.code)) {
+            self.phase = Phase::Waiting;
+            self.status = format!("Moving the ship at {} {}...", encode_indexes(tile), direction);
+            return Action::Move(tile, direction);
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor([-1, 0]),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor([1, 0]),
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor([0, -1]),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor([0, 1]),
+            code => {
+                return match self.phase {
+                    Phase::Placing { .. } => self.placing_key(code),
+                    Phase::Firing => self.firing_key(code),
+                    Phase::Moving { .. } => self.moving_key(code),
+                    Phase::Waiting | Phase::Over => Action::None,
+                }
+            }
+        }
+        Action::None
+    }
+
+    fn placing_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Char('r') => self.reorient(Shape::rotate),
+            KeyCode::Char('m') => self.reorient(Shape::mirror),
+            KeyCode::Char('g') => self.generate(),
+            KeyCode::Backspace | KeyCode::Char('u') => self.undo(),
+            KeyCode::Enter | KeyCode::Char(' ') => return self.place_next(),
+            _ => (),
+        }
+        Action::None
+    }
+
+    fn firing_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Char('f') => self.fire(),
+            KeyCode::Char('w') => {
+                self.next_weapon();
+                Action::None
+            }
+            KeyCode::Tab => {
+                self.next_opponent();
+                Action::None
+            }
+            KeyCode::Char('v') if self.ruleset.is_moving() => {
+                self.weapon = None;
+                self.phase = Phase::Moving { ship: None };
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn moving_key(&mut self, code: KeyCode) -> Action {
+        match code {
+            KeyCode::Enter | KeyCode::Char(' ') => match self.own.get_tile(self.cursor).has_ship() {
+                true => self.phase = Phase::Moving { ship: Some(self.cursor) },
+                false => self.log(format!("There is no ship at {}", encode_indexes(self.cursor))),
+            },
+            KeyCode::Char('v') => self.phase = Phase::Firing,
+            _ => (),
+        }
+        Action::None
+    }
+
+    // click to fire, drag to place a ship, right click to rotate it
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Action {
+        let size = self.ruleset.get_size();
+        let own = self.board_areas.first().and_then(|&area| tile_at(area, size, mouse.column, mouse.row));
+        // seat of the opponent whose board is under the mouse, and the tile on it
+        let target = self
+            .table
+            .rivals()
+            .into_iter()
+            .zip(self.board_areas.iter().skip(1))
+            .find_map(|(seat, &area)| tile_at(area, size, mouse.column, mouse.row).map(|tile| (seat, tile)));
+        match (self.phase, mouse.kind) {
+            (Phase::Placing { .. }, MouseEventKind::Down(MouseButton::Right)) => {
+                return self.placing_key(KeyCode::Char('r'))
+            }
+            (Phase::Placing { .. }, MouseEventKind::Moved) => self.point_at(own),
+            (Phase::Placing { .. }, MouseEventKind::Down(MouseButton::Left)) => {
+                self.drag = own;
+                self.point_at(own);
+            }
+            (Phase::Placing { .. }, MouseEventKind::Drag(MouseButton::Left)) => {
+                if let (Some(start), Some(tile)) = (self.drag, own) {
+                    self.drag_ship(start, tile);
+                }
+            }
+            (Phase::Placing { .. }, MouseEventKind::Up(MouseButton::Left)) => {
+                let dragging = self.drag.take().is_some();
+                if dragging && self.next_ship().is_some() {
+                    return self.place_next();
+                }
+            }
+            (Phase::Moving { ship: None }, MouseEventKind::Moved) => self.point_at(own),
+            (Phase::Moving { ship: None }, MouseEventKind::Down(MouseButton::Left)) if own.is_some() => {
+                self.point_at(own);
+                return self.moving_key(KeyCode::Enter);
+            }
+            (Phase::Firing, MouseEventKind::Moved) => {
+                let tile = target.filter(|&(seat, _)| seat == self.table.get_opponent()).map(|(_, tile)| tile);
+                self.point_at(tile);
+            }
+            // a click on another opponent's board picks them, the next one fires
+            (Phase::Firing, MouseEventKind::Down(MouseButton::Left)) => match target {
+                Some((seat, tile)) if seat == self.table.get_opponent() => {
+                    self.point_at(Some(tile));
+                    return self.fire();
+                }
+                Some((seat, tile)) => {
+                    self.select_opponent(seat);
+                    self.point_at(Some(tile));
+                }
+                None => (),
+            },
+            _ => (),
+        }
+        Action::None
+    }
+
+    fn point_at(&mut self, tile: Option<[usize; 2]>) {
+        if let Some(tile) = tile {
+            self.cursor = tile;
+            self.fit_ship();
+        }
+    }
+
+    // a straight ship starts where the drag started and points where the mouse is, dragging up
+    // or left puts its end at the start instead - other shapes just follow the mouse
+    fn drag_ship(&mut self, start: [usize; 2], tile: [usize; 2]) {
+        let Some(ship_type) = self.next_ship() else {
+            return;
+        };
+        if tile == start {
+            return;
+        }
+        if !ship_type.get_shape().is_line() {
+            self.point_at(Some(tile));
+            return;
+        }
+        let horizontal = tile[1].abs_diff(start[1]) >= tile[0].abs_diff(start[0]);
+        let back = ship_type.get_size() as usize - 1;
+        self.cursor = match horizontal {
+            true if tile[1] < start[1] => [start[0], start[1].saturating_sub(back)],
+            false if tile[0] < start[0] => [start[0].saturating_sub(back), start[1]],
+            _ => start,
+        };
+        // a straight ship lies along the row in its first orientation
+        let orientation = if horizontal { 0 } else { 1 };
+        self.phase = Phase::Placing { orientation };
+        self.fit_ship();
+    }
+
+    // turn or flip the ship being placed, `change` takes its orientation to the new one
+    fn reorient(&mut self, change: fn(&Shape) -> Shape) {
+        let Some((ship_type, shape)) = self.next_orientation() else {
+            return;
+        };
+        let next = change(&shape);
+        let orientations = ship_type.get_shape().orientations();
+        let orientation = orientations.iter().position(|other| *other == next).unwrap_or(0);
+        self.phase = Phase::Placing { orientation };
+        self.fit_ship();
+    }
+
+    // put the next ship where the preview is, or hand over the fleet once all ships are placed
+    fn place_next(&mut self) -> Action {
+        let Some((ship_type, tiles)) = self.preview() else {
+            self.phase = Phase::Waiting;
+            self.status = String::from("Waiting for the opponent to place their ships...");
+            return Action::FleetPlaced;
+        };
+        match self.own.place_ship(ship_type, tiles) {
+            Ok(()) => self.fit_ship(),
+            Err(e) => self.log(format!("{}", e)),
+        }
+        Action::None
+    }
+
+    fn fire(&mut self) -> Action {
+        if let Some(weapon) = self.weapon.take() {
+            self.phase = Phase::Waiting;
+            self.status = format!("Using the {} at {}...", weapon, encode_indexes(self.cursor));
+            return Action::Weapon(weapon, self.cursor);
+        }
+        if self.table.get_target(self.table.get_opponent()).is_shot(self.cursor) {
+            self.log(format!("You have already fired at {}", encode_indexes(self.cursor)));
+            return Action::None;
+        }
+        if self.salvo > 0 {
+            return self.aim();
+        }
+        self.phase = Phase::Waiting;
+        self.status = format!("Firing at {}...", encode_indexes(self.cursor));
+        Action::Fire(self.cursor)
+    }
+
+    // aiming at a tile again takes it out of the salvo, which is fired once it's complete
+    fn aim(&mut self) -> Action {
+        match self.aimed.iter().position(|&tile| tile == self.cursor) {
+            Some(index) => {
+                self.aimed.remove(index);
+            }
+            None => self.aimed.push(self.cursor),
+        }
+        if self.aimed.len() < self.salvo {
+            return Action::None;
+        }
+        let targets = std::mem::take(&mut self.aimed);
+        let tiles = targets.iter().map(|&tile| encode_indexes(tile)).collect::<Vec<_>>();
+        self.phase = Phase::Waiting;
+        self.status = format!("Firing a salvo at {}...", tiles.join(" "));
+        Action::Salvo(targets)
+    }
+
+    // pick the next weapon that has uses left, going back to plain shots after the last one
+    fn next_weapon(&mut self) {
+        let start = self.weapon.map_or(0, |weapon| weapon as usize + 1);
+        self.weapon = Weapon::ALL[start.min(Weapon::ALL.len())..]
+            .iter()
+            .copied()
+            .find(|&weapon| self.arsenal.get_uses(weapon) > 0);
+    }
+
+    // a salvo is aimed at a single opponent, so picking another one starts it over
+    fn next_opponent(&mut self) {
+        if self.table.next_opponent() {
+            self.aimed.clear();
+        }
+    }
+
+    fn select_opponent(&mut self, seat: usize) {
+        if self.table.select_opponent(seat) {
+            self.aimed.clear();
+        }
+    }
+
+    fn move_on(&mut self) {
+        if self.table.move_on() {
+            self.aimed.clear();
+        }
+    }
+
+    fn move_cursor(&mut self, [di, dj]: [isize; 2]) {
+        let [i, j] = self.cursor;
+        let [height, width] = self.ruleset.get_size();
+        self.cursor = [
+            (i as isize + di).clamp(0, height as isize - 1) as usize,
+            (j as isize + dj).clamp(0, width as isize - 1) as usize,
+        ];
+        self.fit_ship();
+    }
+
+    // keep the ship being placed inside the board
+    fn fit_ship(&mut self) {
+        let Some((_, shape)) = self.next_orientation() else {
+            return;
+        };
+        let [rows, cols] = shape.get_dimensions();
+        let [height, width] = self.ruleset.get_size();
+        let [i, j] = self.cursor;
+        self.cursor = [i.min(height.saturating_sub(rows)), j.min(width.saturating_sub(cols))];
+    }
+
+    // first ship of the fleet that isn't on the board yet
+    fn next_ship(&self) -> Option<ShipType> {
+        let mut placed = self.own.get_ships().iter().map(|ship| ship.get_type()).collect::<Vec<_>>();
+        self.ruleset.get_fleet().iter().copied().find(|ship_type| {
+            match placed.iter().position(|placed| placed == ship_type) {
+                Some(index) => {
+                    placed.remove(index);
+                    false
+                }
+                None => true,
+            }
+        })
+    }
+
+    // next ship to place, turned the way the player has chosen
+    fn next_orientation(&self) -> Option<(ShipType, Shape)> {
+        let Phase::Placing { orientation } = self.phase else {
+            return None;
+        };
+        let ship_type = self.next_ship()?;
+        let orientations = ship_type.get_shape().orientations();
+        let shape = orientations[orientation % orientations.len()].clone();
+        Some((ship_type, shape))
+    }
+
+    // tiles the next ship would take with the corner of its box at the cursor
+    fn preview(&self) -> Option<(ShipType, Vec<[usize; 2]>)> {
+        let (ship_type, shape) = self.next_orientation()?;
+        Some((ship_type, shape.at(self.cursor).collect()))
+    }
+
+    // the ships placed so far stay where they are when no layout could be generated
+    fn generate(&mut self) {
+        match random_fleet(&self.ruleset, self.placement, &mut self.rng) {
+            Ok(own) => self.own = own,
+            Err(e) => return self.log(format!("{}", e)),
+        }
+        self.phase = Phase::Placing { orientation: 0 };
+    }
+
+    // take the last placed ship off the board
+    fn undo(&mut self) {
+        let mut ships = self.own.get_ships().to_vec();
+        if ships.pop().is_none() {
+            return;
+        }
+        self.own = Board::from_ruleset(&self.ruleset);
+        for ship in ships {
+            let _ = self.own.place_ship(ship.get_type(), ship.get_tiles().to_vec());
+        }
+        self.fit_ship();
+    }
+
+    // every fleet is placed and the game begins - with more opponents we only know who fires
+    // first when it's us
+    pub fn start(&mut self, your_turn: bool) {
+        self.started = true;
+        let (players, seat) = (self.table.get_players(), self.table.get_seat());
+        match (your_turn, players.len()) {
+            (true, _) => self.log(format!("{} fires first", players[seat])),
+            (false, 2) => self.log(format!("{} fires first", players[1 - seat])),
+            (false, _) => (),
+        }
+        self.set_turn(your_turn);
+    }
+
+    // shots in our next salvo, the server tells us after every salvo
+    pub fn set_salvo(&mut self, salvo: usize) {
+        self.salvo = salvo;
+    }
+
+    // uses of the special weapons we have left, the server tells us after every use
+    pub fn set_arsenal(&mut self, arsenal: Arsenal) {
+        self.arsenal = arsenal;
+        self.weapon = self.weapon.filter(|&weapon| arsenal.get_uses(weapon) > 0);
+    }
+
+    pub fn set_turn(&mut self, your_turn: bool) {
+        if self.phase == Phase::Over {
+            return;
+        }
+        self.aimed.clear();
+        let phase = if your_turn { Phase::Firing } else { Phase::Waiting };
+        if phase == Phase::Firing || self.phase != Phase::Waiting {
+            self.turn_started = Instant::now();
+        }
+        self.phase = phase;
+        self.status = match (self.own.is_fleet_destroyed(), self.table.get_players().len()) {
+            (true, _) => String::from("Your fleet is sunk - watching the rest of the game"),
+            (false, 2) => format!("{}'s turn", self.table.get_players()[1 - self.table.get_seat()]),
+            (false, _) => String::from("Other players' turn"),
+        };
+    }
+
+    // result of a shot by any player at any other - our fleet takes the shots at us, the
+    // others fill in what we know about the fleet that was fired at
+    pub fn shot(&mut self, player: usize, opponent: usize, target: [usize; 2], result: ShotResult) {
+        if opponent == self.table.get_seat() {
+            let _ = self.own.fire(target);
+        }
+        self.table.record_shot(opponent, target, result);
+        let shooter = match player == self.table.get_seat() {
+            true => String::from("you fire"),
+            false => format!("{} fires", self.table.get_players()[player]),
+        };
+        self.log(format!("{} at {} - {}", shooter, self.table.describe_target(opponent, target), result));
+        self.move_on();
+    }
+
+    // what a special weapon used by any player did
+    pub fn weapon_result(&mut self, player: usize, opponent: usize, weapon: Weapon, target: [usize; 2], strike: &Strike) {
+        let user = match player == self.table.get_seat() {
+            true => String::from("you use"),
+            false => format!("{} uses", self.table.get_players()[player]),
+        };
+        self.log(format!("{} the {} at {}", user, weapon, self.table.describe_target(opponent, target)));
+        for &(tile, result) in strike.get_shots() {
+            self.shot(player, opponent, tile, result);
+        }
+        if let Some(detected) = strike.get_detected() {
+            let found = if detected { "found a ship" } else { "found nothing" };
+            self.log(format!("the radar {}", found));
+            self.table.record_scan(opponent, &weapon.area(target, self.ruleset.get_size()), detected);
+        }
+    }
+
+    // a ship was moved - ours by the tile and direction, anyone else's somewhere we can't see
+    pub fn move_result(&mut self, player: usize, moved: Option<([usize; 2], Direction)>) {
+        match (player == self.table.get_seat(), moved) {
+            (true, Some((tile, direction))) => {
+                let _ = self.own.move_ship(tile, direction);
+                self.log(format!("you move the ship at {} {}", encode_indexes(tile), direction));
+            }
+            _ => {
+                self.table.record_move(player);
+                self.log(format!("{} moves a ship", self.table.get_players()[player]));
+            }
+        }
+    }
+
+    // the game goes on without a player that left, we pick another opponent if they were ours
+    pub fn player_left(&mut self, player: usize) {
+        self.table.leave(player);
+        self.log(format!("{} has left the game", self.table.get_players()[player]));
+        self.move_on();
+    }
+
+    // the server didn't accept our fleet or shot, let the player try again
+    pub fn reject(&mut self, msg: &str) {
+        self.log(format!("{} - trying again", msg));
+        if self.started {
+            self.set_turn(true);
+        } else {
+            self.phase = Phase::Placing { orientation: 0 };
+        }
+    }
+
+    pub fn game_over(&mut self, won: bool, reason: &str) {
+        self.phase = Phase::Over;
+        self.won = Some(won);
+        let outcome = if won { "You won!" } else { "You lost!" };
+        self.log(format!("{} - {}", outcome, reason));
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame) {
+        let [height, width] = self.ruleset.get_size();
+        // column numbers plus one line per row, inside a border
+        let board_height = height as u16 + 3;
+        // row letters plus three characters per tile, inside a border
+        let board_width = 3 * width as u16 + 4;
+        // our board first, then the opponents' ones, wrapping onto more rows when they don't fit
+        let rivals = self.table.rivals();
+        let per_row = (frame.area().width / board_width).max(1);
+        let rows = (rivals.len() as u16 + 1).div_ceil(per_row);
+        let [boards, log, status, help] = Layout::vertical([
+            Constraint::Length(board_height * rows),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.board_areas = (0..=rivals.len() as u16)
+            .map(|index| {
+                let [row, column] = [index / per_row, index % per_row];
+                let [x, y] = [boards.x + column * board_width, boards.y + row * board_height];
+                Rect::new(x, y, board_width, board_height).intersection(boards)
+            })
+            .collect();
+
+        let own_active = matches!(self.phase, Phase::Placing { .. } | Phase::Moving { .. });
+        frame.render_widget(self.own_board(own_active), self.board_areas[0]);
+        for (&seat, &area) in rivals.iter().zip(&self.board_areas[1..]) {
+            let active = self.phase == Phase::Firing && seat == self.table.get_opponent();
+            frame.render_widget(self.target_board(seat, active), area);
+        }
+
+        let lines = self.log[self.log.len().saturating_sub(log.height.saturating_sub(2) as usize)..]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect::<Vec<_>>();
+        let block = Block::bordered().border_set(self.theme.border()).title(" Log ");
+        frame.render_widget(Paragraph::new(lines).block(block), log);
+
+        // us and our teammates against everyone else
+        let (players, ours) = (self.table.get_players(), self.table.get_seat());
+        let team = self.ruleset.get_team(ours);
+        let teammates = (0..players.len()).filter(|&seat| seat != ours && self.ruleset.get_team(seat) == team);
+        let ours = std::iter::once(ours).chain(teammates).map(|seat| players[seat].as_str());
+        let theirs = rivals.iter().map(|&seat| players[seat].as_str());
+        let secs = self.turn_started.elapsed().as_secs();
+        let status_line = format!(
+            " {} vs {} | {} | {}:{:02}",
+            ours.collect::<Vec<_>>().join(" & "),
+            theirs.collect::<Vec<_>>().join(", "),
+            self.describe_phase(),
+            secs / 60,
+            secs % 60
+        );
+        frame.render_widget(
+            Paragraph::new(status_line).style(Style::new().add_modifier(Modifier::REVERSED)),
+            status,
+        );
+        frame.render_widget(
+            Paragraph::new(self.help()).style(Style::new().fg(Color::DarkGray)),
+            help,
+        );
+    }
+
+    fn describe_phase(&self) -> String {
+        match self.phase {
+            Phase::Placing { .. } => match self.next_ship() {
+                Some(ship_type) => format!("Place your {} ({} tiles)", ship_type, ship_type.get_size()),
+                None => String::from("Fleet ready"),
+            },
+            Phase::Waiting => self.status.clone(),
+            Phase::Firing if self.salvo > 0 => {
+                format!("Your salvo - {} of {} tiles aimed at", self.aimed.len(), self.salvo)
+            }
+            Phase::Moving { ship: None } => String::from("Pick a ship to move"),
+            Phase::Moving { ship: Some(tile) } => format!("Move the ship at {}", encode_indexes(tile)),
+            Phase::Firing => match self.weapon {
+                Some(weapon) => format!("Your turn - {} ({} left)", weapon, self.arsenal.get_uses(weapon)),
+                None => String::from("Your turn"),
+            },
+            Phase::Over => match self.won {
+                Some(true) => String::from("You won!"),
+                _ => String::from("You lost!"),
+            },
+        }
+    }
+
+    fn help(&self) -> String {
+        let mut help = String::from(self.base_help());
+        if self.phase == Phase::Firing && !self.arsenal.is_empty() {
+            help = help.replace("  [q] quit", "  [w] weapon  [q] quit");
+        }
+        if self.phase == Phase::Firing && self.ruleset.is_moving() {
+            help = help.replace("  [q] quit", "  [v] move a ship  [q] quit");
+        }
+        if self.phase == Phase::Firing && self.table.rivals_afloat().len() > 1 {
+            help = help.replace("  [q] quit", "  [tab] next opponent  [q] quit");
+        }
+        help
+    }
+
+    fn base_help(&self) -> &'static str {
+        let shaped = self.next_ship().is_some_and(|ship_type| !ship_type.get_shape().is_line());
+        match (self.phase, self.mouse) {
+            (Phase::Placing { .. }, _) if self.next_ship().is_none() => {
+                " [enter] confirm  [backspace] undo  [g] generate  [q] quit"
+            }
+            (Phase::Placing { .. }, false) if shaped => {
+                " [arrows/hjkl] move  [r] rotate  [m] mirror  [enter] place  \
+                 [backspace] undo  [g] generate  [q] quit"
+            }
+            (Phase::Placing { .. }, true) if shaped => {
+                " [arrows/drag] move  [r/right click] rotate  [m] mirror  \
+                 [enter/release] place  [backspace] undo  [g] generate  [q] quit"
+            }
+            (Phase::Placing { .. }, false) => {
+                " [arrows/hjkl] move  [r] rotate  [enter] place  \
+                 [backspace] undo  [g] generate  [q] quit"
+            }
+            (Phase::Placing { .. }, true) => {
+                " [arrows/drag] move  [r/right click] rotate  \
+                 [enter/release] place  [backspace] undo  [g] generate  [q] quit"
+            }
+            (Phase::Firing, false) if self.salvo > 0 => {
+                " [arrows/hjkl] move  [enter] aim/unaim, fires when the salvo is complete  [q] quit"
+            }
+            (Phase::Firing, true) if self.salvo > 0 => {
+                " [arrows/hjkl] move  [enter/click] aim/unaim, \
+                 fires when the salvo is complete  [q] quit"
+            }
+            (Phase::Moving { ship: None }, false) => " [arrows/hjkl] move  [enter] pick the ship  [v] cancel  [q] quit",
+            (Phase::Moving { ship: None }, true) => {
+                " [arrows/hjkl] move  [enter/click] pick the ship  [v] cancel  [q] quit"
+            }
+            (Phase::Moving { ship: Some(_) }, _) => " [arrows/hjkl] move the ship by a tile  [v] cancel  [q] quit",
+            (Phase::Firing, false) => " [arrows/hjkl] aim  [enter] fire  [q] quit",
+            (Phase::Firing, true) => " [arrows/hjkl] aim  [enter/click] fire  [q] quit",
+            (Phase::Waiting | Phase::Over, _) => " [q] quit",
+        }
+    }
+
+    fn own_board(&self, active: bool) -> Paragraph<'static> {
+        let preview = self.preview();
+        // the ship picked to be moved
+        let picked = match self.phase {
+            Phase::Moving { ship: Some(tile) } => {
+                self.own.get_ships().iter().find(|ship| ship.get_tiles().contains(&tile))
+            }
+            _ => None,
+        };
+        let lines = board_lines(self.ruleset.get_size(), |tile| {
+            if let Some((ship_type, tiles)) = &preview {
+                if tiles.contains(&tile) {
+                    return self.theme.preview(self.own.check_placement(*ship_type, tiles).is_ok());
+                }
+            }
+            if picked.is_some_and(|ship| ship.get_tiles().contains(&tile)) {
+                return self.theme.preview(true);
+            }
+            if self.phase == (Phase::Moving { ship: None }) && tile == self.cursor {
+                let mark = if self.own.get_tile(tile).has_ship() { Mark::Ship } else { Mark::Water };
+                return (self.theme.symbol(mark), self.theme.style(mark).add_modifier(Modifier::REVERSED));
+            }
+            let state = self.own.get_tile(tile);
+            let mark = match (state.is_shot(), state.has_ship()) {
+                (true, false) => Mark::Miss,
+                (true, true) => Mark::Hit,
+                (false, false) => Mark::Water,
+                (false, true) => Mark::Ship,
+            };
+            (self.theme.symbol(mark), self.theme.style(mark))
+        });
+        let nick = &self.table.get_players()[self.table.get_seat()];
+        Paragraph::new(lines).block(self.board_block(nick, active))
+    }
+
+    fn target_board(&self, seat: usize, active: bool) -> Paragraph<'static> {
+        let target = self.table.get_target(seat);
+        let size = self.ruleset.get_size();
+        let area = match (active, self.weapon) {
+            (true, Some(weapon)) => weapon.area(self.cursor, size),
+            _ => Vec::new(),
+        };
+        let lines = board_lines(size, |tile| {
+            let mark = match target.get_tile(tile) {
+                Cell::Unknown if target.is_stale(tile) => Mark::Stale,
+                Cell::Unknown => Mark::Water,
+                Cell::Miss => Mark::Miss,
+                Cell::Hit => Mark::Hit,
+                Cell::Sunk => Mark::Sunk,
+                Cell::Blocked => Mark::Blocked,
+            };
+            let aimed = seat == self.table.get_opponent() && self.aimed.contains(&tile);
+            let (symbol, style) = match aimed || area.contains(&tile) {
+                true => self.theme.preview(true),
+                false => (self.theme.symbol(mark), self.theme.style(mark)),
+            };
+            match active && tile == self.cursor {
+                true => (symbol, style.add_modifier(Modifier::REVERSED)),
+                false => (symbol, style),
+            }
+        });
+        let left = format!(" {} ships left ", target.get_remaining().len());
+        let nick = &self.table.get_players()[seat];
+        Paragraph::new(lines).block(self.board_block(nick, active).title_bottom(left))
+    }
+
+    fn board_block(&self, title: &str, active: bool) -> Block<'static> {
+        let block = Block::bordered()
+            .border_set(self.theme.border())
+            .title(format!(" {} ", title));
+        match active {
+            true => block.border_style(self.theme.active_border()),
+            false => block,
+        }
+    }
+}
+
+// tile of a board of the given size drawn in the area at given terminal position
+fn tile_at(area: Rect, [height, width]: [usize; 2], column: u16, row: u16) -> Option<[usize; 2]> {
+    // skip the border and the row letters on the left, the border and column numbers on top
+    let x = column.checked_sub(area.x + 3)? as usize;
+    let y = row.checked_sub(area.y + 2)? as usize;
+    let tile = [y, x / 3];
+    (tile[0] < height && tile[1] < width).then_some(tile)
+}
+
+// direction of an arrow key, or of its vim counterpart
+fn arrow(code: KeyCode) -> Option<Direction> {
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => Some(Direction::Up),
+        KeyCode::Down | KeyCode::Char('j') => Some(Direction::Down),
+        KeyCode::Left | KeyCode::Char('h') => Some(Direction::Left),
+        KeyCode::Right | KeyCode::Char('l') => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+// column numbers, then a row letter followed by a symbol for every tile
+fn board_lines(
+    [height, width]: [usize; 2],
+    tile: impl Fn([usize; 2]) -> (&'static str, Style),
+) -> Vec<Line<'static>> {
+    let mut header = String::from("  ");
+    for j in 1..=width {
+        header += format!("{:^3}", j).as_str();
+    }
+    let mut lines = vec![Line::from(header)];
+    for (i, letter) in COORDINATES_LETTERS.chars().take(height).enumerate() {
+        let mut spans = vec![Span::raw(format!("{} ", letter))];
+        for j in 0..width {
+            let (symbol, style) = tile([i, j]);
+            spans.push(Span::styled(symbol, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn press(app: &mut App, codes: &[KeyCode]) -> Action {
+        let mut action = Action::None;
+        for &code in codes {
+            action = app.handle_key(KeyEvent::from(code));
+        }
+        action
+    }
+
+    #[test]
+    fn test_placing_and_firing_with_keys() {
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &Ruleset::classic(), None);
+        let down = [KeyCode::Down, KeyCode::Down];
+        // carrier across the top row, then every other row with the submarine rotated
+        press(&mut app, &[KeyCode::Enter]);
+        for _ in 0..2 {
+            press(&mut app, &down);
+            press(&mut app, &[KeyCode::Enter]);
+        }
+        press(&mut app, &down);
+        press(&mut app, &[KeyCode::Char('r'), KeyCode::Enter]);
+        assert_eq!(app.get_own_board().get_ships().len(), 4);
+        assert_eq!(app.get_own_board().get_ships()[3].get_tiles(), [[6, 0], [7, 0], [8, 0]]);
+
+        press(&mut app, &[KeyCode::Char('u'), KeyCode::Char('r'), KeyCode::Enter]);
+        press(&mut app, &down);
+        press(&mut app, &[KeyCode::Right, KeyCode::Right, KeyCode::Enter]);
+        assert_eq!(app.get_own_board().get_ships().len(), 5);
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+
+        app.start(true);
+        assert_eq!(press(&mut app, &[KeyCode::Char('f')]), Action::Fire([8, 2]));
+        app.shot(0, 1, [8, 2], ShotResult::Miss);
+        app.set_turn(true);
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::None);
+        assert_eq!(press(&mut app, &[KeyCode::Up, KeyCode::Enter]), Action::Fire([7, 2]));
+    }
+
+    fn click(app: &mut App, kind: MouseEventKind, board: usize, [i, j]: [usize; 2]) -> Action {
+        let area = app.board_areas[board];
+        app.handle_mouse(MouseEvent {
+            kind,
+            column: area.x + 4 + 3 * j as u16,
+            row: area.y + 2 + i as u16,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    #[test]
+    fn test_mouse() {
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &Ruleset::classic(), None);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+
+        // dragging left ends the carrier where the drag started
+        click(&mut app, MouseEventKind::Down(MouseButton::Left), 0, [2, 6]);
+        click(&mut app, MouseEventKind::Drag(MouseButton::Left), 0, [2, 3]);
+        click(&mut app, MouseEventKind::Up(MouseButton::Left), 0, [2, 3]);
+        // right click rotates, a click without dragging places at the tile
+        click(&mut app, MouseEventKind::Down(MouseButton::Right), 0, [0, 0]);
+        click(&mut app, MouseEventKind::Down(MouseButton::Left), 0, [4, 0]);
+        click(&mut app, MouseEventKind::Up(MouseButton::Left), 0, [4, 0]);
+        let ships = app.get_own_board().get_ships();
+        assert_eq!(ships[0].get_tiles(), [[2, 2], [2, 3], [2, 4], [2, 5], [2, 6]]);
+        assert_eq!(ships[1].get_tiles(), [[4, 0], [5, 0], [6, 0], [7, 0]]);
+
+        press(&mut app, &[KeyCode::Char('g')]);
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+        app.start(true);
+        assert_eq!(click(&mut app, MouseEventKind::Down(MouseButton::Left), 0, [3, 4]), Action::None);
+        assert_eq!(
+            click(&mut app, MouseEventKind::Down(MouseButton::Left), 1, [3, 4]),
+            Action::Fire([3, 4])
+        );
+    }
+
+    #[test]
+    fn test_rotating_and_mirroring_shaped_ships() {
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &Ruleset::house(), None);
+        let down = [KeyCode::Down, KeyCode::Down];
+        press(&mut app, &[KeyCode::Enter]);
+        press(&mut app, &down);
+        press(&mut app, &[KeyCode::Enter]);
+        let ships = app.get_own_board().get_ships();
+        assert_eq!(ships[1].get_tiles(), [[2, 1], [3, 0], [3, 1], [3, 2], [4, 1]]);
+
+        // the L turned on its side with its foot flipped to the other end, then pushed
+        // against the right edge
+        press(&mut app, &down);
+        press(&mut app, &down);
+        press(&mut app, &[KeyCode::Char('r'), KeyCode::Char('m')]);
+        assert_eq!(app.preview().map(|(_, tiles)| tiles), Some(vec![[6, 0], [6, 1], [6, 2], [7, 2]]));
+        press(&mut app, &[KeyCode::Right; 9]);
+        press(&mut app, &[KeyCode::Enter]);
+        assert_eq!(app.get_own_board().get_ships()[2].get_tiles(), [[6, 7], [6, 8], [6, 9], [7, 9]]);
+    }
+
+    #[test]
+    fn test_aiming_a_salvo() {
+        let ruleset = Ruleset::classic().with_salvo(true);
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &ruleset, Some(PlacementMode::Uniform));
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+        app.set_salvo(3);
+        app.start(true);
+        // aiming twice at the same tile takes it back out of the salvo
+        assert_eq!(press(&mut app, &[KeyCode::Enter, KeyCode::Right, KeyCode::Enter]), Action::None);
+        assert_eq!(press(&mut app, &[KeyCode::Enter, KeyCode::Down, KeyCode::Enter]), Action::None);
+        assert_eq!(
+            press(&mut app, &[KeyCode::Right, KeyCode::Enter]),
+            Action::Salvo(vec![[0, 0], [1, 1], [1, 2]])
+        );
+        // a rejected salvo is aimed again from scratch
+        app.reject("Salvo has to have exactly 3 shots");
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::None);
+        assert_eq!(app.aimed, [[1, 2]]);
+    }
+
+    #[test]
+    fn test_using_weapons() {
+        let ruleset = Ruleset::classic().with_weapons(true);
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &ruleset, Some(PlacementMode::Uniform));
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+        let mut arsenal = Arsenal::full();
+        arsenal.take(Weapon::Bombardment).expect("full arsenal has a bombardment");
+        app.set_arsenal(arsenal);
+        app.start(true);
+        // the used up bombardment is skipped, and after the radar it's back to plain shots
+        press(&mut app, &[KeyCode::Char('w')]);
+        assert_eq!(app.weapon, Some(Weapon::Torpedo));
+        press(&mut app, &[KeyCode::Char('w'), KeyCode::Char('w')]);
+        assert_eq!(app.weapon, None);
+        assert_eq!(
+            press(&mut app, &[KeyCode::Char('w'), KeyCode::Char('w'), KeyCode::Down, KeyCode::Enter]),
+            Action::Weapon(Weapon::Radar, [1, 0])
+        );
+
+        // nothing found, so every unknown tile the radar scanned is ruled out
+        app.weapon_result(0, 1, Weapon::Radar, [1, 0], &Strike::scan(false));
+        assert_eq!(app.table.get_target(1).get_tile([2, 1]), Cell::Blocked);
+        assert_eq!(app.table.get_target(1).get_tile([3, 0]), Cell::Unknown);
+    }
+
+    #[test]
+    fn test_moving_a_ship() {
+        let ruleset = Ruleset::classic().with_moving(true);
+        let mut app = App::new(vec![String::from("alice"), String::from("bob")], &ruleset, Some(PlacementMode::Uniform));
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+        app.own = Board::new();
+        app.own
+            .place_ship(ShipType::Destroyer, vec![[1, 1], [1, 2]])
+            .expect("failed to place a ship");
+        app.start(true);
+
+        // there's nothing to pick on an empty tile, arrows move the cursor until a ship is picked
+        assert_eq!(press(&mut app, &[KeyCode::Char('v'), KeyCode::Enter]), Action::None);
+        assert_eq!(app.phase, Phase::Moving { ship: None });
+        press(&mut app, &[KeyCode::Down, KeyCode::Right, KeyCode::Enter]);
+        assert_eq!(app.phase, Phase::Moving { ship: Some([1, 1]) });
+        assert_eq!(press(&mut app, &[KeyCode::Left]), Action::Move([1, 1], Direction::Left));
+
+        app.move_result(0, Some(([1, 1], Direction::Left)));
+        assert_eq!(app.own.get_ships()[0].get_tiles(), [[1, 0], [1, 1]]);
+        app.move_result(1, None);
+        assert!(app.log.last().is_some_and(|line| line == "bob moves a ship"));
+    }
+
+    #[test]
+    fn test_picking_an_opponent() {
+        let ruleset = Ruleset::preset("classic+ffa3").expect("preset should exist");
+        let players = ["alice", "bob", "carol"].map(String::from).to_vec();
+        let mut app = App::new(players, &ruleset, Some(PlacementMode::Uniform)).with_seat(1);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        assert_eq!(press(&mut app, &[KeyCode::Enter]), Action::FleetPlaced);
+        app.start(true);
+        assert_eq!(app.get_opponent(), 0);
+
+        // tab goes around the table, a click on another board picks it before firing at it
+        press(&mut app, &[KeyCode::Tab]);
+        assert_eq!(app.get_opponent(), 2);
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        assert_eq!(app.board_areas.len(), 3);
+        assert_eq!(click(&mut app, MouseEventKind::Down(MouseButton::Left), 1, [3, 4]), Action::None);
+        assert_eq!(app.get_opponent(), 0);
+        assert_eq!(
+            click(&mut app, MouseEventKind::Down(MouseButton::Left), 1, [3, 4]),
+            Action::Fire([3, 4])
+        );
+
+        // shots between the others fill in their boards too
+        app.shot(2, 0, [5, 5], ShotResult::Hit);
+        assert_eq!(app.table.get_target(0).get_tile([5, 5]), Cell::Hit);
+        assert!(app.log.last().is_some_and(|line| line == "carol fires at alice's f6 - hit"));
+
+        // the game goes on without an opponent that leaves, the shots go to the one that stays
+        app.player_left(0);
+        assert_eq!(app.get_opponent(), 2);
+        press(&mut app, &[KeyCode::Tab]);
+        assert_eq!(app.get_opponent(), 2);
+    }
+
+    #[test]
+    fn test_seed_picks_the_layout() {
+        let layout = |seed| {
+            let players = vec![String::from("alice"), String::from("bob")];
+            let app = App::new(players, &Ruleset::classic(), Some(PlacementMode::Uniform)).with_seed(seed);
+            let ships = app.get_own_board().get_ships().iter();
+            ships.map(|ship| ship.get_tiles().to_vec()).collect::<Vec<_>>()
+        };
+        assert_eq!(layout(5), layout(5));
+        assert_ne!(layout(5), layout(6));
+    }
+}
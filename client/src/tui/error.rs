@@ -0,0 +1,19 @@
+use core::fmt::Display;
+use std::io;
+
+#[derive(Debug, Clone)]
+pub struct TuiError {
+    msg: String,
+}
+impl Display for TuiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TuiError: {}", self.msg)
+    }
+}
+impl From<io::Error> for TuiError {
+    fn from(value: io::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use bslib::bot::{Bot, StrategyBot};
+use bslib::placement::PlacementMode;
+use bslib::strategy::Difficulty;
+
+// bot taking part in a tournament, written as `difficulty` or `difficulty/placement`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entrant {
+    difficulty: Difficulty,
+    placement: PlacementMode,
+}
+impl Entrant {
+    pub fn new(difficulty: Difficulty, placement: PlacementMode) -> Self {
+        Self {
+            difficulty,
+            placement,
+        }
+    }
+
+    pub fn bot(&self, seed: u64) -> Box<dyn Bot> {
+        Box::new(StrategyBot::new(self.difficulty, self.placement).with_seed(seed))
+    }
+}
+impl Display for Entrant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.difficulty, self.placement)
+    }
+}
+impl FromStr for Entrant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (difficulty, placement) = match s.split_once('/') {
+            Some((difficulty, placement)) => (difficulty, placement.parse()?),
+            None => (s, PlacementMode::Uniform),
+        };
+        Ok(Self::new(difficulty.parse()?, placement))
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use bslib::game::ruleset::Ruleset;
+use clap::Parser;
+use entrant::Entrant;
+use tournament::{round_robin, standings, Matchup, Standing};
+
+mod entrant;
+mod stats;
+mod tournament;
+
+#[derive(Parser)]
+#[command(version, about = "Plays bots against each other and compares the results")]
+struct Cli {
+    /// Bots taking part, as `difficulty` or `difficulty/placement`
+    #[arg(long, value_delimiter = ',', default_value = "random,hunt-target,density")]
+    entrants: Vec<Entrant>,
+    /// Games played by every pair of bots
+    #[arg(long, default_value_t = 1000)]
+    games: usize,
+    /// Seed for all random decisions, the same seed gives the same results
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Rules every game is played with: classic, russian, large or house, with options like +salvo
+    #[arg(long, default_value = "classic", value_parser = parse_ruleset)]
+    ruleset: Ruleset,
+    /// Also write the results of every matchup to this csv file
+    #[arg(long)]
+    csv: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    if cli.entrants.len() < 2 {
+        println!("At least two entrants are needed");
+        return ExitCode::FAILURE;
+    }
+    let start = Instant::now();
+    let matchups = round_robin(&cli.entrants, cli.games, cli.seed, &cli.ruleset);
+    println!(
+        "Played {} {} games in {:.1?} (seed {})\n",
+        matchups.iter().map(Matchup::get_games).sum::<usize>(),
+        cli.ruleset.get_name(),
+        start.elapsed(),
+        cli.seed
+    );
+
+    print_matchups(&matchups);
+    println!();
+    print_standings(&standings(&cli.entrants, &matchups));
+
+    if let Some(path) = cli.csv {
+        if let Err(e) = fs::write(&path, to_csv(&matchups)) {
+            println!("Failed to write {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+        println!("\nResults saved to {}", path.display());
+    }
+    ExitCode::SUCCESS
+}
+
+// bots are only ever played against each other in pairs, so team and free-for-all games are out
+fn parse_ruleset(name: &str) -> Result<Ruleset, String> {
+    let ruleset = Ruleset::preset(name).ok_or_else(|| {
+        format!("unknown ruleset `{name}`, expected one of: {}", Ruleset::describe_presets())
+    })?;
+    match ruleset.get_players() {
+        2 => Ok(ruleset),
+        _ => Err(format!("`{name}` isn't played by two players, the harness only plays duels")),
+    }
+}
+
+// `mean ± ci`, or a dash when there's not enough data
+fn format_mean(values: &[usize]) -> String {
+    match (stats::mean(values), stats::mean_ci(values)) {
+        (Some(mean), Some(ci)) => format!("{mean:.1} ± {ci:.1}"),
+        (Some(mean), None) => format!("{mean:.1}"),
+        _ => String::from("-"),
+    }
+}
+
+fn format_rate(wins: usize, games: usize) -> String {
+    match stats::rate_ci(wins, games) {
+        Some(ci) => format!("{:.1}% ± {:.1}", 100.0 * wins as f64 / games as f64, 100.0 * ci),
+        None => String::from("-"),
+    }
+}
+
+fn print_matchups(matchups: &[Matchup]) {
+    println!(
+        "{:<24}{:<24}{:>16}{:>16}{:>16}",
+        "entrant", "opponent", "win rate", "shots to win", "opp. shots"
+    );
+    for matchup in matchups {
+        println!(
+            "{:<24}{:<24}{:>16}{:>16}{:>16}",
+            matchup.entrants[0].to_string(),
+            matchup.entrants[1].to_string(),
+            format_rate(matchup.wins[0], matchup.get_games()),
+            format_mean(&matchup.shots_to_win[0]),
+            format_mean(&matchup.shots_to_win[1]),
+        );
+        if matchup.forfeits > 0 {
+            println!("  {} games were forfeited", matchup.forfeits);
+        }
+    }
+}
+
+fn print_standings(standings: &[Standing]) {
+    println!("{:<6}{:<24}{:>8}{:>8}{:>16}{:>16}", "place", "entrant", "games", "wins", "win rate", "shots to win");
+    for (place, standing) in standings.iter().enumerate() {
+        println!(
+            "{:<6}{:<24}{:>8}{:>8}{:>16}{:>16}",
+            place + 1,
+            standing.entrant.to_string(),
+            standing.games,
+            standing.wins,
+            format_rate(standing.wins, standing.games),
+            format_mean(&standing.shots_to_win),
+        );
+    }
+}
+
+fn to_csv(matchups: &[Matchup]) -> String {
+    let optional = |value: Option<f64>| value.map(|value| format!("{value:.4}")).unwrap_or_default();
+    let mut csv = String::from(
+        "entrant,opponent,games,wins,opponent_wins,win_rate,win_rate_ci,mean_shots_to_win,mean_shots_to_win_ci,opponent_mean_shots_to_win,opponent_mean_shots_to_win_ci,forfeits\n",
+    );
+    for matchup in matchups {
+        let games = matchup.get_games();
+        csv += &format!(
+            "{},{},{},{},{},{:.4},{},{},{},{},{},{}\n",
+            matchup.entrants[0],
+            matchup.entrants[1],
+            games,
+            matchup.wins[0],
+            matchup.wins[1],
+            matchup.wins[0] as f64 / games.max(1) as f64,
+            optional(stats::rate_ci(matchup.wins[0], games)),
+            optional(stats::mean(&matchup.shots_to_win[0])),
+            optional(stats::mean_ci(&matchup.shots_to_win[0])),
+            optional(stats::mean(&matchup.shots_to_win[1])),
+            optional(stats::mean_ci(&matchup.shots_to_win[1])),
+            matchup.forfeits,
+        );
+    }
+    csv
+}
@@ -0,0 +1,49 @@
+// z value of a 95% confidence interval
+const Z_95: f64 = 1.96;
+
+pub fn mean(values: &[usize]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<usize>() as f64 / values.len() as f64)
+}
+
+// half width of the 95% confidence interval of the mean, using the normal approximation
+pub fn mean_ci(values: &[usize]) -> Option<f64> {
+    let mean = mean(values)?;
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let variance = values
+        .iter()
+        .map(|&value| (value as f64 - mean).powi(2))
+        .sum::<f64>()
+        / (n - 1.0);
+    Some(Z_95 * (variance / n).sqrt())
+}
+
+// half width of the 95% confidence interval of a win rate (Wald interval)
+pub fn rate_ci(successes: usize, trials: usize) -> Option<f64> {
+    if trials == 0 {
+        return None;
+    }
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    Some(Z_95 * (p * (1.0 - p) / n).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidence_intervals() {
+        assert_eq!(mean(&[]), None);
+        assert_eq!(mean(&[40, 50, 60]), Some(50.0));
+        let ci = mean_ci(&[40, 50, 60]).expect("three values are enough");
+        assert!((ci - Z_95 * (100.0f64 / 3.0).sqrt()).abs() < 1e-9);
+        assert_eq!(rate_ci(10, 10), Some(0.0));
+        assert!(rate_ci(50, 100).expect("there were trials") > rate_ci(500, 1000).expect("there were trials"));
+    }
+}
@@ -0,0 +1,212 @@
+use bslib::bot::Bot;
+use bslib::game::board::Board;
+use bslib::game::ruleset::Ruleset;
+use bslib::game::Game;
+use bslib::game_record::GameRecord;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::entrant::Entrant;
+
+// outcome of one game - `shots` is how many shots the winner needed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameResult {
+    pub winner: usize,
+    pub shots: usize,
+    // the loser broke the rules, e.g. fired at the same tile twice
+    pub forfeit: bool,
+}
+
+// play a game between two bots with the same rules code the server uses - the ruleset has to
+// be one for duels
+pub fn play_game(mut bots: [Box<dyn Bot>; 2], first: usize, ruleset: &Ruleset) -> GameResult {
+    assert_eq!(ruleset.get_players(), 2, "only duels can be played between two bots");
+    let mut boards = Vec::new();
+    for (player, bot) in bots.iter_mut().enumerate() {
+        let board = bot.place_fleet(ruleset).ok().map(|ships| Board::from_fleet(&ships, ruleset));
+        match board {
+            Some(Ok(board)) => boards.push(board),
+            _ => {
+                return GameResult {
+                    winner: 1 - player,
+                    shots: 0,
+                    forfeit: true,
+                }
+            }
+        }
+    }
+    let mut record = GameRecord::new(vec![String::from("a"), String::from("b")]);
+    record.set_ruleset(ruleset.clone());
+    let mut game = Game::from_record(record, boards, first);
+    let mut shots = [0; 2];
+
+    let winner = loop {
+        if let Some(winner) = game.get_winner() {
+            break winner;
+        }
+        let player = game.get_turn();
+        let (targets, fired) = match ruleset.is_salvo() {
+            true => {
+                let targets = bots[player].choose_salvo(game.get_salvo_size(player));
+                let fired = game.fire_salvo(player, 1 - player, &targets);
+                (targets, fired)
+            }
+            false => {
+                let target = bots[player].choose_shot();
+                (vec![target], game.fire(player, 1 - player, target).map(|result| vec![result]))
+            }
+        };
+        shots[player] += targets.len();
+        match fired {
+            Ok(results) => {
+                for (target, result) in targets.into_iter().zip(results) {
+                    bots[player].on_result(target, result);
+                    bots[1 - player].on_opponent_shot(target, result);
+                }
+            }
+            Err(_) => {
+                return GameResult {
+                    winner: 1 - player,
+                    shots: shots[1 - player],
+                    forfeit: true,
+                }
+            }
+        }
+    };
+    for (player, bot) in bots.iter_mut().enumerate() {
+        bot.on_game_over(player == winner);
+    }
+    GameResult {
+        winner,
+        shots: shots[winner],
+        forfeit: false,
+    }
+}
+
+// all games between two entrants
+#[derive(Debug, Clone)]
+pub struct Matchup {
+    pub entrants: [Entrant; 2],
+    pub wins: [usize; 2],
+    pub shots_to_win: [Vec<usize>; 2],
+    pub forfeits: usize,
+}
+impl Matchup {
+    pub fn get_games(&self) -> usize {
+        self.wins[0] + self.wins[1]
+    }
+}
+
+// each game gets its own seeds drawn from one generator, so a matchup can be replayed exactly,
+// and the first shot alternates to keep it fair
+pub fn play_matchup(entrants: [Entrant; 2], games: usize, seed: u64, ruleset: &Ruleset) -> Matchup {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut matchup = Matchup {
+        entrants,
+        wins: [0; 2],
+        shots_to_win: [Vec::new(), Vec::new()],
+        forfeits: 0,
+    };
+    for game in 0..games {
+        let bots = [entrants[0].bot(rng.gen()), entrants[1].bot(rng.gen())];
+        let result = play_game(bots, game % 2, ruleset);
+        matchup.wins[result.winner] += 1;
+        matchup.forfeits += result.forfeit as usize;
+        if !result.forfeit {
+            matchup.shots_to_win[result.winner].push(result.shots);
+        }
+    }
+    matchup
+}
+
+// every entrant plays every other one, matchups run in parallel
+pub fn round_robin(entrants: &[Entrant], games: usize, seed: u64, ruleset: &Ruleset) -> Vec<Matchup> {
+    let mut pairs = Vec::new();
+    for (i, &a) in entrants.iter().enumerate() {
+        for &b in &entrants[i + 1..] {
+            pairs.push([a, b]);
+        }
+    }
+    std::thread::scope(|scope| {
+        let handles = pairs
+            .into_iter()
+            .enumerate()
+            .map(|(index, pair)| {
+                // mixing in the index keeps matchups independent but reproducible
+                let seed = seed ^ (index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                scope.spawn(move || play_matchup(pair, games, seed, ruleset))
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("matchup thread panicked"))
+            .collect()
+    })
+}
+
+// totals of one entrant over the whole tournament
+#[derive(Debug, Clone)]
+pub struct Standing {
+    pub entrant: Entrant,
+    pub games: usize,
+    pub wins: usize,
+    pub shots_to_win: Vec<usize>,
+}
+
+// sorted from the best win rate
+pub fn standings(entrants: &[Entrant], matchups: &[Matchup]) -> Vec<Standing> {
+    let mut standings = entrants
+        .iter()
+        .map(|&entrant| Standing {
+            entrant,
+            games: 0,
+            wins: 0,
+            shots_to_win: Vec::new(),
+        })
+        .collect::<Vec<_>>();
+    for matchup in matchups {
+        for side in 0..2 {
+            let Some(standing) = standings
+                .iter_mut()
+                .find(|standing| standing.entrant == matchup.entrants[side])
+            else {
+                continue;
+            };
+            standing.games += matchup.get_games();
+            standing.wins += matchup.wins[side];
+            standing.shots_to_win.extend(&matchup.shots_to_win[side]);
+        }
+    }
+    standings.sort_by(|a, b| {
+        let rate = |standing: &Standing| standing.wins as f64 / standing.games.max(1) as f64;
+        rate(b).total_cmp(&rate(a))
+    });
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bslib::placement::PlacementMode;
+    use bslib::strategy::Difficulty;
+
+    #[test]
+    fn test_matchups_are_reproducible() {
+        let entrants = [
+            Entrant::new(Difficulty::Random, PlacementMode::Uniform),
+            Entrant::new(Difficulty::HuntTarget, PlacementMode::Uniform),
+        ];
+        let first = play_matchup(entrants, 10, 42, &Ruleset::classic());
+        let second = play_matchup(entrants, 10, 42, &Ruleset::classic());
+        assert_eq!(first.wins, second.wins);
+        assert_eq!(first.shots_to_win, second.shots_to_win);
+        assert_eq!(first.get_games(), 10);
+        assert_eq!(first.forfeits, 0);
+        assert!(first.wins[1] > first.wins[0]);
+        // bots follow whatever rules they're given
+        let large = play_matchup(entrants, 4, 42, &Ruleset::large());
+        assert_eq!(large.forfeits, 0);
+        let salvo = play_matchup(entrants, 4, 42, &Ruleset::classic().with_salvo(true));
+        assert_eq!(salvo.forfeits, 0);
+    }
+}
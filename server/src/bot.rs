@@ -0,0 +1,205 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use bslib::game::ruleset::Ruleset;
+use bslib::game::ShotResult;
+use bslib::placement::{random_fleet, PlacementMode};
+use bslib::strategy::{fill_salvo, Difficulty, Strategy};
+use bslib::tcp_protocol::{
+    FireBody, Packet, PacketBody, PlaceFleetBody, ProtocolCommand, Ready, SalvoBody,
+};
+use log::{error, info, warn};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::sync::Mutex;
+
+use crate::game_manager::server_game::Seat;
+use crate::game_manager::server_player::ServerPlayer;
+use crate::game_manager::GameManager;
+use crate::handlers;
+
+// after `wait` the empty seats of the player's game go to bots, unless someone took them in
+// the meantime
+pub async fn seat_after(wait: Duration, player: Arc<Mutex<ServerPlayer>>, game_manager: Arc<Mutex<GameManager>>) {
+    tokio::time::sleep(wait).await;
+    let mut manager = game_manager.lock().await;
+    while manager.is_waiting(&player) {
+        let nick = manager.next_bot_nick();
+        let (outbox, inbox) = mpsc::unbounded_channel();
+        let bot = Arc::new(Mutex::new(ServerPlayer::bot(&nick, outbox)));
+        let seat = Seat::new(bot.clone(), &*bot.lock().await);
+        let Some(seed) = manager.seat_bot(&player, seat) else {
+            return;
+        };
+        info!("{} took an empty seat", nick);
+        tokio::spawn(run(bot, inbox, game_manager.clone(), manager.get_bot_difficulty(), seed));
+    }
+}
+
+// what the bot knows about the boards at its game - its teammates' shots teach it as much as
+// its own, so there's a strategy for every seat even though only the opponents' ones are used
+struct Targets {
+    ruleset: Ruleset,
+    seat: usize,
+    strategies: Vec<Box<dyn Strategy>>,
+    // tiles fired at on every seat, for salvos the strategy cuts short
+    fired: Vec<Vec<[usize; 2]>>,
+    // ships sunk on every seat, a player is out once their whole fleet is, or once they leave
+    sunk: Vec<usize>,
+}
+impl Targets {
+    fn new(difficulty: Difficulty, ruleset: &Ruleset) -> Self {
+        Self {
+            ruleset: ruleset.clone(),
+            seat: 0,
+            strategies: (0..ruleset.get_players()).map(|_| difficulty.strategy(ruleset)).collect(),
+            fired: vec![Vec::new(); ruleset.get_players()],
+            sunk: vec![0; ruleset.get_players()],
+        }
+    }
+
+    fn is_opponent(&self, seat: usize) -> bool {
+        self.ruleset.get_team(seat) != self.ruleset.get_team(self.seat)
+    }
+
+    fn record(&mut self, opponent: usize, target: [usize; 2], result: ShotResult) {
+        if !self.is_opponent(opponent) {
+            return;
+        }
+        self.strategies[opponent].on_result(target, result);
+        self.fired[opponent].push(target);
+        if let ShotResult::Sunk(_) = result {
+            self.sunk[opponent] += 1;
+        }
+    }
+
+    // a player that left is out as if their whole fleet was sunk
+    fn remove(&mut self, seat: usize) {
+        self.sunk[seat] = self.ruleset.get_fleet().len();
+    }
+
+    // a single shot at the first opponent still afloat, or a salvo of `salvo` shots in games
+    // played with salvos
+    fn fire(&mut self, salvo: usize, rng: &mut StdRng) -> Option<PacketBody> {
+        let fleet = self.ruleset.get_fleet().len();
+        let opponent = (0..self.sunk.len()).find(|&seat| self.is_opponent(seat) && self.sunk[seat] < fleet)?;
+        let strategy = self.strategies[opponent].as_mut();
+        if salvo > 0 {
+            let mut targets = strategy.choose_salvo(salvo, rng);
+            fill_salvo(&mut targets, salvo, self.ruleset.get_size(), &self.fired[opponent]);
+            let body = SalvoBody::new(targets).with_opponent(opponent);
+            return Some(PacketBody::Salvo(Box::new(body)));
+        }
+        let target = strategy.choose_shot(rng)?;
+        Some(PacketBody::Fire(Box::new(FireBody::new(target).with_opponent(opponent))))
+    }
+}
+
+// the bot reacts to the packets a client would get and answers through the same handlers
+async fn run(
+    player: Arc<Mutex<ServerPlayer>>,
+    mut inbox: UnboundedReceiver<Packet<Ready>>,
+    game_manager: Arc<Mutex<GameManager>>,
+    difficulty: Difficulty,
+    seed: u64,
+) {
+    // replaced once the bot learns the rules of its game
+    let mut targets = Targets::new(difficulty, &Ruleset::default());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut placed = false;
+    while let Some(packet) = inbox.recv().await {
+        let Ok(body) = packet.get_body() else {
+            continue;
+        };
+        let reply = match body {
+            // the fleet goes out when the first other player is announced, in team games some
+            // seats may still be empty then
+            PacketBody::ConnectResp(body) if body.get_opponent() && !placed => {
+                placed = true;
+                targets = Targets::new(difficulty, body.get_ruleset());
+                // the ruleset was validated on connect, so this only happens when the search for a
+                // layout ran out of steps
+                let board = match random_fleet(body.get_ruleset(), PlacementMode::AntiAi, &mut rng) {
+                    Ok(board) => board,
+                    Err(e) => {
+                        error!("{e}");
+                        break;
+                    }
+                };
+                let body = PlaceFleetBody::new(board.get_ships().to_vec());
+                Some(PacketBody::PlaceFleet(Box::new(body)))
+            }
+            PacketBody::GameStart(body) => {
+                targets.seat = body.get_seat();
+                match body.is_your_turn() {
+                    true => targets.fire(body.get_salvo(), &mut rng),
+                    false => None,
+                }
+            }
+            PacketBody::FireResult(body) => {
+                targets.record(body.get_opponent(), body.get_target(), body.get_result());
+                match body.is_your_turn() {
+                    true => targets.fire(0, &mut rng),
+                    false => None,
+                }
+            }
+            PacketBody::SalvoResult(body) => {
+                for &(target, result) in body.get_shots() {
+                    targets.record(body.get_opponent(), target, result);
+                }
+                match body.is_your_turn() {
+                    true => targets.fire(body.get_salvo(), &mut rng),
+                    false => None,
+                }
+            }
+            // the bot sticks to plain shots, weapons only come from the other players
+            PacketBody::WeaponResult(body) => {
+                for &(target, result) in body.get_strike().get_shots() {
+                    targets.record(body.get_opponent(), target, result);
+                }
+                match body.is_your_turn() {
+                    true => targets.fire(0, &mut rng),
+                    false => None,
+                }
+            }
+            // the bot keeps its ships where they are, only the other players move theirs
+            PacketBody::MoveResult(body) => match body.is_your_turn() {
+                true => targets.fire(0, &mut rng),
+                false => None,
+            },
+            PacketBody::PlayerLeft(body) => {
+                targets.remove(body.get_player());
+                match body.is_your_turn() {
+                    true => targets.fire(body.get_salvo(), &mut rng),
+                    false => None,
+                }
+            }
+            // the bot only makes legal moves, so this is a bug worth knowing about
+            PacketBody::Error(body) => {
+                warn!("bot's move was rejected: {}", body.get_msg());
+                break;
+            }
+            PacketBody::GameOver(_) | PacketBody::Shutdown(_) => break,
+            _ => None,
+        };
+        let Some(body) = reply else {
+            continue;
+        };
+        // the bot never connects, so it doesn't go through handle_packet, which could seat
+        // another bot and make this future recursive
+        let packet = Packet::from_body(body);
+        let handled = match packet.get_cmd() {
+            ProtocolCommand::PlaceFleet => {
+                handlers::handle_place_fleet_cmd(packet, player.clone(), &game_manager).await
+            }
+            ProtocolCommand::Salvo => handlers::handle_salvo_cmd(packet, player.clone(), &game_manager).await,
+            _ => handlers::handle_fire_cmd(packet, player.clone(), &game_manager).await,
+        };
+        if let Err(e) = handled {
+            error!("{e}");
+            break;
+        }
+    }
+    game_manager.lock().await.remove_player(&player);
+}
@@ -1,6 +1,5 @@
 use crate::handlers::error::HandlersModError;
 use core::fmt::Display;
-use std::sync::TryLockError;
 use bslib::tcp_protocol::error::PacketError;
 use tokio::io;
 use tokio::sync::mpsc::error::SendError;
@@ -35,13 +34,6 @@ impl<T> From<SendError<T>> for HandlingError {
         }
     }
 }
-impl<T> From<TryLockError<T>> for HandlingError {
-    fn from(value: TryLockError<T>) -> Self {
-        Self {
-            msg: format!("{value:}"),
-        }
-    }
-}
 impl From<PacketError> for HandlingError {
     fn from(value: PacketError) -> Self {
         Self {
@@ -70,4 +62,11 @@ impl From<bslib::tcp_protocol::error::PacketReaderError> for HandlingError {
         }
     }
 }
-impl std::error::Error for HandlingError {}
+impl From<serde_json::Error> for HandlingError {
+    fn from(value: serde_json::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::error::Error for HandlingError {}
\ No newline at end of file
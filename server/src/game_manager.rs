@@ -1,29 +1,313 @@
-use std::sync::{Arc, Mutex};
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use server_game::ServerGame;
+use bslib::game::movement::Direction;
+use bslib::game::ruleset::Ruleset;
+use bslib::game::weapon::Weapon;
+use bslib::game_record::PlacedShip;
+use bslib::strategy::Difficulty;
+use error::GameManagerError;
+use log::{debug, error, info};
+use server_game::{Seat, ServerGame};
 use server_player::ServerPlayer;
+use tokio::sync::Mutex;
 
-mod server_game;
+pub mod error;
+pub mod server_game;
 pub mod server_player;
 
 #[derive(Debug, Default)]
 pub struct GameManager {
     games: Vec<ServerGame>,
+    max_games: usize,
+    // rules for players that don't ask for a preset
+    ruleset: Ruleset,
+    next_game_id: usize,
+    bot_wait: Option<Duration>,
+    bot_difficulty: Difficulty,
+    next_bot_id: usize,
+    // pinned seed every game uses, each game gets a random one otherwise
+    seed: Option<u64>,
+    // set once the server starts shutting down - running games then outlive their players,
+    // so they can still be saved
+    shutting_down: bool,
+    stats: Stats,
 }
 impl GameManager {
-    fn create_game(&mut self, player: Arc<Mutex<ServerPlayer>>) {
-        self.games.push(ServerGame::new(player));
+    pub fn new(max_games: usize) -> Self {
+        Self {
+            max_games,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_ruleset(mut self, ruleset: Ruleset) -> Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    // rules for a player asking for the given preset, the default ones when they don't ask
+    pub fn find_ruleset(&self, preset: Option<&str>) -> Result<Ruleset, GameManagerError> {
+        let Some(name) = preset else {
+            return Ok(self.ruleset.clone());
+        };
+        Ruleset::preset(name).ok_or_else(|| {
+            GameManagerError::new(&format!(
+                "Unknown ruleset `{name}`, expected one of: {}",
+                Ruleset::describe_presets()
+            ))
+        })
+    }
+
+    // seat a bot with players that waited `wait` without anyone joining them
+    pub fn with_bots(mut self, wait: Duration, difficulty: Difficulty) -> Self {
+        self.bot_wait = Some(wait);
+        self.bot_difficulty = difficulty;
+        self
+    }
+
+    // games play out the same way every time, to reproduce a bug or a disputed game
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn get_bot_wait(&self) -> Option<Duration> {
+        self.bot_wait
+    }
+
+    pub fn get_bot_difficulty(&self) -> Difficulty {
+        self.bot_difficulty
+    }
+
+    pub fn get_stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn begin_shutdown(&mut self) {
+        self.shutting_down = true;
     }
 
-    pub fn assign_player(&mut self, player: Arc<Mutex<ServerPlayer>>) {
+    fn create_game(&mut self, seat: Seat, ruleset: Ruleset) -> Result<(), GameManagerError> {
+        if self.games.len() >= self.max_games {
+            return Err(GameManagerError::new(
+                "Server has reached the maximum number of games",
+            ));
+        }
+        let seed = self.seed.unwrap_or_else(rand::random);
+        self.games.push(ServerGame::new(self.next_game_id, seat, ruleset, seed));
+        self.next_game_id += 1;
+        Ok(())
+    }
+
+    // returns whether the player took the last empty seat of a game with the same rules -
+    // everyone at the game is told about it through their outboxes
+    pub fn assign_player(&mut self, seat: Seat, ruleset: Ruleset) -> Result<bool, GameManagerError> {
         for game in &mut self.games {
-            if game.has_empty_slot() {
-                game.add_opponent(player);
-                println!("assigned a player to a game, game_manager be like: {:#?}", self);
-                return;
+            if game.has_empty_slot() && *game.get_record().get_ruleset() == ruleset {
+                game.add_player(seat);
+                let full = !game.has_empty_slot();
+                debug!("assigned a player to a game, game_manager be like: {:#?}", self);
+                return Ok(full);
+            }
+        }
+        self.create_game(seat, ruleset)?;
+        debug!("assigned a player to a game, game_manager be like: {:#?}", self);
+        Ok(false)
+    }
+
+    // whether the player has a seat in any game, running or not
+    pub fn is_seated(&self, player: &Arc<Mutex<ServerPlayer>>) -> bool {
+        self.games.iter().any(|game| game.has_player(player))
+    }
+
+    // whether the player's game still has empty seats
+    pub fn is_waiting(&self, player: &Arc<Mutex<ServerPlayer>>) -> bool {
+        self.games
+            .iter()
+            .any(|game| game.has_player(player) && game.has_empty_slot())
+    }
+
+    // nick for the next bot - numbered so bots in different games can be told apart
+    pub fn next_bot_nick(&mut self) -> String {
+        self.next_bot_id += 1;
+        format!("bot-{}", self.next_bot_id)
+    }
+
+    // take an empty seat in a waiting player's game, returns the seed for the bot's choices,
+    // or None when the seats are gone
+    pub fn seat_bot(&mut self, player: &Arc<Mutex<ServerPlayer>>, bot: Seat) -> Option<u64> {
+        let game = self
+            .games
+            .iter_mut()
+            .find(|game| game.has_player(player) && game.has_empty_slot())?;
+        let seed = game.next_seed();
+        game.add_player(bot);
+        Some(seed)
+    }
+
+    pub fn place_fleet(&mut self, player: &Arc<Mutex<ServerPlayer>>, ships: &[PlacedShip]) -> Result<(), GameManagerError> {
+        let (game, index) = self.find_game(player)?;
+        game.place_fleet(index, ships)?;
+        Ok(())
+    }
+
+    pub fn fire(
+        &mut self,
+        player: &Arc<Mutex<ServerPlayer>>,
+        opponent: Option<usize>,
+        target: [usize; 2],
+    ) -> Result<(), GameManagerError> {
+        let (game, index) = self.find_game(player)?;
+        game.fire(index, opponent, target)?;
+        self.count_win(player);
+        Ok(())
+    }
+
+    // stats learn about the game the player's move has just won
+    fn count_win(&mut self, player: &Arc<Mutex<ServerPlayer>>) {
+        let game = self.games.iter().find(|game| game.has_player(player));
+        if let Some(game) = game.filter(|game| game.is_finished()) {
+            self.stats.add_finished(game);
+        }
+    }
+
+    pub fn salvo(
+        &mut self,
+        player: &Arc<Mutex<ServerPlayer>>,
+        opponent: Option<usize>,
+        targets: &[[usize; 2]],
+    ) -> Result<(), GameManagerError> {
+        let (game, index) = self.find_game(player)?;
+        game.salvo(index, opponent, targets)?;
+        self.count_win(player);
+        Ok(())
+    }
+
+    pub fn use_weapon(
+        &mut self,
+        player: &Arc<Mutex<ServerPlayer>>,
+        opponent: Option<usize>,
+        weapon: Weapon,
+        target: [usize; 2],
+    ) -> Result<(), GameManagerError> {
+        let (game, index) = self.find_game(player)?;
+        game.use_weapon(index, opponent, weapon, target)?;
+        self.count_win(player);
+        Ok(())
+    }
+
+    // a move never wins the game, so there's nothing to count
+    pub fn move_ship(
+        &mut self,
+        player: &Arc<Mutex<ServerPlayer>>,
+        tile: [usize; 2],
+        direction: Direction,
+    ) -> Result<(), GameManagerError> {
+        let (game, index) = self.find_game(player)?;
+        game.move_ship(index, tile, direction)?;
+        Ok(())
+    }
+
+    fn find_game(&mut self, player: &Arc<Mutex<ServerPlayer>>) -> Result<(&mut ServerGame, usize), GameManagerError> {
+        self.games
+            .iter_mut()
+            .find_map(|game| game.index_of(player).map(|index| (game, index)))
+            .ok_or(GameManagerError::new("You are not in a game"))
+    }
+
+    // whether the player is in a running game that has all its seats taken
+    pub fn is_playing(&self, player: &Arc<Mutex<ServerPlayer>>) -> bool {
+        self.games.iter().any(|game| {
+            game.has_player(player) && !game.has_empty_slot() && !game.is_finished()
+        })
+    }
+
+    // a game goes on without a player that leaves while other teams are still playing it - it
+    // ends once a single team is left, or when it hadn't started yet, unless the server is
+    // shutting down and the game is kept to be saved
+    pub fn remove_player(&mut self, player: &Arc<Mutex<ServerPlayer>>) {
+        for game in self.games.iter_mut().filter(|game| game.is_spectator(player)) {
+            game.leave(player);
+        }
+        let shutting_down = self.shutting_down;
+        let stats = &mut self.stats;
+        self.games.retain_mut(|game| {
+            if !game.has_player(player) || (shutting_down && !game.is_finished()) {
+                return true;
             }
+            let finished = game.is_finished();
+            let keep = game.forfeit(player);
+            if !keep {
+                info!("game {} has ended", game.get_id());
+            }
+            // a game that had started is won by whoever is left
+            if !finished && game.is_finished() {
+                stats.add_finished(game);
+            }
+            keep
+        });
+    }
+
+    // write records of the games that are still going, so they can be replayed later - a
+    // restarted server doesn't pick them up again
+    pub fn save_games(&self, dir: &Path) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or_default();
+        for game in self.games.iter().filter(|game| !game.has_empty_slot() && !game.is_finished()) {
+            let path = dir.join(format!("game-{}-{}.json", timestamp, game.get_id()));
+            match game.get_record().save(&path) {
+                Ok(()) => info!("saved game {} to {}", game.get_id(), path.display()),
+                Err(e) => error!("failed to save game {}: {e}", game.get_id()),
+            }
+        }
+    }
+}
+
+// results of the games finished since the server started
+#[derive(Debug, Default)]
+pub struct Stats {
+    games: usize,
+    bot_games: usize,
+    bot_wins: usize,
+}
+impl Stats {
+    // counts a game that has just been won, whether by sinking fleets or by the others leaving
+    fn add_finished(&mut self, game: &ServerGame) {
+        let record = game.get_record();
+        let winners = game.get_winners();
+        let nicks = |won: bool| {
+            let players = record.get_players().iter().enumerate();
+            let players = players.filter(|(index, _)| winners.contains(index) == won);
+            players.map(|(_, nick)| nick.as_str()).collect::<Vec<_>>().join(" and ")
+        };
+        info!("game {} was won by {} against {}", game.get_id(), nicks(true), nicks(false));
+        let bots = (0..record.get_players().len()).map(|index| record.is_bot(index)).collect::<Vec<_>>();
+        self.add_game(&bots, &winners);
+        info!("{}", self);
+    }
+
+    // a win counts for the bots when there was one on the winning team
+    fn add_game(&mut self, bots: &[bool], winners: &[usize]) {
+        self.games += 1;
+        if bots.contains(&true) {
+            self.bot_games += 1;
+        }
+        if winners.iter().any(|&winner| bots[winner]) {
+            self.bot_wins += 1;
         }
-        self.create_game(player);
-        println!("assigned a player to a game, game_manager be like: {:#?}", self);
+    }
+}
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} games finished, {} of them against bots, bots won {}",
+            self.games, self.bot_games, self.bot_wins
+        )
     }
 }
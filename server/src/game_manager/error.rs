@@ -0,0 +1,27 @@
+use bslib::game::error::GameError;
+use core::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct GameManagerError {
+    msg: String,
+}
+impl GameManagerError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for GameManagerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GameManagerError: {}", self.msg)
+    }
+}
+impl From<GameError> for GameManagerError {
+    fn from(value: GameError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::error::Error for GameManagerError {}
@@ -1,25 +1,367 @@
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
-use super::server_player::ServerPlayer;
+use bslib::game::board::Board;
+use bslib::game::error::GameError;
+use bslib::game::movement::Direction;
+use bslib::game::ruleset::Ruleset;
+use bslib::game::weapon::Weapon;
+use bslib::game::Game;
+use bslib::game_record::{GameRecord, PlacedShip};
+use bslib::tcp_protocol::{
+    ConnectRespBody, FireResultBody, GameOverBody, GameStartBody, MoveResultBody, Packet, PacketBody,
+    PlayerLeftBody, SalvoResultBody, WeaponResultBody,
+};
+use log::info;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::sync::Mutex;
+
+use super::server_player::{Outbox, ServerPlayer};
+
+// player sitting at a game - what the game needs to know about them without locking the player
+#[derive(Debug)]
+pub struct Seat {
+    player: Arc<Mutex<ServerPlayer>>,
+    nick: String,
+    outbox: Outbox,
+    bot: bool,
+    fleet: Option<Board>,
+    // a player that left while the game went on - the seat stays so the turns don't shift
+    left: bool,
+}
+impl Seat {
+    pub fn new(player: Arc<Mutex<ServerPlayer>>, server_player: &ServerPlayer) -> Self {
+        Self {
+            nick: String::from(server_player.get_nick()),
+            outbox: server_player.get_outbox(),
+            bot: server_player.is_bot(),
+            player,
+            fleet: None,
+            left: false,
+        }
+    }
+
+    pub fn get_nick(&self) -> &str {
+        &self.nick
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot
+    }
+
+    fn send(&self, body: PacketBody) {
+        let _ = self.outbox.send(Packet::from_body(body));
+    }
+}
 
 #[derive(Debug)]
 pub struct ServerGame {
-    player1: Arc<Mutex<ServerPlayer>>,
-    player2: Option<Arc<Mutex<ServerPlayer>>>,
+    id: usize,
+    // in the order players joined, which is also the order of their turns
+    seats: Vec<Seat>,
+    record: GameRecord,
+    game: Option<Game>,
+    // every random choice for the game comes from here, bots included, so the seed in the
+    // record is enough to make them again
+    rng: StdRng,
 }
 impl ServerGame {
-    pub fn new(player: Arc<Mutex<ServerPlayer>>) -> Self {
+    pub fn new(id: usize, seat: Seat, ruleset: Ruleset, seed: u64) -> Self {
+        let mut record = GameRecord::new(vec![String::new(); ruleset.get_players()]);
+        record.set_seed(seed);
+        record.set_player(0, seat.get_nick());
+        record.set_bot(0, seat.is_bot());
+        let body = ConnectRespBody::new(false).with_ruleset(ruleset.clone());
+        seat.send(PacketBody::ConnectResp(Box::new(body)));
+        record.set_ruleset(ruleset);
         ServerGame {
-            player1: player,
-            player2: None,
+            id,
+            seats: vec![seat],
+            record,
+            game: None,
+            rng: StdRng::seed_from_u64(seed),
         }
     }
 
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_record(&self) -> &GameRecord {
+        match &self.game {
+            Some(game) => game.get_record(),
+            None => &self.record,
+        }
+    }
+
+    // seed for a bot taking a seat at the game
+    pub fn next_seed(&mut self) -> u64 {
+        self.rng.gen()
+    }
+
+    pub fn get_seat(&self, index: usize) -> Option<&Seat> {
+        self.seats.get(index)
+    }
+
     pub fn has_empty_slot(&self) -> bool {
-        self.player2.is_none()
+        self.seats.len() < self.record.get_ruleset().get_players()
+    }
+
+    pub fn has_player(&self, player: &Arc<Mutex<ServerPlayer>>) -> bool {
+        self.index_of(player).is_some()
+    }
+
+    pub fn index_of(&self, player: &Arc<Mutex<ServerPlayer>>) -> Option<usize> {
+        self.seats
+            .iter()
+            .position(|seat| !seat.left && Arc::ptr_eq(&seat.player, player))
+    }
+
+    // whether the player's fleet was sunk while the others are still playing - they can keep
+    // watching the game or leave it without ending it
+    pub fn is_spectator(&self, player: &Arc<Mutex<ServerPlayer>>) -> bool {
+        match (&self.game, self.index_of(player)) {
+            (Some(game), Some(index)) => !self.is_finished() && game.is_eliminated(index),
+            _ => false,
+        }
+    }
+
+    pub fn leave(&mut self, player: &Arc<Mutex<ServerPlayer>>) {
+        if let Some(index) = self.index_of(player) {
+            info!("{} stopped watching game {}", self.seats[index].get_nick(), self.id);
+            self.seats[index].left = true;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.get_winner().is_some()
+    }
+
+    // team that won the game
+    pub fn get_winner(&self) -> Option<usize> {
+        self.game.as_ref().and_then(|game| game.get_winner())
+    }
+
+    // seats of the players on the team that won, empty while the game is on
+    pub fn get_winners(&self) -> Vec<usize> {
+        let Some(game) = &self.game else {
+            return Vec::new();
+        };
+        (0..self.seats.len()).filter(|&index| game.is_winner(index)).collect()
     }
 
-    pub fn add_opponent(&mut self, player: Arc<Mutex<ServerPlayer>>) {
-        self.player2 = Some(player);
+    // the new player learns who is already seated, and everyone seated learns about them
+    pub fn add_player(&mut self, seat: Seat) {
+        info!("{} joined {}'s game", seat.get_nick(), self.seats[0].get_nick());
+        let index = self.seats.len();
+        self.record.set_player(index, seat.get_nick());
+        self.record.set_bot(index, seat.is_bot());
+        let ruleset = self.record.get_ruleset();
+        for (other, sitting) in self.seats.iter().enumerate() {
+            let body = ConnectRespBody::with_opponent(String::from(sitting.get_nick()), sitting.is_bot());
+            seat.send(PacketBody::ConnectResp(Box::new(body.with_seat(other).with_ruleset(ruleset.clone()))));
+            let body = ConnectRespBody::with_opponent(String::from(seat.get_nick()), seat.is_bot());
+            sitting.send(PacketBody::ConnectResp(Box::new(body.with_seat(index).with_ruleset(ruleset.clone()))));
+        }
+        self.seats.push(seat);
+    }
+
+    // fleets can be placed while seats are still empty, the game starts once every seat is
+    // taken and every fleet is placed
+    pub fn place_fleet(&mut self, index: usize, ships: &[PlacedShip]) -> Result<(), GameError> {
+        if self.game.is_some() {
+            return Err(GameError::new("The game has already started"));
+        }
+        let board = Board::from_fleet(ships, self.record.get_ruleset())?;
+        let seat = &mut self.seats[index];
+        if seat.fleet.is_some() {
+            return Err(GameError::new("Ships were already placed"));
+        }
+        seat.fleet = Some(board);
+        if self.has_empty_slot() || self.seats.iter().any(|seat| seat.fleet.is_none()) {
+            return Ok(());
+        }
+
+        let boards = self
+            .seats
+            .iter_mut()
+            .map(|seat| seat.fleet.take().unwrap_or_default())
+            .collect();
+        let first = self.rng.gen_range(0..self.seats.len());
+        let game = Game::from_record(self.record.clone(), boards, first);
+        let salvo = self.record.get_ruleset().is_salvo();
+        info!("game {} has started with seed {}", self.id, self.record.get_seed().unwrap_or_default());
+        for (index, seat) in self.seats.iter().enumerate() {
+            let body = GameStartBody::new(index == first)
+                .with_salvo(if salvo { game.get_salvo_size(index) } else { 0 })
+                .with_arsenal(game.get_arsenal(index))
+                .with_seat(index);
+            seat.send(PacketBody::GameStart(Box::new(body)));
+        }
+        self.game = Some(game);
+        Ok(())
     }
-}
\ No newline at end of file
+
+    // every player gets the result, so teammates share what they learn about their opponents,
+    // followed by the end of the game when the shot sank the last enemy ship
+    pub fn fire(&mut self, index: usize, opponent: Option<usize>, target: [usize; 2]) -> Result<(), GameError> {
+        let Some(game) = &mut self.game else {
+            return Err(GameError::new("The game hasn't started yet"));
+        };
+        let opponent = pick_opponent(game, index, opponent)?;
+        let result = game.fire(index, opponent, target)?;
+        let turn = game.get_turn();
+        let winner = game.get_winner();
+        for (receiver, seat) in self.seats.iter().enumerate() {
+            let your_turn = winner.is_none() && turn == receiver;
+            let body = FireResultBody::new(receiver == index, target, result, your_turn).with_seats(index, opponent);
+            seat.send(PacketBody::FireResult(Box::new(body)));
+            self.send_game_over(seat, receiver);
+        }
+        Ok(())
+    }
+
+    // the whole salvo is resolved before anyone hears about it
+    pub fn salvo(&mut self, index: usize, opponent: Option<usize>, targets: &[[usize; 2]]) -> Result<(), GameError> {
+        let Some(game) = &mut self.game else {
+            return Err(GameError::new("The game hasn't started yet"));
+        };
+        let opponent = pick_opponent(game, index, opponent)?;
+        let results = game.fire_salvo(index, opponent, targets)?;
+        let shots = targets.iter().copied().zip(results).collect::<Vec<_>>();
+        let Some(game) = &self.game else {
+            return Ok(());
+        };
+        for (receiver, seat) in self.seats.iter().enumerate() {
+            let your_turn = game.get_winner().is_none() && game.get_turn() == receiver;
+            let body = SalvoResultBody::new(
+                receiver == index,
+                shots.clone(),
+                your_turn,
+                game.get_salvo_size(receiver),
+            );
+            seat.send(PacketBody::SalvoResult(Box::new(body.with_seats(index, opponent))));
+            self.send_game_over(seat, receiver);
+        }
+        Ok(())
+    }
+
+    // every player sees what the weapon did, a radar scan included
+    pub fn use_weapon(
+        &mut self,
+        index: usize,
+        opponent: Option<usize>,
+        weapon: Weapon,
+        target: [usize; 2],
+    ) -> Result<(), GameError> {
+        let Some(game) = &mut self.game else {
+            return Err(GameError::new("The game hasn't started yet"));
+        };
+        let opponent = pick_opponent(game, index, opponent)?;
+        let strike = game.use_weapon(index, opponent, weapon, target)?;
+        let Some(game) = &self.game else {
+            return Ok(());
+        };
+        for (receiver, seat) in self.seats.iter().enumerate() {
+            let your_turn = game.get_winner().is_none() && game.get_turn() == receiver;
+            let body = WeaponResultBody::new(receiver == index, weapon, target, strike.clone(), your_turn)
+                .with_arsenal(game.get_arsenal(receiver))
+                .with_seats(index, opponent);
+            seat.send(PacketBody::WeaponResult(Box::new(body)));
+            self.send_game_over(seat, receiver);
+        }
+        Ok(())
+    }
+
+    // the opponents only learn that some ship was moved, not which one or where
+    pub fn move_ship(&mut self, index: usize, tile: [usize; 2], direction: Direction) -> Result<(), GameError> {
+        let Some(game) = &mut self.game else {
+            return Err(GameError::new("The game hasn't started yet"));
+        };
+        game.move_ship(index, tile, direction)?;
+        let turn = game.get_turn();
+        let team = game.get_team(index);
+        for (receiver, seat) in self.seats.iter().enumerate() {
+            let body = MoveResultBody::new(receiver == index, turn == receiver).with_player(index);
+            let body = match self.record.get_ruleset().get_team(receiver) == team {
+                true => body.with_move(tile, direction),
+                false => body,
+            };
+            seat.send(PacketBody::MoveResult(Box::new(body)));
+        }
+        Ok(())
+    }
+
+    fn send_game_over(&self, seat: &Seat, receiver: usize) {
+        let Some(game) = &self.game else {
+            return;
+        };
+        if game.get_winner().is_none() {
+            return;
+        }
+        let players = self.get_record().get_players();
+        let winners = self.get_winners().into_iter().map(|index| players[index].as_str()).collect::<Vec<_>>();
+        let reason = match winners.len() {
+            1 => format!("{} sank the whole fleet", winners[0]),
+            _ => format!("{} sank every enemy fleet", winners.join(" and ")),
+        };
+        seat.send(PacketBody::GameOver(Box::new(GameOverBody::new(game.is_winner(receiver), reason))));
+    }
+
+    // a player leaving a running game sits out the rest of it, and the players that stay learn
+    // who gets the turn when it was theirs - returns whether the game goes on, which it does while more than one
+    // team has players left
+    pub fn forfeit(&mut self, leaving: &Arc<Mutex<ServerPlayer>>) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let Some(index) = self.index_of(leaving) else {
+            return true;
+        };
+        let Some(game) = &mut self.game else {
+            self.end(index);
+            return false;
+        };
+        let turn = game.get_turn();
+        game.forfeit(index);
+        if game.get_winner().is_some() {
+            self.end(index);
+            return false;
+        }
+        info!("{} left game {}, which goes on without them", self.seats[index].get_nick(), self.id);
+        let salvo = self.record.get_ruleset().is_salvo();
+        for (receiver, seat) in self.seats.iter().enumerate().filter(|&(receiver, _)| receiver != index) {
+            let your_turn = turn == index && game.get_turn() == receiver;
+            let body = PlayerLeftBody::new(index, your_turn)
+                .with_salvo(if salvo { game.get_salvo_size(receiver) } else { 0 });
+            seat.send(PacketBody::PlayerLeft(Box::new(body)));
+        }
+        self.seats[index].left = true;
+        true
+    }
+
+    // tell the players that stay that the game is over - before it started, it's won by
+    // everyone who wasn't on the leaving player's team
+    fn end(&self, index: usize) {
+        let reason = format!("{} has left the game", self.get_record().get_players()[index]);
+        let ruleset = self.record.get_ruleset();
+        for (receiver, seat) in self.seats.iter().enumerate().filter(|&(receiver, _)| receiver != index) {
+            let won = match &self.game {
+                Some(game) => game.is_winner(receiver),
+                None => ruleset.get_team(receiver) != ruleset.get_team(index),
+            };
+            seat.send(PacketBody::GameOver(Box::new(GameOverBody::new(won, reason.clone()))));
+        }
+    }
+}
+
+// duel clients don't say whom they fire at, there's only the one opponent to pick
+fn pick_opponent(game: &Game, player: usize, opponent: Option<usize>) -> Result<usize, GameError> {
+    if let Some(opponent) = opponent {
+        return Ok(opponent);
+    }
+    match game.get_opponents(player)[..] {
+        [opponent] => Ok(opponent),
+        _ => Err(GameError::new("Choose which opponent to fire at")),
+    }
+}
@@ -1,9 +1,50 @@
-#[derive(Debug, Default)]
+use bslib::tcp_protocol::{Packet, PacketBody, Ready};
+use tokio::sync::mpsc::UnboundedSender;
+
+// packets queued for a player - a connection writes them to its stream, a bot reads them itself
+pub type Outbox = UnboundedSender<Packet<Ready>>;
+
+#[derive(Debug)]
 pub struct ServerPlayer {
-    nick: String
+    nick: String,
+    outbox: Outbox,
+    bot: bool,
 }
 impl ServerPlayer {
+    pub fn new(outbox: Outbox) -> Self {
+        Self {
+            nick: String::new(),
+            outbox,
+            bot: false,
+        }
+    }
+
+    pub fn bot(nick: &str, outbox: Outbox) -> Self {
+        Self {
+            nick: String::from(nick),
+            outbox,
+            bot: true,
+        }
+    }
+
     pub fn set_nick(&mut self, nick: &str) {
         self.nick = String::from(nick);
     }
-}
\ No newline at end of file
+
+    pub fn get_nick(&self) -> &str {
+        &self.nick
+    }
+
+    pub fn get_outbox(&self) -> Outbox {
+        self.outbox.clone()
+    }
+
+    pub fn is_bot(&self) -> bool {
+        self.bot
+    }
+
+    // a closed outbox means the player is already leaving, so there's no one to tell
+    pub fn send(&self, body: PacketBody) {
+        let _ = self.outbox.send(Packet::from_body(body));
+    }
+}
@@ -1,11 +1,14 @@
 use std::sync::Arc;
-use std::sync::Mutex;
 
-use error::ConnectError;
-use tokio::io::AsyncWriteExt;
-use tokio::io::WriteHalf;
-use tokio::net::TcpStream;
+use bslib::tcp_protocol::{ErrorBody, Packet, PacketBody, ProtocolCommand, Ready};
+use error::{ConnectError, PlayError};
+use log::debug;
+use tokio::sync::Mutex;
 
+use crate::bot;
+use crate::error::HandlingError;
+use crate::game_manager::error::GameManagerError;
+use crate::game_manager::server_game::Seat;
 use crate::game_manager::server_player::ServerPlayer;
 use crate::game_manager::GameManager;
 
@@ -15,16 +18,207 @@ pub async fn handle_unknown() -> Result<(), ConnectError> {
     Ok(())
 }
 
-pub async fn handle_connect_cmd<'a: 'b, 'b: 'c, 'c>(stream: &mut WriteHalf<TcpStream>, player: Arc<Mutex<ServerPlayer>>, game_manager: &'b Arc<Mutex<GameManager>>) -> Result<(), ConnectError> {
-    println!("handle_connect_cmd");
-    {
-        let mut game_manager = game_manager.try_lock()?;
-        game_manager.assign_player(player);
+// entry point for every request, whether it came from a connection or from a server-side bot
+pub async fn handle_packet(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), HandlingError> {
+    debug!("decode handler: {:#?}", packet.get_cmd());
+    match packet.get_cmd() {
+        ProtocolCommand::Connect => {
+            let body = packet.get_body()?;
+            let nick = body.get_nick()?;
+            {
+                let mut player = player.lock().await;
+                player.set_nick(nick);
+            }
+
+            handle_connect_cmd(player, body.get_ruleset()?, game_manager).await?
+        }
+        ProtocolCommand::PlaceFleet => handle_place_fleet_cmd(packet, player, game_manager).await?,
+        ProtocolCommand::Fire => handle_fire_cmd(packet, player, game_manager).await?,
+        ProtocolCommand::Salvo => handle_salvo_cmd(packet, player, game_manager).await?,
+        ProtocolCommand::Weapon => handle_weapon_cmd(packet, player, game_manager).await?,
+        ProtocolCommand::Move => handle_move_cmd(packet, player, game_manager).await?,
+        ProtocolCommand::Test => (),
+        ProtocolCommand::ConnectResp
+        | ProtocolCommand::Shutdown
+        | ProtocolCommand::GameStart
+        | ProtocolCommand::FireResult
+        | ProtocolCommand::SalvoResult
+        | ProtocolCommand::WeaponResult
+        | ProtocolCommand::MoveResult
+        | ProtocolCommand::PlayerLeft
+        | ProtocolCommand::GameOver
+        | ProtocolCommand::Error => {
+            return Err(HandlingError::new(
+                "Invalid request command - a response command has been provided",
+            ))
+        }
+    }
+    debug!("handler has finished");
+    Ok(())
+}
+
+pub async fn handle_connect_cmd(
+    player: Arc<Mutex<ServerPlayer>>,
+    preset: Option<&str>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), ConnectError> {
+    debug!("handle_connect_cmd");
+    let seat = Seat::new(player.clone(), &*player.lock().await);
+    let mut manager = game_manager.lock().await;
+    // a player gets one seat at a time, leaving ends the game they're in
+    if manager.is_seated(&player) {
+        drop(manager);
+        reject(Err(GameManagerError::new("You already have a seat in a game")), &player).await;
+        return Ok(());
+    }
+    // the player stays connected and can ask again for rules the server knows
+    let ruleset = match manager.find_ruleset(preset) {
+        Ok(ruleset) => ruleset,
+        Err(e) => {
+            drop(manager);
+            reject(Err(e), &player).await;
+            return Ok(());
+        }
+    };
+    let full = manager.assign_player(seat, ruleset)?;
+    // seats left to fill - bots take the ones that stay empty for too long
+    if let (false, Some(wait)) = (full, manager.get_bot_wait()) {
+        tokio::spawn(bot::seat_after(wait, player, game_manager.clone()));
     }
-    stream
-        .write_all("#bs connect_resp\n#end\n".as_bytes())
-        .await?;
-    stream.flush().await?;
-    println!("handle_connect_cmd finished");
+    debug!("handle_connect_cmd finished");
+    Ok(())
+}
+
+pub async fn handle_place_fleet_cmd(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), PlayError> {
+    let PacketBody::PlaceFleet(body) = packet.get_body()? else {
+        return Err(PlayError::new("Expected a place_fleet body"));
+    };
+    let result = game_manager.lock().await.place_fleet(&player, body.get_ships());
+    reject(result, &player).await;
+    Ok(())
+}
+
+pub async fn handle_fire_cmd(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), PlayError> {
+    let PacketBody::Fire(body) = packet.get_body()? else {
+        return Err(PlayError::new("Expected a fire body"));
+    };
+    let result = game_manager.lock().await.fire(&player, body.get_opponent(), body.get_target());
+    reject(result, &player).await;
+    Ok(())
+}
+
+pub async fn handle_salvo_cmd(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), PlayError> {
+    let PacketBody::Salvo(body) = packet.get_body()? else {
+        return Err(PlayError::new("Expected a salvo body"));
+    };
+    let result = game_manager.lock().await.salvo(&player, body.get_opponent(), body.get_targets());
+    reject(result, &player).await;
+    Ok(())
+}
+
+pub async fn handle_weapon_cmd(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), PlayError> {
+    let PacketBody::Weapon(body) = packet.get_body()? else {
+        return Err(PlayError::new("Expected a weapon body"));
+    };
+    let result = game_manager
+        .lock()
+        .await
+        .use_weapon(&player, body.get_opponent(), body.get_weapon(), body.get_target());
+    reject(result, &player).await;
+    Ok(())
+}
+
+pub async fn handle_move_cmd(
+    packet: Packet<Ready>,
+    player: Arc<Mutex<ServerPlayer>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+) -> Result<(), PlayError> {
+    let PacketBody::Move(body) = packet.get_body()? else {
+        return Err(PlayError::new("Expected a move body"));
+    };
+    let result = game_manager
+        .lock()
+        .await
+        .move_ship(&player, body.get_tile(), body.get_direction());
+    reject(result, &player).await;
     Ok(())
 }
+
+// moves breaking the rules don't end the connection, the player is told what was wrong
+async fn reject(result: Result<(), GameManagerError>, player: &Arc<Mutex<ServerPlayer>>) {
+    if let Err(e) = result {
+        debug!("rejected a move: {e}");
+        let body = PacketBody::Error(Box::new(ErrorBody::new(e.to_string())));
+        player.lock().await.send(body);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_seated_player_cant_connect_again() {
+        let game_manager = Arc::new(Mutex::new(GameManager::new(4)));
+        let (outbox, mut inbox) = mpsc::unbounded_channel();
+        let player = Arc::new(Mutex::new(ServerPlayer::new(outbox)));
+        player.lock().await.set_nick("alice");
+
+        handle_connect_cmd(player.clone(), None, &game_manager)
+            .await
+            .expect("failed to connect");
+        let seated = inbox.recv().await.expect("no response to the connect");
+        assert!(matches!(seated.get_body(), Ok(PacketBody::ConnectResp(_))));
+        handle_connect_cmd(player.clone(), Some("russian"), &game_manager)
+            .await
+            .expect("a second connect shouldn't drop the connection");
+        let rejected = inbox.recv().await.expect("no response to the second connect");
+        assert!(matches!(rejected.get_body(), Ok(PacketBody::Error(_))));
+        assert!(inbox.try_recv().is_err());
+    }
+
+    // only salvo results tell players how big their next salvo is, so salvos don't mix with
+    // weapon or move turns
+    #[tokio::test]
+    async fn test_salvo_only_takes_plain_turns() {
+        let game_manager = Arc::new(Mutex::new(GameManager::new(4)));
+        let (outbox, mut inbox) = mpsc::unbounded_channel();
+        let player = Arc::new(Mutex::new(ServerPlayer::new(outbox)));
+        player.lock().await.set_nick("alice");
+
+        for preset in [
+            "classic+salvo+weapons",
+            "house+weapons+salvo",
+            "classic+salvo+moving",
+            "large+moving+salvo",
+        ] {
+            handle_connect_cmd(player.clone(), Some(preset), &game_manager)
+                .await
+                .expect("rules the server refuses shouldn't drop the connection");
+            let rejected = inbox.recv().await.expect("no response to the connect");
+            assert!(matches!(rejected.get_body(), Ok(PacketBody::Error(_))));
+        }
+        assert!(!game_manager.lock().await.is_seated(&player));
+    }
+}
@@ -1,5 +1,6 @@
+use crate::game_manager::error::GameManagerError;
+use bslib::tcp_protocol::error::PacketError;
 use core::fmt::Display;
-use std::sync::TryLockError;
 use tokio::io;
 
 pub trait HandlersModError: std::error::Error {}
@@ -22,10 +23,50 @@ impl From<io::Error> for ConnectError {
         }
     }
 }
-impl<T> From<TryLockError<T>> for ConnectError {
-    fn from(value: TryLockError<T>) -> Self {
+
+impl From<PacketError> for ConnectError {
+    fn from(value: PacketError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<serde_json::Error> for ConnectError {
+    fn from(value: serde_json::Error) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl From<GameManagerError> for ConnectError {
+    fn from(value: GameManagerError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+#[derive(Debug, Clone)]
+pub struct PlayError {
+    msg: String,
+}
+impl PlayError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for PlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PlayError: {}", self.msg)
+    }
+}
+impl From<PacketError> for PlayError {
+    fn from(value: PacketError) -> Self {
         Self {
             msg: format!("{value:}"),
         }
     }
 }
+impl std::error::Error for PlayError {}
+impl HandlersModError for PlayError {}
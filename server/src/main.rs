@@ -1,111 +1,867 @@
-use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
 
-use bslib::tcp_protocol::{Packet, PacketReader, ProtocolCommand, Ready};
-use config::{Config, Environment};
+use bslib::tcp_protocol::{
+    Connection, Packet, PacketBody, PacketReader, ProtocolCommand, Ready, ShutdownBody,
+};
+use clap::Parser;
 use dotenv::dotenv;
 use error::HandlingError;
 use game_manager::server_player::ServerPlayer;
 use game_manager::GameManager;
-use serde::{Deserialize, Serialize};
-use tokio::io::{self, WriteHalf};
-use tokio::net::{TcpListener, TcpStream};
+use log::{debug, error, info, warn};
+use server_config::ServerConfig;
+use tokio::io::{self, AsyncRead, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::sync::mpsc;
-use tokio::sync::mpsc::Sender;
+use tokio::sync::mpsc::{Sender, UnboundedReceiver};
+use tokio::sync::Mutex;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
 
+#[cfg(not(unix))]
+type UnixListener = TcpListener;
+
+mod bot;
 mod error;
 pub mod game_manager;
 pub mod handlers;
+mod server_config;
 
-#[derive(Serialize, Deserialize)]
-pub struct ServerConfig {
-    pub server_addr: String,
+#[derive(Parser)]
+#[command(version, about = "Battleships game server")]
+struct Cli {
+    /// Config file, `server.toml` is used when it exists
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Validate the configuration and exit
+    #[arg(long)]
+    check_config: bool,
 }
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenv().ok();
-    let config = Config::builder()
-        .add_source(Environment::default().separator("__"))
-        .build()
-        .unwrap();
+    let cli = Cli::parse();
+    let config = match ServerConfig::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if cli.check_config {
+        println!("Configuration is valid:\n{config:#?}");
+        return ExitCode::SUCCESS;
+    }
+
+    env_logger::Builder::new()
+        .filter_level(config.get_log_level().unwrap_or(log::LevelFilter::Info))
+        .init();
+    if let Err(e) = std::fs::create_dir_all(&config.data_dir) {
+        error!("failed to create data directory {}: {e}", config.data_dir.display());
+        return ExitCode::FAILURE;
+    }
+    info!("using the {} ruleset", config.ruleset.preset);
 
-    let config: ServerConfig = config.try_deserialize().unwrap();
-    let listener = TcpListener::bind(config.server_addr)
+    let listener = TcpListener::bind(&config.server_addr)
         .await
         .expect("failed to create a listener");
+    info!("listening on {}", config.server_addr);
+    #[cfg(unix)]
+    let unix_listener = config.unix_socket.as_ref().map(|path| {
+        // a socket file left by a previous run would make bind fail
+        let _ = std::fs::remove_file(path);
+        info!("listening on {path}");
+        UnixListener::bind(path).expect("failed to create a unix socket listener")
+    });
+    #[cfg(not(unix))]
+    let unix_listener: Option<UnixListener> = None;
 
-
-    let game_manager = Arc::new(Mutex::new(GameManager::default()));
+    let mut game_manager = GameManager::new(config.max_games)
+        .with_ruleset(config.ruleset.get_ruleset())
+        .with_seed(config.seed);
+    if let Some(seed) = config.seed {
+        info!("every game uses the pinned seed {seed}");
+    }
+    if let Some(wait) = config.bots.get_wait() {
+        info!("bots join players waiting longer than {:?}", wait);
+        game_manager = game_manager.with_bots(wait, config.bots.get_difficulty());
+    }
+    let game_manager = Arc::new(Mutex::new(game_manager));
+    let idle_timeout = config.timeouts.get_idle();
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    let shutdown_signal = shutdown_signal();
+    tokio::pin!(shutdown_signal);
 
     loop {
-        let (stream, _) = listener
-            .accept()
-            .await
-            .expect("failed to establish a connection");
+        let stream = tokio::select! {
+            accepted = accept(&listener, unix_listener.as_ref()) => match accepted {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("failed to establish a connection: {e}");
+                    continue;
+                }
+            },
+            // reap finished connections so the set doesn't grow forever
+            Some(_) = connections.join_next() => continue,
+            _ = &mut shutdown_signal => break,
+        };
         let game_manager_clone = game_manager.clone();
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(stream, game_manager_clone).await {
-                println!("Error: {e}");
+        let shutdown_rx = shutdown_rx.clone();
+        connections.spawn(async move {
+            if let Err(e) = handle_connection(stream, game_manager_clone, idle_timeout, shutdown_rx).await {
+                error!("{e}");
             } else {
-                println!("Handled perfectly");
+                debug!("Handled perfectly");
             }
         });
     }
+
+    info!("shutting down, waiting up to {:?} for games to finish", config.timeouts.get_shutdown());
+    drop(listener);
+    #[cfg(unix)]
+    if let Some(path) = &config.unix_socket {
+        let _ = std::fs::remove_file(path);
+    }
+    game_manager.lock().await.begin_shutdown();
+    let _ = shutdown_tx.send(true);
+    let drained = tokio::time::timeout(config.timeouts.get_shutdown(), async {
+        while connections.join_next().await.is_some() {}
+    })
+    .await;
+    if drained.is_err() {
+        warn!("{} connections still open, closing them", connections.len());
+        connections.shutdown().await;
+    }
+
+    let game_manager = game_manager.lock().await;
+    game_manager.save_games(&config.data_dir);
+    info!("{}", game_manager.get_stats());
+    info!("server stopped");
+    ExitCode::SUCCESS
+}
+
+// resolves on ctrl-c, or on SIGTERM where it's available
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => (),
+            _ = terminate.recv() => (),
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+// wait for a connection on either of the listeners
+async fn accept(
+    listener: &TcpListener,
+    unix_listener: Option<&UnixListener>,
+) -> io::Result<Box<dyn Connection>> {
+    match unix_listener {
+        #[cfg(unix)]
+        Some(unix_listener) => tokio::select! {
+            accepted = listener.accept() => Ok(Box::new(accepted?.0)),
+            accepted = unix_listener.accept() => Ok(Box::new(accepted?.0)),
+        },
+        _ => Ok(Box::new(listener.accept().await?.0)),
+    }
 }
 
-async fn handle_connection(stream: TcpStream, game_manager: Arc<Mutex<GameManager>>) -> Result<(), HandlingError> {
-    println!("Handling connection");
+async fn handle_connection<S: AsyncRead + AsyncWrite + Send + 'static>(
+    stream: S,
+    game_manager: Arc<Mutex<GameManager>>,
+    idle_timeout: Duration,
+    shutdown: watch::Receiver<bool>,
+) -> Result<(), HandlingError> {
+    info!("Handling connection");
+    let (outbox, inbox) = mpsc::unbounded_channel();
+    let player = Arc::new(Mutex::new(ServerPlayer::new(outbox)));
+    let result = serve_player(stream, player.clone(), inbox, &game_manager, idle_timeout, shutdown).await;
+    game_manager.lock().await.remove_player(&player);
+    result
+}
+
+async fn serve_player<S: AsyncRead + AsyncWrite + Send + 'static>(
+    stream: S,
+    player: Arc<Mutex<ServerPlayer>>,
+    mut inbox: UnboundedReceiver<Packet<Ready>>,
+    game_manager: &Arc<Mutex<GameManager>>,
+    idle_timeout: Duration,
+    mut shutdown: watch::Receiver<bool>,
+) -> Result<(), HandlingError> {
     let (read_half, mut write_half) = tokio::io::split(stream);
 
     let (tx, mut rx) = mpsc::channel(128);
 
-    let player = Arc::new(Mutex::new(ServerPlayer::default()));
-
     let listener = tokio::spawn(async move {
         let packet_reader = PacketReader::new(io::BufReader::new(read_half));
-        listen_stream(packet_reader, tx).await
+        listen_stream(packet_reader, tx, idle_timeout).await
     });
 
-    while let Some(packet) = rx.recv().await {
-        println!("{:#?}", packet);
-        decode_handler(packet, &mut write_half, player.clone(), &game_manager).await?;
+    let mut notified = false;
+    loop {
+        tokio::select! {
+            packet = rx.recv() => {
+                let Some(packet) = packet else {
+                    break;
+                };
+                debug!("{:#?}", packet);
+                handlers::handle_packet(packet, player.clone(), game_manager).await?;
+            }
+            // packets for this player queued by its game
+            Some(packet) = inbox.recv() => {
+                write_half.write_all(&packet.as_bytes()?).await?;
+                write_half.flush().await?;
+            }
+            _ = shutdown.changed(), if !notified => {
+                notified = true;
+                send_shutdown(&mut write_half).await?;
+                // players in a running game get the rest of the deadline to finish it
+                if !game_manager.lock().await.is_playing(&player) {
+                    listener.abort();
+                    return Ok(());
+                }
+            }
+        }
     }
     listener.await??;
     Ok(())
 }
 
-async fn listen_stream(
-    mut packet_reader: PacketReader,
+async fn send_shutdown<S: AsyncWrite>(write_half: &mut WriteHalf<S>) -> Result<(), HandlingError> {
+    let reason = String::from("the server is shutting down, running games are saved to be replayed");
+    let body = PacketBody::Shutdown(Box::new(ShutdownBody::new(reason)));
+    let packet = Packet::new(ProtocolCommand::Shutdown).load_body(body)?;
+    write_half.write_all(&packet.as_bytes()?).await?;
+    write_half.flush().await?;
+    Ok(())
+}
+
+async fn listen_stream<S: AsyncRead>(
+    mut packet_reader: PacketReader<ReadHalf<S>>,
     tx: Sender<Packet<Ready>>,
+    idle_timeout: Duration,
 ) -> Result<(), HandlingError> {
-    while let Some(packet) = packet_reader.read_packet().await? {
-        println!("sending packet to handle_connection");
+    loop {
+        let packet = match tokio::time::timeout(idle_timeout, packet_reader.read_packet()).await {
+            Ok(packet) => packet?,
+            Err(_) => return Err(HandlingError::new("Connection closed after being idle for too long")),
+        };
+        let Some(packet) = packet else {
+            return Ok(());
+        };
+        debug!("sending packet to handle_connection");
         tx.send(packet).await?;
     }
-    Ok(())
 }
 
-async fn decode_handler<'a: 'b, 'b: 'c, 'c>(
-    packet: Packet<Ready>,
-    write_half: &mut WriteHalf<TcpStream>,
-    player: Arc<Mutex<ServerPlayer>>,
-    game_manager: &'b Arc<Mutex<GameManager>>
-) -> Result<(), HandlingError> {
-    println!("decode handler: {:#?}", packet.get_cmd());
-    match packet.get_cmd() {
-        ProtocolCommand::Connect => {
-            let body = packet.get_body()?;
-            let nick = body.get_nick()?;
-            {
-                let mut player = player.try_lock()?;
-                player.set_nick(nick);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bslib::game::ruleset::Ruleset;
+    use bslib::placement::{random_fleet, PlacementMode};
+    use bslib::strategy::Difficulty;
+    use bslib::game::board::{Board, BOARD_SIZE};
+    use bslib::game::movement::Direction;
+    use bslib::game::ShotResult;
+    use bslib::game_record::GameRecord;
+    use bslib::tcp_protocol::{ConnectBody, FireBody, MoveBody, PlaceFleetBody, Requester, SalvoBody};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use tokio::net::TcpStream;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_concurrent_connects() {
+        const CLIENTS: usize = 300;
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to create a listener");
+        let addr = listener.local_addr().expect("listener has no address");
+        let game_manager = Arc::new(Mutex::new(GameManager::new(CLIENTS)));
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(
+                    stream,
+                    game_manager.clone(),
+                    Duration::from_secs(30),
+                    shutdown_rx.clone(),
+                ));
             }
+        });
 
-            handlers::handle_connect_cmd(write_half, player, game_manager).await?
-        },
-        ProtocolCommand::Test => (),
-        ProtocolCommand::ConnectResp => return Err(HandlingError::new("Invalid request command - a response command has been provided"))
+        let mut clients = JoinSet::new();
+        for i in 0..CLIENTS {
+            clients.spawn(async move {
+                let stream = TcpStream::connect(addr).await.expect("failed to connect");
+                let (read_half, mut write_half) = tokio::io::split(stream);
+                let body = PacketBody::Connect(Box::new(ConnectBody::new(format!("player{i}"))));
+                let packet = Packet::new(ProtocolCommand::Connect)
+                    .load_body(body)
+                    .expect("failed to build a packet");
+                write_half
+                    .write_all(&packet.as_bytes().expect("failed to serialize a packet"))
+                    .await
+                    .expect("failed to send a packet");
+                let mut reader = PacketReader::new(io::BufReader::new(read_half));
+                let response = reader
+                    .read_packet()
+                    .await
+                    .expect("failed to read a response")
+                    .expect("connection closed without a response");
+                // the connection stays open so the game isn't ended before everyone joins
+                (response, reader, write_half)
+            });
+        }
+
+        let mut connections = Vec::new();
+        let mut joined_opponent = 0;
+        while let Some(client) = clients.join_next().await {
+            let (response, reader, write_half) = client.expect("client task failed");
+            match response.get_body().expect("response has no body") {
+                PacketBody::ConnectResp(body) => joined_opponent += body.get_opponent() as usize,
+                body => panic!("expected a connect response, got {body:?}"),
+            }
+            connections.push((reader, write_half));
+        }
+        assert_eq!(joined_opponent, CLIENTS / 2);
+    }
+
+    async fn send(requester: &mut Requester<TcpStream>, body: PacketBody) {
+        requester
+            .send(Packet::from_body(body))
+            .await
+            .expect("failed to send a packet");
+    }
+
+    async fn next_packet(requester: &mut Requester<TcpStream>) -> Packet<Ready> {
+        requester
+            .receive()
+            .await
+            .expect("failed to read a packet")
+            .expect("connection closed")
+    }
+
+    // server with bots joining quickly, taking a single connection
+    async fn connect_to_bot_server() -> Requester<TcpStream> {
+        connect_to_seeded_bot_server(None).await
+    }
+
+    async fn connect_to_seeded_bot_server(seed: Option<u64>) -> Requester<TcpStream> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to create a listener");
+        let addr = listener.local_addr().expect("listener has no address");
+        let game_manager = GameManager::new(4)
+            .with_bots(Duration::from_millis(50), Difficulty::Random)
+            .with_seed(seed);
+        let game_manager = Arc::new(Mutex::new(game_manager));
+        tokio::spawn(async move {
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            let (stream, _) = listener.accept().await.expect("failed to accept");
+            handle_connection(stream, game_manager, Duration::from_secs(30), shutdown_rx).await
+        });
+        let stream = TcpStream::connect(addr).await.expect("failed to connect");
+        Requester::new(stream)
+    }
+
+    fn connect(preset: &str) -> PacketBody {
+        let body = ConnectBody::new(String::from("alice")).with_ruleset(Some(String::from(preset)));
+        PacketBody::Connect(Box::new(body))
+    }
+
+    // waits for the bot and places a random fleet, returns the rules of the game
+    async fn join_bot(requester: &mut Requester<TcpStream>) -> Ruleset {
+        let waiting = next_packet(requester).await;
+        assert!(matches!(waiting.get_body(), Ok(PacketBody::ConnectResp(body)) if !body.get_opponent()));
+        let joined = next_packet(requester).await;
+        let Ok(PacketBody::ConnectResp(joined)) = joined.get_body() else {
+            panic!("expected a connect response, got {joined:?}");
+        };
+        assert!(joined.is_bot());
+        joined.get_ruleset().clone()
+    }
+
+    async fn place_fleet(requester: &mut Requester<TcpStream>, ruleset: &Ruleset) -> Board {
+        let board = random_fleet(ruleset, PlacementMode::Uniform, &mut rand::thread_rng()).expect("fleet should fit");
+        let fleet = PlaceFleetBody::new(board.get_ships().to_vec());
+        send(requester, PacketBody::PlaceFleet(Box::new(fleet))).await;
+        board
+    }
+
+    // reads packets until the game is over, `respond` sees every other packet and returns what to
+    // send back, if anything - returns whether the game was won
+    async fn play_until_over(
+        requester: &mut Requester<TcpStream>,
+        mut respond: impl FnMut(&PacketBody) -> Option<PacketBody>,
+    ) -> bool {
+        loop {
+            let packet = next_packet(requester).await;
+            let body = packet.get_body().expect("packet has no body");
+            if let PacketBody::GameOver(body) = body {
+                return body.is_won();
+            }
+            if let Some(reply) = respond(body) {
+                send(requester, reply).await;
+            }
+        }
+    }
+
+    fn fire(target: [usize; 2]) -> PacketBody {
+        PacketBody::Fire(Box::new(FireBody::new(target)))
+    }
+
+    #[tokio::test]
+    async fn test_bot_takes_empty_seat() {
+        let mut requester = connect_to_bot_server().await;
+        // rules the server doesn't know are rejected, asking again works
+        send(&mut requester, connect("hexagonal")).await;
+        let rejected = next_packet(&mut requester).await;
+        assert!(matches!(rejected.get_body(), Ok(PacketBody::Error(_))));
+        send(&mut requester, connect("russian")).await;
+        let ruleset = join_bot(&mut requester).await;
+        assert_eq!(ruleset, Ruleset::russian());
+
+        // shots before the game starts are rejected without dropping the connection
+        send(&mut requester, fire([0, 0])).await;
+        let rejected = next_packet(&mut requester).await;
+        assert!(matches!(rejected.get_body(), Ok(PacketBody::Error(_))));
+
+        place_fleet(&mut requester, &ruleset).await;
+        let [height, width] = ruleset.get_size();
+        let mut targets = (0..height).flat_map(|i| (0..width).map(move |j| [i, j]));
+        play_until_over(&mut requester, |body| {
+            let your_turn = match body {
+                PacketBody::GameStart(body) => body.is_your_turn(),
+                PacketBody::FireResult(body) => body.is_your_turn(),
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            your_turn.then(|| fire(targets.next().expect("ran out of targets")))
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_salvo_game() {
+        let mut requester = connect_to_bot_server().await;
+        send(&mut requester, connect("classic+salvo")).await;
+        let ruleset = join_bot(&mut requester).await;
+        assert!(ruleset.is_salvo());
+        place_fleet(&mut requester, &ruleset).await;
+
+        let mut targets = (0..BOARD_SIZE).flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]));
+        let mut rejected = false;
+        loop {
+            let packet = next_packet(&mut requester).await;
+            let salvo = match packet.get_body() {
+                Ok(PacketBody::GameStart(body)) if body.is_your_turn() => body.get_salvo(),
+                Ok(PacketBody::SalvoResult(body)) if body.is_your_turn() => body.get_salvo(),
+                Ok(PacketBody::GameStart(_) | PacketBody::SalvoResult(_)) => continue,
+                Ok(PacketBody::GameOver(_)) => break,
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            assert!(salvo > 0);
+            // single shots and short salvos break the rules, but the turn isn't lost
+            if !rejected {
+                send(&mut requester, fire([0, 0])).await;
+                let packet = next_packet(&mut requester).await;
+                assert!(matches!(packet.get_body(), Ok(PacketBody::Error(_))));
+                let body = SalvoBody::new(vec![[0, 0]; salvo + 1]);
+                send(&mut requester, PacketBody::Salvo(Box::new(body))).await;
+                let packet = next_packet(&mut requester).await;
+                assert!(matches!(packet.get_body(), Ok(PacketBody::Error(_))));
+                rejected = true;
+            }
+            let salvo = targets.by_ref().take(salvo).collect();
+            send(&mut requester, PacketBody::Salvo(Box::new(SalvoBody::new(salvo)))).await;
+        }
+        assert!(rejected);
+    }
+
+    #[tokio::test]
+    async fn test_hit_means_another_shot() {
+        let mut requester = connect_to_bot_server().await;
+        send(&mut requester, connect("classic+extra-shot")).await;
+        let ruleset = join_bot(&mut requester).await;
+        assert!(ruleset.is_extra_shot());
+        place_fleet(&mut requester, &ruleset).await;
+
+        let mut targets = (0..BOARD_SIZE).flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]));
+        play_until_over(&mut requester, |body| {
+            let your_turn = match body {
+                PacketBody::GameStart(body) => body.is_your_turn(),
+                // whoever hit something fires again, unless it sank the last ship
+                PacketBody::FireResult(body) => {
+                    match body.get_result() {
+                        ShotResult::Miss => assert_eq!(body.is_your_turn(), !body.is_yours()),
+                        ShotResult::Hit => assert_eq!(body.is_your_turn(), body.is_yours()),
+                        ShotResult::Sunk(_) => (),
+                    }
+                    body.is_your_turn()
+                }
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            your_turn.then(|| fire(targets.next().expect("ran out of targets")))
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_moving_a_ship() {
+        let mut requester = connect_to_bot_server().await;
+        send(&mut requester, connect("classic+moving")).await;
+        let ruleset = join_bot(&mut requester).await;
+        assert!(ruleset.is_moving());
+        let mut board = place_fleet(&mut requester, &ruleset).await;
+
+        let mut targets = (0..BOARD_SIZE).flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]));
+        let mut moved = None;
+        let mut rejected = false;
+        play_until_over(&mut requester, |body| {
+            let your_turn = match body {
+                PacketBody::GameStart(body) => body.is_your_turn(),
+                PacketBody::FireResult(body) => {
+                    if !body.is_yours() {
+                        let _ = board.fire(body.get_target());
+                    }
+                    body.is_your_turn()
+                }
+                PacketBody::MoveResult(body) => {
+                    assert!(body.is_yours() && !body.is_your_turn());
+                    assert_eq!(body.get_move(), moved);
+                    false
+                }
+                // the turn isn't lost with a move that breaks the rules
+                PacketBody::Error(_) => {
+                    assert!(!rejected && moved.is_none());
+                    rejected = true;
+                    true
+                }
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            if !your_turn {
+                return None;
+            }
+            if !rejected {
+                // the server checks the move against its own copy of the board
+                let off_board = MoveBody::new([BOARD_SIZE, 0], Direction::Up);
+                return Some(PacketBody::Move(Box::new(off_board)));
+            }
+            if moved.is_none() {
+                // a single shot can't have damaged every ship, one of them can take a step
+                let (tile, direction) = board
+                    .get_ships()
+                    .iter()
+                    .flat_map(|ship| Direction::ALL.map(|direction| (ship.get_tiles()[0], direction)))
+                    .find(|&(tile, direction)| board.clone().move_ship(tile, direction).is_ok())
+                    .expect("no ship can move");
+                moved = Some((tile, direction));
+                return Some(PacketBody::Move(Box::new(MoveBody::new(tile, direction))));
+            }
+            Some(fire(targets.next().expect("ran out of targets")))
+        })
+        .await;
+        assert!(moved.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_team_game() {
+        let mut requester = connect_to_bot_server().await;
+        send(&mut requester, connect("classic+2v2")).await;
+        let waiting = next_packet(&mut requester).await;
+        assert!(matches!(waiting.get_body(), Ok(PacketBody::ConnectResp(body)) if !body.get_opponent()));
+        // bots fill all three empty seats, in the order they sit down
+        for seat in 1..4 {
+            let joined = next_packet(&mut requester).await;
+            assert!(matches!(joined.get_body(), Ok(PacketBody::ConnectResp(body)) if body.get_seat() == seat));
+        }
+        let ruleset = Ruleset::preset("classic+2v2").expect("preset should exist");
+        place_fleet(&mut requester, &ruleset).await;
+
+        let mut shot = vec![Vec::new(); 4];
+        let mut sunk = [0; 4];
+        let mut teammate_shots = 0;
+        let mut rejected = false;
+        play_until_over(&mut requester, |body| {
+            let your_turn = match body {
+                PacketBody::GameStart(body) => {
+                    assert_eq!(body.get_seat(), 0);
+                    body.is_your_turn()
+                }
+                PacketBody::FireResult(body) => {
+                    let [player, opponent] = [body.get_player(), body.get_opponent()];
+                    assert_ne!(ruleset.get_team(player), ruleset.get_team(opponent));
+                    // what the teammate learns about the opponents is shared
+                    teammate_shots += (player == 2) as usize;
+                    sunk[opponent] += matches!(body.get_result(), ShotResult::Sunk(_)) as usize;
+                    shot[opponent].push(body.get_target());
+                    body.is_your_turn()
+                }
+                PacketBody::Error(_) => {
+                    assert!(!rejected);
+                    rejected = true;
+                    true
+                }
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            if !your_turn {
+                return None;
+            }
+            // there are two opponents, so the shot has to say which one it's for
+            if !rejected {
+                return Some(fire([0, 0]));
+            }
+            let opponent = match sunk[1] < ruleset.get_fleet().len() {
+                true => 1,
+                false => 3,
+            };
+            // the teammate fires at the same boards, so some tiles are already taken care of
+            let target = (0..BOARD_SIZE)
+                .flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]))
+                .find(|tile| !shot[opponent].contains(tile))
+                .expect("ran out of targets");
+            let body = FireBody::new(target).with_opponent(opponent);
+            Some(PacketBody::Fire(Box::new(body)))
+        })
+        .await;
+        assert!(rejected);
+        assert!(teammate_shots > 0);
+    }
+
+    #[tokio::test]
+    async fn test_free_for_all_spectator() {
+        let mut requester = connect_to_bot_server().await;
+        send(&mut requester, connect("classic+ffa3")).await;
+        let waiting = next_packet(&mut requester).await;
+        assert!(matches!(waiting.get_body(), Ok(PacketBody::ConnectResp(body)) if !body.get_opponent()));
+        for seat in 1..3 {
+            let joined = next_packet(&mut requester).await;
+            assert!(matches!(joined.get_body(), Ok(PacketBody::ConnectResp(body)) if body.get_seat() == seat));
+        }
+        let ruleset = Ruleset::preset("classic+ffa3").expect("preset should exist");
+        place_fleet(&mut requester, &ruleset).await;
+
+        let mut shot = vec![Vec::new(); 3];
+        let mut sunk = [0; 3];
+        let mut watched = 0;
+        let won = play_until_over(&mut requester, |body| {
+            let your_turn = match body {
+                PacketBody::GameStart(body) => body.is_your_turn(),
+                PacketBody::FireResult(body) => {
+                    let [player, opponent] = [body.get_player(), body.get_opponent()];
+                    // results keep coming once our fleet is gone
+                    watched += (sunk[0] == ruleset.get_fleet().len()) as usize;
+                    sunk[opponent] += matches!(body.get_result(), ShotResult::Sunk(_)) as usize;
+                    shot[opponent].push(body.get_target());
+                    assert!(player != 0 || sunk[0] < ruleset.get_fleet().len());
+                    body.is_your_turn()
+                }
+                body => panic!("unexpected packet during the game: {body:?}"),
+            };
+            if !your_turn {
+                return None;
+            }
+            let opponent = match sunk[1] < ruleset.get_fleet().len() {
+                true => 1,
+                false => 2,
+            };
+            let target = (0..BOARD_SIZE)
+                .flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]))
+                .find(|tile| !shot[opponent].contains(tile))
+                .expect("ran out of targets");
+            let body = FireBody::new(target).with_opponent(opponent);
+            Some(PacketBody::Fire(Box::new(body)))
+        })
+        .await;
+        // both bots go after the first opponent they find, which is us
+        assert!(!won);
+        assert!(watched > 0);
+    }
+
+    #[tokio::test]
+    async fn test_free_for_all_goes_on_without_a_leaver() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to create a listener");
+        let addr = listener.local_addr().expect("listener has no address");
+        let game_manager = Arc::new(Mutex::new(GameManager::new(4)));
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(
+                    stream,
+                    game_manager.clone(),
+                    Duration::from_secs(30),
+                    shutdown_rx.clone(),
+                ));
+            }
+        });
+
+        // everyone sits down in the order they connect, carol on the last seat
+        let ruleset = Ruleset::preset("classic+ffa3").expect("preset should exist");
+        let mut requesters = Vec::new();
+        for nick in ["alice", "bob", "carol"] {
+            let stream = TcpStream::connect(addr).await.expect("failed to connect");
+            let mut requester = Requester::new(stream);
+            let preset = Some(String::from(ruleset.get_name()));
+            let body = ConnectBody::new(String::from(nick)).with_ruleset(preset);
+            send(&mut requester, PacketBody::Connect(Box::new(body))).await;
+            next_packet(&mut requester).await;
+            place_fleet(&mut requester, &ruleset).await;
+            requesters.push(requester);
+        }
+        let mut carol = requesters.pop().expect("carol has no connection");
+
+        // alice and bob only fire at each other, so the game waits for carol until she leaves
+        let mut players = JoinSet::new();
+        for mut requester in requesters {
+            players.spawn(async move {
+                let mut seat = 0;
+                let mut left = None;
+                let mut targets = (0..BOARD_SIZE).flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]));
+                let won = play_until_over(&mut requester, |body| {
+                    let your_turn = match body {
+                        PacketBody::ConnectResp(_) => false,
+                        PacketBody::GameStart(body) => {
+                            seat = body.get_seat();
+                            body.is_your_turn()
+                        }
+                        PacketBody::FireResult(body) => body.is_your_turn(),
+                        PacketBody::PlayerLeft(body) => {
+                            left = Some(body.get_player());
+                            body.is_your_turn()
+                        }
+                        body => panic!("unexpected packet during the game: {body:?}"),
+                    };
+                    let target = your_turn.then(|| targets.next().expect("ran out of targets"))?;
+                    Some(PacketBody::Fire(Box::new(FireBody::new(target).with_opponent(1 - seat))))
+                })
+                .await;
+                (won, left)
+            });
+        }
+        while !matches!(next_packet(&mut carol).await.get_body(), Ok(PacketBody::GameStart(_))) {}
+        drop(carol);
+
+        let mut outcomes = Vec::new();
+        while let Some(outcome) = players.join_next().await {
+            outcomes.push(outcome.expect("player task failed"));
+        }
+        assert!(outcomes.iter().all(|&(_, left)| left == Some(2)));
+        assert_eq!(outcomes.iter().filter(|&&(won, _)| won).count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_seed_repeats_the_game() {
+        let mut games = Vec::new();
+        for _ in 0..2 {
+            let mut requester = connect_to_seeded_bot_server(Some(7)).await;
+            send(&mut requester, connect("classic")).await;
+            let ruleset = join_bot(&mut requester).await;
+            let seeded = random_fleet(&ruleset, PlacementMode::Uniform, &mut StdRng::seed_from_u64(0));
+            let board = seeded.expect("fleet should fit");
+            let fleet = PlaceFleetBody::new(board.get_ships().to_vec());
+            send(&mut requester, PacketBody::PlaceFleet(Box::new(fleet))).await;
+
+            // who fires first and every shot the bot fires back
+            let mut first = None;
+            let mut bot_shots = Vec::new();
+            let mut targets = (0..BOARD_SIZE).flat_map(|i| (0..BOARD_SIZE).map(move |j| [i, j]));
+            play_until_over(&mut requester, |body| {
+                let your_turn = match body {
+                    PacketBody::GameStart(body) => *first.insert(body.is_your_turn()),
+                    PacketBody::FireResult(body) => {
+                        if !body.is_yours() {
+                            bot_shots.push(body.get_target());
+                        }
+                        body.is_your_turn()
+                    }
+                    body => panic!("unexpected packet during the game: {body:?}"),
+                };
+                your_turn.then(|| fire(targets.next().expect("ran out of targets")))
+            })
+            .await;
+            games.push((first, bot_shots));
+        }
+        assert!(!games[0].1.is_empty());
+        assert_eq!(games[0], games[1]);
+    }
+
+    #[tokio::test]
+    async fn test_leaving_counts_as_a_finished_game() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to create a listener");
+        let addr = listener.local_addr().expect("listener has no address");
+        let game_manager = GameManager::new(4).with_bots(Duration::from_millis(50), Difficulty::Random);
+        let game_manager = Arc::new(Mutex::new(game_manager));
+        let manager = game_manager.clone();
+        let connection = tokio::spawn(async move {
+            let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+            let (stream, _) = listener.accept().await.expect("failed to accept");
+            handle_connection(stream, manager, Duration::from_secs(30), shutdown_rx).await
+        });
+        let mut requester = Requester::new(TcpStream::connect(addr).await.expect("failed to connect"));
+        send(&mut requester, connect("classic")).await;
+        let ruleset = join_bot(&mut requester).await;
+        place_fleet(&mut requester, &ruleset).await;
+        while !matches!(next_packet(&mut requester).await.get_body(), Ok(PacketBody::GameStart(_))) {}
+
+        // alice leaves mid game, so the bot wins
+        drop(requester);
+        let _ = connection.await;
+        let stats = game_manager.lock().await.get_stats().to_string();
+        assert_eq!(stats, "1 games finished, 1 of them against bots, bots won 1");
+    }
+
+    #[tokio::test]
+    async fn test_games_are_saved_on_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to create a listener");
+        let addr = listener.local_addr().expect("listener has no address");
+        let game_manager = GameManager::new(4).with_bots(Duration::from_millis(50), Difficulty::Random);
+        let game_manager = Arc::new(Mutex::new(game_manager));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let manager = game_manager.clone();
+        let connection = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.expect("failed to accept");
+            handle_connection(stream, manager, Duration::from_secs(30), shutdown_rx).await
+        });
+        let mut requester = Requester::new(TcpStream::connect(addr).await.expect("failed to connect"));
+        send(&mut requester, connect("classic")).await;
+        let ruleset = join_bot(&mut requester).await;
+        place_fleet(&mut requester, &ruleset).await;
+        while !matches!(next_packet(&mut requester).await.get_body(), Ok(PacketBody::GameStart(_))) {}
+
+        // the client gives up as soon as it hears about the shutdown, the game stays to be saved
+        game_manager.lock().await.begin_shutdown();
+        shutdown_tx.send(true).expect("failed to signal the shutdown");
+        while !matches!(next_packet(&mut requester).await.get_body(), Ok(PacketBody::Shutdown(_))) {}
+        drop(requester);
+        let _ = connection.await;
+
+        let dir = std::env::temp_dir().join(format!("battleships-shutdown-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("failed to create a directory");
+        game_manager.lock().await.save_games(&dir);
+        let saved = std::fs::read_dir(&dir)
+            .expect("failed to read the directory")
+            .map(|entry| entry.expect("failed to read an entry").path())
+            .collect::<Vec<_>>();
+        let records = saved.iter().map(|path| GameRecord::load(path)).collect::<Vec<_>>();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(records.len(), 1);
+        let record = records[0].as_ref().expect("failed to load the saved game");
+        assert_eq!(record.get_players()[0], "alice");
     }
-    println!("handler has finished");
-    Ok(())
 }
@@ -0,0 +1,248 @@
+use std::net::ToSocketAddrs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use bslib::game::ruleset::Ruleset;
+use bslib::strategy::Difficulty;
+use config::{Config, Environment, File};
+use error::ConfigError;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+pub mod error;
+
+pub const DEFAULT_CONFIG_PATH: &str = "server.toml";
+const KEYS: [&str; 9] = [
+    "server_addr",
+    "unix_socket",
+    "max_games",
+    "data_dir",
+    "log_level",
+    "timeouts",
+    "ruleset",
+    "bots",
+    "seed",
+];
+
+// server settings - read from a toml file and overridden by environment variables
+// (nested keys use a double underscore, e.g. TIMEOUTS__IDLE=60)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub server_addr: String,
+    pub unix_socket: Option<String>,
+    pub max_games: usize,
+    pub data_dir: PathBuf,
+    pub log_level: String,
+    pub timeouts: Timeouts,
+    pub ruleset: RulesetConfig,
+    pub bots: BotsConfig,
+    // every game draws its random choices from this seed instead of a fresh one
+    pub seed: Option<u64>,
+}
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            server_addr: String::from("127.0.0.1:8000"),
+            unix_socket: None,
+            max_games: 100,
+            data_dir: PathBuf::from("data"),
+            log_level: String::from("info"),
+            timeouts: Timeouts::default(),
+            ruleset: RulesetConfig::default(),
+            bots: BotsConfig::default(),
+            seed: None,
+        }
+    }
+}
+impl ServerConfig {
+    // `path` is required only when it was given explicitly
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let file = match path {
+            Some(path) => File::from(path).required(true),
+            None => File::with_name(DEFAULT_CONFIG_PATH).required(false),
+        };
+        let config: Self = Config::builder()
+            .add_source(file)
+            .add_source(Self::environment())
+            .build()?
+            .try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // only variables matching our keys are used, anything else in the environment
+    // would be rejected as an unknown field
+    fn environment() -> Environment {
+        let vars = std::env::vars()
+            .filter(|(key, _)| {
+                let key = key.to_lowercase();
+                KEYS.iter()
+                    .any(|k| key == *k || key.starts_with(&format!("{k}__")))
+            })
+            .collect();
+        Environment::default()
+            .separator("__")
+            .try_parsing(true)
+            .source(Some(vars))
+    }
+
+    // report every invalid setting at once instead of stopping at the first one
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        // host names are fine too, as long as they resolve
+        if let Err(e) = self.server_addr.to_socket_addrs() {
+            problems.push(format!("server_addr `{}` is not a valid address: {e}", self.server_addr));
+        }
+        if let Some(path) = &self.unix_socket {
+            if path.is_empty() {
+                problems.push(String::from("unix_socket can't be an empty path"));
+            }
+        }
+        if self.max_games == 0 {
+            problems.push(String::from("max_games has to be greater than 0"));
+        }
+        if self.data_dir.is_file() {
+            problems.push(format!("data_dir `{}` is a file", self.data_dir.display()));
+        }
+        if self.get_log_level().is_none() {
+            problems.push(format!(
+                "log_level `{}` is not one of: off, error, warn, info, debug, trace",
+                self.log_level
+            ));
+        }
+        if self.timeouts.idle == 0 {
+            problems.push(String::from("timeouts.idle has to be greater than 0"));
+        }
+        if Ruleset::preset(&self.ruleset.preset).is_none() {
+            problems.push(format!(
+                "ruleset.preset `{}` is not one of: {}",
+                self.ruleset.preset,
+                Ruleset::describe_presets()
+            ));
+        }
+        if let Err(e) = Difficulty::from_str(&self.bots.difficulty) {
+            problems.push(format!("bots.difficulty: {e}"));
+        }
+
+        match problems.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError::new(&format!(
+                "invalid settings:\n  {}",
+                problems.join("\n  ")
+            ))),
+        }
+    }
+
+    pub fn get_log_level(&self) -> Option<LevelFilter> {
+        LevelFilter::from_str(&self.log_level).ok()
+    }
+}
+
+// all values are in seconds
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Timeouts {
+    pub idle: u64,
+    pub shutdown: u64,
+}
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            idle: 300,
+            shutdown: 30,
+        }
+    }
+}
+impl Timeouts {
+    pub fn get_idle(&self) -> Duration {
+        Duration::from_secs(self.idle)
+    }
+
+    pub fn get_shutdown(&self) -> Duration {
+        Duration::from_secs(self.shutdown)
+    }
+}
+
+// rules used for new games unless the players agree on something else
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RulesetConfig {
+    pub preset: String,
+}
+impl Default for RulesetConfig {
+    fn default() -> Self {
+        Self {
+            preset: String::from("classic"),
+        }
+    }
+}
+impl RulesetConfig {
+    pub fn get_ruleset(&self) -> Ruleset {
+        Ruleset::preset(&self.preset).unwrap_or_default()
+    }
+}
+
+// server-side opponents for players nobody joins
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BotsConfig {
+    // seconds a player waits alone before a bot joins, 0 turns bots off
+    pub wait: u64,
+    pub difficulty: String,
+}
+impl Default for BotsConfig {
+    fn default() -> Self {
+        Self {
+            wait: 30,
+            difficulty: String::from("hunt-target"),
+        }
+    }
+}
+impl BotsConfig {
+    pub fn get_wait(&self) -> Option<Duration> {
+        (self.wait > 0).then(|| Duration::from_secs(self.wait))
+    }
+
+    pub fn get_difficulty(&self) -> Difficulty {
+        Difficulty::from_str(&self.difficulty).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(ServerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_accepts_host_names() {
+        let config = ServerConfig {
+            server_addr: String::from("localhost:8000"),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_reports_all_problems() {
+        let config = ServerConfig {
+            server_addr: String::from("nowhere"),
+            max_games: 0,
+            log_level: String::from("loud"),
+            ruleset: RulesetConfig {
+                preset: String::from("hexagonal"),
+            },
+            ..Default::default()
+        };
+        let msg = config.validate().expect_err("config should be invalid").to_string();
+        assert!(msg.contains("server_addr"));
+        assert!(msg.contains("max_games"));
+        assert!(msg.contains("log_level"));
+        assert!(msg.contains("ruleset.preset"));
+    }
+}
@@ -0,0 +1,26 @@
+use core::fmt::Display;
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    msg: String,
+}
+impl ConfigError {
+    pub fn new(msg: &str) -> Self {
+        Self {
+            msg: String::from(msg),
+        }
+    }
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ConfigError: {}", self.msg)
+    }
+}
+impl From<config::ConfigError> for ConfigError {
+    fn from(value: config::ConfigError) -> Self {
+        Self {
+            msg: format!("{value:}"),
+        }
+    }
+}
+impl std::error::Error for ConfigError {}